@@ -0,0 +1,283 @@
+use super::{EvmPrecompileResult, Precompile};
+use crate::prelude::parameters::{CrossContractCallArgs, CrossContractCallPermission};
+#[cfg(feature = "contract")]
+use crate::prelude::{
+    parameters::{
+        CrossContractCallLog, CrossContractCallbackArgs, PromiseArgs, PromiseCreateArgs,
+        PromiseWithCallbackArgs,
+    },
+    sdk,
+    storage::{bytes_to_key, KeyPrefix},
+    vec, BorshDeserialize, BorshSerialize, ToString, Vec, U256,
+};
+use crate::prelude::{Address, BTreeMap, Cow};
+use crate::PrecompileOutput;
+use aurora_engine_types::account_id::AccountId;
+use core::cell::RefCell;
+#[cfg(feature = "contract")]
+use evm::backend::Log;
+use evm::{Context, ExitError};
+
+/// Method of the engine calling back an EVM contract with the result of a NEAR call.
+pub const CALLBACK_METHOD: &str = "cross_contract_call_callback";
+
+mod costs {
+    use crate::prelude::types::Gas;
+
+    // TODO(#51): Determine the correct amount of gas
+    pub(super) const CROSS_CONTRACT_CALL_GAS: Gas = 0;
+
+    // TODO(#332): Determine the correct amount of gas
+    #[cfg(feature = "contract")]
+    pub(super) const CALLBACK_GAS: Gas = 50_000_000_000_000;
+}
+
+/// Schedules a NEAR function call on behalf of the EVM contract calling it, within the bounds
+/// of the `CrossContractCallPermission` the owner gave to that contract. The input is a
+/// borsh-encoded `CrossContractCallArgs`.
+///
+/// The deposit allowance of the permission is consumed by the engine when it schedules the
+/// call, once the execution is over. Until then, the precompile keeps track of the deposits
+/// attached by each contract, so their sum stays within the allowance.
+pub struct CrossContractCall {
+    current_account_id: AccountId,
+    attached_balances: RefCell<BTreeMap<Address, u128>>,
+}
+
+impl CrossContractCall {
+    /// Cross-contract call precompile address
+    ///
+    /// Address: `0x516cded1d16af10cad47d6d49128e2eb7d27b372`
+    /// This address is computed as: `&keccak("nearCrossContractCall")[12..]`
+    pub const ADDRESS: Address =
+        super::make_address(0x516cded1, 0xd16af10cad47d6d49128e2eb7d27b372);
+
+    pub fn new(current_account_id: AccountId) -> Self {
+        Self {
+            current_account_id,
+            attached_balances: RefCell::new(BTreeMap::new()),
+        }
+    }
+
+    /// Checks the call is within the bounds of the permission of the caller, which already
+    /// attached `attached_balance` to its previous calls of the execution. The engine itself
+    /// cannot be called, as its private methods would accept the call.
+    pub fn check_call(
+        &self,
+        args: &CrossContractCallArgs,
+        permission: Option<&CrossContractCallPermission>,
+        attached_balance: u128,
+    ) -> Result<(), ExitError> {
+        let permission = permission.ok_or(ExitError::Other(Cow::from("ERR_XCC_NOT_ALLOWED")))?;
+        if args.call.target_account_id == self.current_account_id {
+            return Err(ExitError::Other(Cow::from("ERR_XCC_INVALID_TARGET")));
+        }
+        if args.call.attached_balance > permission.max_attached_balance {
+            return Err(ExitError::Other(Cow::from("ERR_XCC_BALANCE_TOO_HIGH")));
+        }
+        if args.call.attached_gas > permission.max_attached_gas {
+            return Err(ExitError::Other(Cow::from("ERR_XCC_GAS_TOO_HIGH")));
+        }
+        let total_attached_balance = attached_balance
+            .checked_add(args.call.attached_balance)
+            .filter(|total| *total <= permission.deposit_allowance);
+        if total_attached_balance.is_none() {
+            return Err(ExitError::Other(Cow::from("ERR_XCC_ALLOWANCE_EXCEEDED")));
+        }
+        Ok(())
+    }
+
+    /// Deposits attached by `caller` to the calls it scheduled so far in the execution.
+    pub fn attached_balance(&self, caller: &Address) -> u128 {
+        self.attached_balances
+            .borrow()
+            .get(caller)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    #[cfg(feature = "contract")]
+    fn record_attached_balance(&self, caller: Address, attached_balance: u128) {
+        let mut attached_balances = self.attached_balances.borrow_mut();
+        let total = attached_balances.entry(caller).or_insert(0);
+        *total = total.saturating_add(attached_balance);
+    }
+}
+
+#[cfg(feature = "contract")]
+fn get_permission(caller: &Address) -> Option<CrossContractCallPermission> {
+    use sdk::io::{StorageIntermediate, IO};
+    sdk::near_runtime::Runtime
+        .read_storage(&bytes_to_key(
+            KeyPrefix::CrossContractCall,
+            caller.as_bytes(),
+        ))
+        .and_then(|bytes| CrossContractCallPermission::try_from_slice(&bytes.to_vec()).ok())
+}
+
+impl Precompile for CrossContractCall {
+    fn required_gas(_input: &[u8]) -> Result<u64, ExitError> {
+        Ok(costs::CROSS_CONTRACT_CALL_GAS)
+    }
+
+    #[cfg(not(feature = "contract"))]
+    fn run(
+        &self,
+        input: &[u8],
+        target_gas: Option<u64>,
+        _context: &Context,
+        _is_static: bool,
+    ) -> EvmPrecompileResult {
+        if let Some(target_gas) = target_gas {
+            if Self::required_gas(input)? > target_gas {
+                return Err(ExitError::OutOfGas);
+            }
+        }
+
+        Ok(PrecompileOutput::default().into())
+    }
+
+    #[cfg(feature = "contract")]
+    fn run(
+        &self,
+        input: &[u8],
+        target_gas: Option<u64>,
+        context: &Context,
+        is_static: bool,
+    ) -> EvmPrecompileResult {
+        if let Some(target_gas) = target_gas {
+            if Self::required_gas(input)? > target_gas {
+                return Err(ExitError::OutOfGas);
+            }
+        }
+
+        // It's not allowed to schedule calls in static mode
+        if is_static {
+            return Err(ExitError::Other(Cow::from("ERR_INVALID_IN_STATIC")));
+        }
+        // The NEAR deposit is paid by the engine account, not with ETH
+        if context.apparent_value != U256::zero() {
+            return Err(ExitError::Other(Cow::from("ERR_ETH_ATTACHED_FOR_XCC")));
+        }
+
+        let args = CrossContractCallArgs::try_from_slice(input)
+            .map_err(|_| ExitError::Other(Cow::from("ERR_INVALID_XCC_ARGS")))?;
+        let caller = context.caller;
+        self.check_call(
+            &args,
+            get_permission(&caller).as_ref(),
+            self.attached_balance(&caller),
+        )?;
+        self.record_attached_balance(caller, args.call.attached_balance);
+
+        let promise = match args.callback {
+            None => PromiseArgs::Create(args.call),
+            Some(callback) => {
+                let callback_args = CrossContractCallbackArgs {
+                    caller: caller.0,
+                    callback,
+                };
+                PromiseArgs::Callback(PromiseWithCallbackArgs {
+                    base: args.call,
+                    callback: PromiseCreateArgs {
+                        target_account_id: self.current_account_id.clone(),
+                        method: CALLBACK_METHOD.to_string(),
+                        args: callback_args.try_to_vec().unwrap(),
+                        attached_balance: 0,
+                        attached_gas: costs::CALLBACK_GAS,
+                    },
+                })
+            }
+        };
+        let promise_log = Log {
+            address: Self::ADDRESS,
+            topics: Vec::new(),
+            data: CrossContractCallLog {
+                caller: caller.0,
+                promise,
+            }
+            .try_to_vec()
+            .unwrap(),
+        };
+
+        Ok(PrecompileOutput {
+            logs: vec![promise_log],
+            ..Default::default()
+        }
+        .into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CrossContractCall;
+    use crate::prelude::parameters::{
+        CrossContractCallArgs, CrossContractCallPermission, PromiseCreateArgs,
+    };
+    use crate::prelude::sdk::types::near_account_to_evm_address;
+    use crate::prelude::{Cow, ToString, TryFrom, Vec};
+    use aurora_engine_types::account_id::AccountId;
+    use evm::ExitError;
+
+    #[test]
+    fn test_precompile_id() {
+        assert_eq!(
+            CrossContractCall::ADDRESS,
+            near_account_to_evm_address("nearCrossContractCall".as_bytes())
+        );
+    }
+
+    #[test]
+    fn test_check_call() {
+        let engine = AccountId::try_from("aurora".to_string()).unwrap();
+        let precompile = CrossContractCall::new(engine.clone());
+        let permission = CrossContractCallPermission {
+            max_attached_balance: 1,
+            max_attached_gas: 10,
+            deposit_allowance: 3,
+        };
+        let args =
+            |target: &AccountId, attached_balance: u128, attached_gas: u64| CrossContractCallArgs {
+                call: PromiseCreateArgs {
+                    target_account_id: target.clone(),
+                    method: "method".to_string(),
+                    args: Vec::new(),
+                    attached_balance,
+                    attached_gas,
+                },
+                callback: None,
+            };
+        let error = |message: &'static str| Err(ExitError::Other(Cow::from(message)));
+        let target = AccountId::try_from("target.near".to_string()).unwrap();
+
+        assert_eq!(
+            precompile.check_call(&args(&target, 1, 10), Some(&permission), 0),
+            Ok(())
+        );
+        assert_eq!(
+            precompile.check_call(&args(&target, 1, 10), None, 0),
+            error("ERR_XCC_NOT_ALLOWED")
+        );
+        assert_eq!(
+            precompile.check_call(&args(&engine, 0, 0), Some(&permission), 0),
+            error("ERR_XCC_INVALID_TARGET")
+        );
+        assert_eq!(
+            precompile.check_call(&args(&target, 2, 10), Some(&permission), 0),
+            error("ERR_XCC_BALANCE_TOO_HIGH")
+        );
+        assert_eq!(
+            precompile.check_call(&args(&target, 1, 11), Some(&permission), 0),
+            error("ERR_XCC_GAS_TOO_HIGH")
+        );
+        // The deposits of the previous calls count against the allowance.
+        assert_eq!(
+            precompile.check_call(&args(&target, 1, 10), Some(&permission), 2),
+            Ok(())
+        );
+        assert_eq!(
+            precompile.check_call(&args(&target, 1, 10), Some(&permission), 3),
+            error("ERR_XCC_ALLOWANCE_EXCEEDED")
+        );
+    }
+}
@@ -6,21 +6,27 @@
 
 pub mod blake2;
 pub mod bn128;
+pub mod cross_contract_call;
 pub mod hash;
 pub mod identity;
 pub mod modexp;
 pub mod native;
+pub mod near_environment;
 mod prelude;
+pub mod promise_result;
 pub mod secp256k1;
 #[cfg(test)]
 mod utils;
 
 use crate::blake2::Blake2F;
 use crate::bn128::{Bn128Add, Bn128Mul, Bn128Pair};
+use crate::cross_contract_call::CrossContractCall;
 use crate::hash::{RIPEMD160, SHA256};
 use crate::identity::Identity;
 use crate::modexp::ModExp;
 use crate::native::{ExitToEthereum, ExitToNear};
+use crate::near_environment::NearEnvironment;
+use crate::promise_result::PromiseResults;
 use crate::secp256k1::ECRecover;
 use aurora_engine_types::{account_id::AccountId, Address, BTreeMap, Box};
 use evm::backend::Log;
@@ -128,13 +134,19 @@ impl Precompiles {
             RIPEMD160::ADDRESS,
             ExitToNear::ADDRESS,
             ExitToEthereum::ADDRESS,
+            CrossContractCall::ADDRESS,
+            PromiseResults::ADDRESS,
+            NearEnvironment::ADDRESS,
         ];
         let fun: prelude::Vec<Box<dyn Precompile>> = prelude::vec![
             Box::new(ECRecover),
             Box::new(SHA256),
             Box::new(RIPEMD160),
             Box::new(ExitToNear::new(current_account_id.clone())),
-            Box::new(ExitToEthereum::new(current_account_id)),
+            Box::new(ExitToEthereum::new(current_account_id.clone())),
+            Box::new(CrossContractCall::new(current_account_id)),
+            Box::new(PromiseResults),
+            Box::new(NearEnvironment::default()),
         ];
         let map: BTreeMap<Address, Box<dyn Precompile>> = addresses.into_iter().zip(fun).collect();
 
@@ -154,6 +166,9 @@ impl Precompiles {
             Bn128Pair::<Byzantium>::ADDRESS,
             ExitToNear::ADDRESS,
             ExitToEthereum::ADDRESS,
+            CrossContractCall::ADDRESS,
+            PromiseResults::ADDRESS,
+            NearEnvironment::ADDRESS,
         ];
         let fun: prelude::Vec<Box<dyn Precompile>> = prelude::vec![
             Box::new(ECRecover),
@@ -165,7 +180,10 @@ impl Precompiles {
             Box::new(Bn128Mul::<Byzantium>::new()),
             Box::new(Bn128Pair::<Byzantium>::new()),
             Box::new(ExitToNear::new(current_account_id.clone())),
-            Box::new(ExitToEthereum::new(current_account_id)),
+            Box::new(ExitToEthereum::new(current_account_id.clone())),
+            Box::new(CrossContractCall::new(current_account_id)),
+            Box::new(PromiseResults),
+            Box::new(NearEnvironment::default()),
         ];
         let map: BTreeMap<Address, Box<dyn Precompile>> = addresses.into_iter().zip(fun).collect();
 
@@ -185,6 +203,9 @@ impl Precompiles {
             Blake2F::ADDRESS,
             ExitToNear::ADDRESS,
             ExitToEthereum::ADDRESS,
+            CrossContractCall::ADDRESS,
+            PromiseResults::ADDRESS,
+            NearEnvironment::ADDRESS,
         ];
         let fun: prelude::Vec<Box<dyn Precompile>> = prelude::vec![
             Box::new(ECRecover),
@@ -197,7 +218,10 @@ impl Precompiles {
             Box::new(Bn128Pair::<Istanbul>::new()),
             Box::new(Blake2F),
             Box::new(ExitToNear::new(current_account_id.clone())),
-            Box::new(ExitToEthereum::new(current_account_id)),
+            Box::new(ExitToEthereum::new(current_account_id.clone())),
+            Box::new(CrossContractCall::new(current_account_id)),
+            Box::new(PromiseResults),
+            Box::new(NearEnvironment::default()),
         ];
         let map: BTreeMap<Address, Box<dyn Precompile>> = addresses.into_iter().zip(fun).collect();
 
@@ -217,6 +241,9 @@ impl Precompiles {
             Blake2F::ADDRESS,
             ExitToNear::ADDRESS,
             ExitToEthereum::ADDRESS,
+            CrossContractCall::ADDRESS,
+            PromiseResults::ADDRESS,
+            NearEnvironment::ADDRESS,
         ];
         let fun: prelude::Vec<Box<dyn Precompile>> = prelude::vec![
             Box::new(ECRecover),
@@ -229,7 +256,10 @@ impl Precompiles {
             Box::new(Bn128Pair::<Istanbul>::new()),
             Box::new(Blake2F),
             Box::new(ExitToNear::new(current_account_id.clone())),
-            Box::new(ExitToEthereum::new(current_account_id)),
+            Box::new(ExitToEthereum::new(current_account_id.clone())),
+            Box::new(CrossContractCall::new(current_account_id)),
+            Box::new(PromiseResults),
+            Box::new(NearEnvironment::default()),
         ];
         let map: BTreeMap<Address, Box<dyn Precompile>> = addresses.into_iter().zip(fun).collect();
 
@@ -240,6 +270,15 @@ impl Precompiles {
         // no precompile changes in London HF
         Self::new_berlin(current_account_id)
     }
+
+    /// Turns the precompiles into those of a view call, which has no NEAR transaction.
+    pub fn into_view(mut self) -> Self {
+        self.0.insert(
+            NearEnvironment::ADDRESS,
+            Box::new(NearEnvironment::new_view()),
+        );
+        self
+    }
 }
 
 /// const fn for making an address by concatenating the bytes from two given numbers,
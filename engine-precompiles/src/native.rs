@@ -1,13 +1,18 @@
 use super::{EvmPrecompileResult, Precompile};
-#[cfg(feature = "contract")]
 use crate::prelude::{
     format,
-    parameters::{PromiseArgs, PromiseCreateArgs, WithdrawCallArgs},
+    parameters::PromiseCreateArgs,
+    types::{json_string, Gas},
+    Cow, String, ToString, TryFrom, U256,
+};
+#[cfg(feature = "contract")]
+use crate::prelude::{
+    parameters::{PromiseArgs, WithdrawCallArgs},
     sdk,
     storage::{bytes_to_key, KeyPrefix},
-    vec, BorshSerialize, Cow, String, ToString, TryFrom, TryInto, Vec, H160, U256,
+    vec, BorshSerialize, TryInto, Vec, H160,
 };
-#[cfg(all(feature = "error_refund", feature = "contract"))]
+#[cfg(feature = "contract")]
 use crate::prelude::{
     parameters::{PromiseWithCallbackArgs, RefundCallArgs},
     types,
@@ -22,6 +27,9 @@ use evm::{Context, ExitError};
 
 const ERR_TARGET_TOKEN_NOT_FOUND: &str = "Target token not found";
 
+/// Method of the engine refunding the sender of an exit to NEAR whose transfer failed.
+pub const REFUND_ON_ERROR_METHOD: &str = "refund_on_error";
+
 mod costs {
     use crate::prelude::types::Gas;
 
@@ -35,7 +43,9 @@ mod costs {
     pub(super) const FT_TRANSFER_GAS: Gas = 100_000_000_000_000;
 
     // TODO(#332): Determine the correct amount of gas
-    #[cfg(feature = "error_refund")]
+    pub(super) const FT_TRANSFER_CALL_GAS: Gas = 150_000_000_000_000;
+
+    // TODO(#332): Determine the correct amount of gas
     pub(super) const REFUND_ON_ERROR_GAS: Gas = 60_000_000_000_000;
 
     // TODO(#332): Determine the correct amount of gas
@@ -43,12 +53,12 @@ mod costs {
 }
 
 pub mod events {
-    use crate::prelude::{vec, Address, String, ToString, H256, U256};
+    use crate::prelude::{vec, Address, String, ToString, Vec, H256, U256};
 
     /// Derived from event signature (see tests::test_exit_signatures)
     pub const EXIT_TO_NEAR_SIGNATURE: H256 = crate::make_h256(
-        0x5a91b8bc9c1981673db8fb226dbd8fcd,
-        0xd0c23f45cd28abb31403a5392f6dd0c7,
+        0x7ce281fb4fc5b8f508c4224fdb5a2404,
+        0x12771ebfaee89498d191404cebe3cb44,
     );
     /// Derived from event signature (see tests::test_exit_signatures)
     pub const EXIT_TO_ETH_SIGNATURE: H256 = crate::make_h256(
@@ -56,6 +66,17 @@ pub mod events {
         0x491373762eeac0831c48400e2d5a5f07,
     );
 
+    /// Derived from event signature (see tests::test_exit_signatures)
+    pub const EXIT_SCHEDULED_SIGNATURE: H256 = crate::make_h256(
+        0x1826938868de90441ceb29c8fd74a920,
+        0x91c8eeecaa58f4de05ef83e633d231e6,
+    );
+    /// Derived from event signature (see tests::test_exit_signatures)
+    pub const REFUNDED_SIGNATURE: H256 = crate::make_h256(
+        0x1c26abe6289671e3a9d493647349673a,
+        0xf828abb9dbadff1e81762387f7cd99a3,
+    );
+
     /// The exit precompile events have an `erc20_address` field to indicate
     /// which ERC-20 token is being withdrawn. However, ETH is not an ERC-20 token
     /// So we need to have some other address to fill this field. This constant is
@@ -66,22 +87,32 @@ pub mod events {
     ///    Address indexed sender,
     ///    Address indexed erc20_address,
     ///    string indexed dest,
-    ///    uint amount
+    ///    uint amount,
+    ///    string memo,
+    ///    string msg
     /// )
     /// Note: in the ERC-20 exit case `sender` == `erc20_address` because it is
     /// the ERC-20 contract which calls the exit precompile. However in the case
     /// of ETH exit the sender will give the true sender (and the `erc20_address`
     /// will not be meaningful because ETH is not an ERC-20 token).
+    /// The `memo` and `msg` are empty unless given in the extended input format,
+    /// and `msg` is non-empty if the tokens were sent with `ft_transfer_call`.
     pub struct ExitToNear {
         pub sender: Address,
         pub erc20_address: Address,
         pub dest: String,
         pub amount: U256,
+        pub memo: String,
+        pub msg: String,
     }
 
     impl ExitToNear {
         pub fn encode(self) -> ethabi::RawLog {
-            let data = ethabi::encode(&[ethabi::Token::Int(self.amount)]);
+            let data = ethabi::encode(&[
+                ethabi::Token::Int(self.amount),
+                ethabi::Token::String(self.memo),
+                ethabi::Token::String(self.msg),
+            ]);
             let topics = vec![
                 EXIT_TO_NEAR_SIGNATURE,
                 encode_address(self.sender),
@@ -124,6 +155,59 @@ pub mod events {
         }
     }
 
+    /// ExitScheduled(
+    ///    uint indexed exit_id
+    /// )
+    /// Logged by the engine, with the address of the exit to NEAR precompile, when it
+    /// schedules the transfer of an exit to NEAR. It precedes the `ExitToNear` event.
+    pub struct ExitScheduled {
+        pub exit_id: u64,
+    }
+
+    impl ExitScheduled {
+        pub fn encode(self) -> ethabi::RawLog {
+            let topics = vec![
+                EXIT_SCHEDULED_SIGNATURE,
+                H256::from_low_u64_be(self.exit_id),
+            ];
+
+            ethabi::RawLog {
+                topics,
+                data: Vec::new(),
+            }
+        }
+    }
+
+    /// Refunded(
+    ///    uint indexed exit_id,
+    ///    Address indexed recipient,
+    ///    Address indexed erc20_address,
+    ///    uint amount
+    /// )
+    /// Logged by the engine, with the address of the exit to NEAR precompile, when it
+    /// refunds the sender of an exit to NEAR. As in the `ExitToNear` event, `erc20_address`
+    /// is `ETH_ADDRESS` for ETH.
+    pub struct Refunded {
+        pub exit_id: u64,
+        pub recipient: Address,
+        pub erc20_address: Address,
+        pub amount: U256,
+    }
+
+    impl Refunded {
+        pub fn encode(self) -> ethabi::RawLog {
+            let data = ethabi::encode(&[ethabi::Token::Int(self.amount)]);
+            let topics = vec![
+                REFUNDED_SIGNATURE,
+                H256::from_low_u64_be(self.exit_id),
+                encode_address(self.recipient),
+                encode_address(self.erc20_address),
+            ];
+
+            ethabi::RawLog { topics, data }
+        }
+    }
+
     fn encode_address(a: Address) -> H256 {
         let mut result = [0u8; 32];
         result[12..].copy_from_slice(a.as_ref());
@@ -154,6 +238,57 @@ pub mod events {
                     kind: ethabi::ParamType::Uint(256),
                     indexed: false,
                 },
+                ethabi::EventParam {
+                    name: "memo".to_string(),
+                    kind: ethabi::ParamType::String,
+                    indexed: false,
+                },
+                ethabi::EventParam {
+                    name: "msg".to_string(),
+                    kind: ethabi::ParamType::String,
+                    indexed: false,
+                },
+            ],
+            anonymous: false,
+        }
+    }
+
+    pub fn exit_scheduled_schema() -> ethabi::Event {
+        ethabi::Event {
+            name: "ExitScheduled".to_string(),
+            inputs: vec![ethabi::EventParam {
+                name: "exit_id".to_string(),
+                kind: ethabi::ParamType::Uint(256),
+                indexed: true,
+            }],
+            anonymous: false,
+        }
+    }
+
+    pub fn refunded_schema() -> ethabi::Event {
+        ethabi::Event {
+            name: "Refunded".to_string(),
+            inputs: vec![
+                ethabi::EventParam {
+                    name: "exit_id".to_string(),
+                    kind: ethabi::ParamType::Uint(256),
+                    indexed: true,
+                },
+                ethabi::EventParam {
+                    name: "recipient".to_string(),
+                    kind: ethabi::ParamType::Address,
+                    indexed: true,
+                },
+                ethabi::EventParam {
+                    name: "erc20_address".to_string(),
+                    kind: ethabi::ParamType::Address,
+                    indexed: true,
+                },
+                ethabi::EventParam {
+                    name: "amount".to_string(),
+                    kind: ethabi::ParamType::Uint(256),
+                    indexed: false,
+                },
             ],
             anonymous: false,
         }
@@ -219,6 +354,180 @@ fn get_nep141_from_erc20(erc20_token: &[u8]) -> AccountId {
     .unwrap()
 }
 
+/// Lowest NEAR gas which can be attached to the transfer of an exit to NEAR.
+pub const MIN_EXIT_TO_NEAR_GAS: Gas = 10_000_000_000_000;
+/// Highest NEAR gas which can be attached to the transfer of an exit to NEAR.
+pub const MAX_EXIT_TO_NEAR_GAS: Gas = 200_000_000_000_000;
+
+/// Recipient of an exit to NEAR and how the tokens are transferred to it.
+///
+/// In the legacy input format the recipient account ID makes up the rest of the input. In the
+/// extended one, it is replaced with the ABI encoding of
+/// `(string receiver_id, string memo, string msg, uint64 gas)`. An empty `memo` is omitted, a
+/// non-empty `msg` makes the transfer an `ft_transfer_call` and a zero `gas` selects the
+/// default gas of the transfer.
+#[derive(Debug, PartialEq, Eq)]
+struct ExitToNearDestination {
+    receiver_id: AccountId,
+    memo: Option<String>,
+    msg: Option<String>,
+    gas: Option<Gas>,
+}
+
+impl ExitToNearDestination {
+    fn parse(input: &[u8], extended: bool) -> Result<Self, ExitError> {
+        if !extended {
+            let receiver_id = AccountId::try_from(input)
+                .map_err(|_| ExitError::Other(Cow::from("ERR_INVALID_RECEIVER_ACCOUNT_ID")))?;
+            return Ok(Self {
+                receiver_id,
+                memo: None,
+                msg: None,
+                gas: None,
+            });
+        }
+
+        let tokens = ethabi::decode(
+            &[
+                ethabi::ParamType::String,
+                ethabi::ParamType::String,
+                ethabi::ParamType::String,
+                ethabi::ParamType::Uint(64),
+            ],
+            input,
+        )
+        .map_err(|_| ExitError::Other(Cow::from("ERR_INVALID_EXIT_OPTIONS")))?;
+        let mut tokens = tokens.into_iter();
+        let mut next_string = || match tokens.next() {
+            Some(ethabi::Token::String(value)) => Ok(value),
+            _ => Err(ExitError::Other(Cow::from("ERR_INVALID_EXIT_OPTIONS"))),
+        };
+        let receiver_id = AccountId::try_from(next_string()?)
+            .map_err(|_| ExitError::Other(Cow::from("ERR_INVALID_RECEIVER_ACCOUNT_ID")))?;
+        let memo = Some(next_string()?).filter(|memo| !memo.is_empty());
+        let msg = Some(next_string()?).filter(|msg| !msg.is_empty());
+        let gas = match tokens.next() {
+            Some(ethabi::Token::Uint(gas)) if gas.is_zero() => None,
+            Some(ethabi::Token::Uint(gas))
+                if gas >= U256::from(MIN_EXIT_TO_NEAR_GAS)
+                    && gas <= U256::from(MAX_EXIT_TO_NEAR_GAS) =>
+            {
+                Some(gas.low_u64())
+            }
+            _ => return Err(ExitError::Other(Cow::from("ERR_INVALID_EXIT_GAS"))),
+        };
+
+        Ok(Self {
+            receiver_id,
+            memo,
+            msg,
+            gas,
+        })
+    }
+
+    /// The call transferring `amount` of the NEP-141 token `token_id` to the recipient.
+    fn transfer_promise(&self, token_id: AccountId, amount: u128) -> PromiseCreateArgs {
+        // There is no way to inject json: the receiver is a valid account id, the amount is
+        // encoded as decimal and the memo and msg are escaped.
+        let memo = self
+            .memo
+            .as_deref()
+            .map_or_else(|| "null".to_string(), json_string);
+        let (method, args, default_gas) = match &self.msg {
+            None => (
+                "ft_transfer",
+                format!(
+                    r#"{{"receiver_id": "{}", "amount": "{}", "memo": {}}}"#,
+                    self.receiver_id, amount, memo
+                ),
+                costs::FT_TRANSFER_GAS,
+            ),
+            Some(msg) => (
+                "ft_transfer_call",
+                format!(
+                    r#"{{"receiver_id": "{}", "amount": "{}", "memo": {}, "msg": {}}}"#,
+                    self.receiver_id,
+                    amount,
+                    memo,
+                    json_string(msg)
+                ),
+                costs::FT_TRANSFER_CALL_GAS,
+            ),
+        };
+        PromiseCreateArgs {
+            target_account_id: token_id,
+            method: method.to_string(),
+            args: args.into_bytes(),
+            attached_balance: 1,
+            attached_gas: self.gas.unwrap_or(default_gas),
+        }
+    }
+}
+
+/// Input of the exit to NEAR precompile.
+///
+/// The first byte of the input is a flag, whose bits select the behavior to be triggered:
+///      0x1 -> Erc20 transfer (Eth transfer otherwise)
+///      0x2 -> extended recipient format (see `ExitToNearDestination`)
+///      0x4 -> the address refunded if the transfer fails follows the flag
+/// Then, for an Erc20 transfer, comes the amount that was burned (U256 big-endian bytes), and
+/// the rest of the input is the recipient.
+///
+/// The legacy flags 0x0 and 0x1, used by the contracts deployed before the refunds, have no
+/// refund address: the caller of the precompile is refunded instead.
+#[derive(Debug, PartialEq, Eq)]
+struct ExitToNearInput<'a> {
+    is_erc20: bool,
+    extended: bool,
+    refund_address: Option<Address>,
+    /// Amount of an Erc20 transfer, the Eth transfers sending the attached value.
+    amount: U256,
+    recipient: &'a [u8],
+}
+
+impl<'a> ExitToNearInput<'a> {
+    fn parse(input: &'a [u8]) -> Result<Self, ExitError> {
+        let (flag, mut input) = input
+            .split_first()
+            .ok_or_else(|| ExitError::Other(Cow::from("ERR_INVALID_INPUT")))?;
+        if *flag > 0x7 {
+            return Err(ExitError::Other(Cow::from("ERR_INVALID_FLAG")));
+        }
+        let is_erc20 = flag & 0x1 != 0;
+        let extended = flag & 0x2 != 0;
+
+        let refund_address = if flag & 0x4 != 0 {
+            if input.len() < 20 {
+                return Err(ExitError::Other(Cow::from("ERR_INVALID_INPUT")));
+            }
+            let refund_address = Address::from_slice(&input[..20]);
+            input = &input[20..];
+            Some(refund_address)
+        } else {
+            None
+        };
+
+        let amount = if is_erc20 {
+            if input.len() < 32 {
+                return Err(ExitError::Other(Cow::from("ERR_INVALID_INPUT")));
+            }
+            let amount = U256::from_big_endian(&input[..32]);
+            input = &input[32..];
+            amount
+        } else {
+            U256::zero()
+        };
+
+        Ok(Self {
+            is_erc20,
+            extended,
+            refund_address,
+            amount,
+            recipient: input,
+        })
+    }
+}
+
 impl Precompile for ExitToNear {
     fn required_gas(_input: &[u8]) -> Result<u64, ExitError> {
         Ok(costs::EXIT_TO_NEAR_GAS)
@@ -249,16 +558,6 @@ impl Precompile for ExitToNear {
         context: &Context,
         is_static: bool,
     ) -> EvmPrecompileResult {
-        #[cfg(feature = "error_refund")]
-        fn parse_input(input: &[u8]) -> (Address, &[u8]) {
-            let refund_address = Address::from_slice(&input[1..21]);
-            (refund_address, &input[21..])
-        }
-        #[cfg(not(feature = "error_refund"))]
-        fn parse_input(input: &[u8]) -> &[u8] {
-            &input[1..]
-        }
-
         if let Some(target_gas) = target_gas {
             if Self::required_gas(input)? > target_gas {
                 return Err(ExitError::OutOfGas);
@@ -270,130 +569,75 @@ impl Precompile for ExitToNear {
             return Err(ExitError::Other(Cow::from("ERR_INVALID_IN_STATIC")));
         }
 
-        // First byte of the input is a flag, selecting the behavior to be triggered:
-        //      0x0 -> Eth transfer
-        //      0x1 -> Erc20 transfer
-        let flag = input[0];
-        #[cfg(feature = "error_refund")]
-        let (refund_address, mut input) = parse_input(input);
-        #[cfg(not(feature = "error_refund"))]
-        let mut input = parse_input(input);
+        let ExitToNearInput {
+            is_erc20,
+            extended,
+            refund_address,
+            amount,
+            recipient,
+        } = ExitToNearInput::parse(input)?;
         let current_account_id = self.current_account_id.clone();
-        #[cfg(feature = "error_refund")]
-        let refund_on_error_target = current_account_id.clone();
 
-        let (nep141_address, args, exit_event) = match flag {
-            0x0 => {
-                // ETH transfer
-                //
-                // Input slice format:
-                //      recipient_account_id (bytes) - the NEAR recipient account which will receive NEP-141 ETH tokens
+        let (nep141_address, sender, erc20_address, amount) = if !is_erc20 {
+            // ETH transfer
+            (
+                current_account_id,
+                context.caller,
+                events::ETH_ADDRESS,
+                context.apparent_value,
+            )
+        } else {
+            // ERC20 transfer
+            //
+            // This precompile branch is expected to be called from the ERC20 burn function
 
-                if let Ok(dest_account) = AccountId::try_from(input) {
-                    (
-                        current_account_id,
-                        // There is no way to inject json, given the encoding of both arguments
-                        // as decimal and valid account id respectively.
-                        format!(
-                            r#"{{"receiver_id": "{}", "amount": "{}", "memo": null}}"#,
-                            dest_account,
-                            context.apparent_value.as_u128()
-                        ),
-                        events::ExitToNear {
-                            sender: context.caller,
-                            erc20_address: events::ETH_ADDRESS,
-                            dest: dest_account.to_string(),
-                            amount: context.apparent_value,
-                        },
-                    )
-                } else {
-                    return Err(ExitError::Other(Cow::from(
-                        "ERR_INVALID_RECEIVER_ACCOUNT_ID",
-                    )));
-                }
+            if context.apparent_value != U256::from(0) {
+                return Err(ExitError::Other(Cow::from(
+                    "ERR_ETH_ATTACHED_FOR_ERC20_EXIT",
+                )));
             }
-            0x1 => {
-                // ERC20 transfer
-                //
-                // This precompile branch is expected to be called from the ERC20 burn function\
-                //
-                // Input slice format:
-                //      amount (U256 big-endian bytes) - the amount that was burned
-                //      recipient_account_id (bytes) - the NEAR recipient account which will receive NEP-141 tokens
-
-                if context.apparent_value != U256::from(0) {
-                    return Err(ExitError::Other(Cow::from(
-                        "ERR_ETH_ATTACHED_FOR_ERC20_EXIT",
-                    )));
-                }
 
-                let erc20_address = context.caller;
-                let nep141_address = get_nep141_from_erc20(erc20_address.as_bytes());
+            let erc20_address = context.caller;
+            let nep141_address = get_nep141_from_erc20(erc20_address.as_bytes());
 
-                let amount = U256::from_big_endian(&input[..32]);
-                input = &input[32..];
-
-                if let Ok(receiver_account_id) = AccountId::try_from(input) {
-                    (
-                        nep141_address,
-                        // There is no way to inject json, given the encoding of both arguments
-                        // as decimal and valid account id respectively.
-                        format!(
-                            r#"{{"receiver_id": "{}", "amount": "{}", "memo": null}}"#,
-                            receiver_account_id,
-                            amount.as_u128()
-                        ),
-                        events::ExitToNear {
-                            sender: erc20_address,
-                            erc20_address,
-                            dest: receiver_account_id.to_string(),
-                            amount,
-                        },
-                    )
-                } else {
-                    return Err(ExitError::Other(Cow::from(
-                        "ERR_INVALID_RECEIVER_ACCOUNT_ID",
-                    )));
-                }
-            }
-            _ => return Err(ExitError::Other(Cow::from("ERR_INVALID_FLAG"))),
+            (nep141_address, erc20_address, erc20_address, amount)
+        };
+        let destination = ExitToNearDestination::parse(recipient, extended)?;
+        let transfer_promise = destination.transfer_promise(nep141_address, amount.as_u128());
+        let exit_event = events::ExitToNear {
+            sender,
+            erc20_address,
+            dest: destination.receiver_id.to_string(),
+            amount,
+            memo: destination.memo.unwrap_or_default(),
+            msg: destination.msg.unwrap_or_default(),
         };
 
-        #[cfg(feature = "error_refund")]
-        let erc20_address = if flag == 0 {
-            None
-        } else {
+        // The legacy format encodes no refund address: the caller is refunded
+        let refund_address = refund_address.unwrap_or(context.caller);
+        let erc20_address = if is_erc20 {
             Some(exit_event.erc20_address.0)
+        } else {
+            None
         };
-        #[cfg(feature = "error_refund")]
         let refund_args = RefundCallArgs {
             recipient_address: refund_address.0,
             erc20_address,
             amount: types::u256_to_arr(&exit_event.amount),
+            // The exit ID is assigned by the engine when it schedules the promise
+            exit_id: 0,
         };
-        #[cfg(feature = "error_refund")]
         let refund_promise = PromiseCreateArgs {
-            target_account_id: refund_on_error_target,
-            method: "refund_on_error".to_string(),
+            target_account_id: self.current_account_id.clone(),
+            method: REFUND_ON_ERROR_METHOD.to_string(),
             args: refund_args.try_to_vec().unwrap(),
             attached_balance: 0,
             attached_gas: costs::REFUND_ON_ERROR_GAS,
         };
-        let transfer_promise = PromiseCreateArgs {
-            target_account_id: nep141_address,
-            method: "ft_transfer".to_string(),
-            args: args.as_bytes().to_vec(),
-            attached_balance: 1,
-            attached_gas: costs::FT_TRANSFER_GAS,
-        };
-
-        #[cfg(feature = "error_refund")]
         let promise = PromiseArgs::Callback(PromiseWithCallbackArgs {
             base: transfer_promise,
             callback: refund_promise,
         });
-        #[cfg(not(feature = "error_refund"))]
-        let promise = PromiseArgs::Create(transfer_promise);
 
         let promise_log = Log {
             address: Self::ADDRESS,
@@ -476,11 +720,11 @@ impl Precompile for ExitToEthereum {
         // First byte of the input is a flag, selecting the behavior to be triggered:
         //      0x0 -> Eth transfer
         //      0x1 -> Erc20 transfer
-        let mut input = input;
-        let flag = input[0];
-        input = &input[1..];
+        let (flag, mut input) = input
+            .split_first()
+            .ok_or_else(|| ExitError::Other(Cow::from("ERR_INVALID_INPUT")))?;
 
-        let (nep141_address, serialized_args, exit_event) = match flag {
+        let (nep141_address, serialized_args, exit_event) = match *flag {
             0x0 => {
                 // ETH transfer
                 //
@@ -526,6 +770,9 @@ impl Precompile for ExitToEthereum {
                 let erc20_address = context.caller;
                 let nep141_address = get_nep141_from_erc20(erc20_address.as_bytes());
 
+                if input.len() < 32 {
+                    return Err(ExitError::Other(Cow::from("ERR_INVALID_INPUT")));
+                }
                 let amount = U256::from_big_endian(&input[..32]);
                 input = &input[32..];
 
@@ -595,8 +842,13 @@ impl Precompile for ExitToEthereum {
 
 #[cfg(test)]
 mod tests {
-    use super::{ExitToEthereum, ExitToNear};
+    use super::{
+        ExitToEthereum, ExitToNear, ExitToNearDestination, ExitToNearInput, MAX_EXIT_TO_NEAR_GAS,
+    };
     use crate::prelude::sdk::types::near_account_to_evm_address;
+    use crate::prelude::{Address, Cow, ToString, TryFrom, U256};
+    use aurora_engine_types::account_id::AccountId;
+    use evm::ExitError;
 
     #[test]
     fn test_precompile_id() {
@@ -623,5 +875,122 @@ mod tests {
             exit_to_eth.signature(),
             super::events::EXIT_TO_ETH_SIGNATURE
         );
+        assert_eq!(
+            super::events::exit_scheduled_schema().signature(),
+            super::events::EXIT_SCHEDULED_SIGNATURE
+        );
+        assert_eq!(
+            super::events::refunded_schema().signature(),
+            super::events::REFUNDED_SIGNATURE
+        );
+    }
+
+    #[test]
+    fn test_exit_to_near_destination() {
+        let token_id = AccountId::try_from("token.near".to_string()).unwrap();
+        let options = |receiver_id: &str, memo: &str, msg: &str, gas: u64| {
+            ethabi::encode(&[
+                ethabi::Token::String(receiver_id.to_string()),
+                ethabi::Token::String(memo.to_string()),
+                ethabi::Token::String(msg.to_string()),
+                ethabi::Token::Uint(U256::from(gas)),
+            ])
+        };
+
+        // Legacy format: plain `ft_transfer`
+        let destination = ExitToNearDestination::parse(b"alice.near", false).unwrap();
+        let promise = destination.transfer_promise(token_id.clone(), 10);
+        assert_eq!(promise.method, "ft_transfer");
+        let args: serde_json::Value = serde_json::from_slice(&promise.args).unwrap();
+        assert_eq!(
+            args,
+            serde_json::json!({"receiver_id": "alice.near", "amount": "10", "memo": null})
+        );
+
+        // Extended format with a memo and a msg: `ft_transfer_call` with the given gas
+        let memo = "for \"bob\"";
+        let msg = "{\"action\": \"swap\"}\n";
+        let input = options("pool.near", memo, msg, MAX_EXIT_TO_NEAR_GAS);
+        let destination = ExitToNearDestination::parse(&input, true).unwrap();
+        let promise = destination.transfer_promise(token_id, 10);
+        assert_eq!(promise.method, "ft_transfer_call");
+        assert_eq!(promise.attached_gas, MAX_EXIT_TO_NEAR_GAS);
+        let args: serde_json::Value = serde_json::from_slice(&promise.args).unwrap();
+        assert_eq!(
+            args,
+            serde_json::json!({
+                "receiver_id": "pool.near",
+                "amount": "10",
+                "memo": memo,
+                "msg": msg,
+            })
+        );
+
+        // The gas must be within bounds
+        let input = options("pool.near", "", "", MAX_EXIT_TO_NEAR_GAS + 1);
+        assert_eq!(
+            ExitToNearDestination::parse(&input, true),
+            Err(ExitError::Other(Cow::from("ERR_INVALID_EXIT_GAS")))
+        );
+        assert_eq!(
+            ExitToNearDestination::parse(b"not an account", false),
+            Err(ExitError::Other(Cow::from(
+                "ERR_INVALID_RECEIVER_ACCOUNT_ID"
+            )))
+        );
+    }
+
+    #[test]
+    fn test_exit_to_near_input() {
+        let refund_address = Address::from_low_u64_be(0x1234);
+        let mut amount = [0u8; 32];
+        U256::from(10).to_big_endian(&mut amount);
+
+        // Legacy format, used by the deployed contracts: no refund address
+        let input = [&[0x0][..], b"alice.near"].concat();
+        assert_eq!(
+            ExitToNearInput::parse(&input).unwrap(),
+            ExitToNearInput {
+                is_erc20: false,
+                extended: false,
+                refund_address: None,
+                amount: U256::zero(),
+                recipient: b"alice.near",
+            }
+        );
+        let input = [&[0x1][..], &amount, b"alice.near"].concat();
+        assert_eq!(
+            ExitToNearInput::parse(&input).unwrap(),
+            ExitToNearInput {
+                is_erc20: true,
+                extended: false,
+                refund_address: None,
+                amount: U256::from(10),
+                recipient: b"alice.near",
+            }
+        );
+
+        // With a refund address
+        let input = [&[0x7][..], refund_address.as_bytes(), &amount, b"options"].concat();
+        assert_eq!(
+            ExitToNearInput::parse(&input).unwrap(),
+            ExitToNearInput {
+                is_erc20: true,
+                extended: true,
+                refund_address: Some(refund_address),
+                amount: U256::from(10),
+                recipient: b"options",
+            }
+        );
+
+        // Short inputs are rejected
+        let invalid_input = Err(ExitError::Other(Cow::from("ERR_INVALID_INPUT")));
+        assert_eq!(ExitToNearInput::parse(&[]), invalid_input);
+        assert_eq!(ExitToNearInput::parse(&[0x4; 20]), invalid_input);
+        assert_eq!(ExitToNearInput::parse(&[0x1; 32]), invalid_input);
+        assert_eq!(
+            ExitToNearInput::parse(&[0x8]),
+            Err(ExitError::Other(Cow::from("ERR_INVALID_FLAG")))
+        );
     }
 }
@@ -0,0 +1,241 @@
+use super::{EvmPrecompileResult, Precompile};
+use crate::prelude::sdk::env::Env;
+use crate::prelude::{Address, Cow, String, ToString, U256};
+use crate::PrecompileOutput;
+use evm::{Context, ExitError};
+
+mod costs {
+    use crate::prelude::types::Gas;
+
+    // TODO(#51): Determine the correct amount of gas
+    pub(super) const NEAR_ENVIRONMENT_GAS: Gas = 500;
+}
+
+/// Gives EVM contracts the NEAR context of the transaction, which the `origin` address
+/// mapped from the relayer account does not carry.
+///
+/// The input must be empty. The output is the ABI encoding of
+/// `(string predecessor_account_id, string signer_account_id, uint256 block_height,
+/// uint256 block_timestamp, bytes32 random_seed)`, where the timestamp is in nanoseconds and
+/// the random seed is the one of the NEAR block. In a view call, which has no NEAR
+/// transaction, the predecessor and signer accounts are empty strings.
+#[derive(Default)]
+pub struct NearEnvironment {
+    /// Whether the precompile runs in a view call, where reading the predecessor and signer
+    /// accounts would abort the call.
+    is_view: bool,
+}
+
+impl NearEnvironment {
+    /// NEAR environment precompile address
+    ///
+    /// Address: `0x1d0b0341c2f8b4d8ddfbf98775c2ae9af4ba20b6`
+    /// This address is computed as: `&keccak("nearEnvironment")[12..]`
+    pub const ADDRESS: Address =
+        super::make_address(0x1d0b0341, 0xc2f8b4d8ddfbf98775c2ae9af4ba20b6);
+
+    /// The precompile of the view calls.
+    pub fn new_view() -> Self {
+        Self { is_view: true }
+    }
+
+    /// Runs the precompile against the given environment.
+    pub fn execute<E: Env>(
+        &self,
+        env: &E,
+        input: &[u8],
+        target_gas: Option<u64>,
+    ) -> EvmPrecompileResult {
+        let cost = Self::required_gas(input)?;
+        if let Some(target_gas) = target_gas {
+            if cost > target_gas {
+                return Err(ExitError::OutOfGas);
+            }
+        }
+        if !input.is_empty() {
+            return Err(ExitError::Other(Cow::from("ERR_INVALID_INPUT")));
+        }
+
+        let (predecessor_account_id, signer_account_id) = if self.is_view {
+            (String::new(), String::new())
+        } else {
+            (
+                env.predecessor_account_id().to_string(),
+                env.signer_account_id().to_string(),
+            )
+        };
+        let output = ethabi::encode(&[
+            ethabi::Token::String(predecessor_account_id),
+            ethabi::Token::String(signer_account_id),
+            ethabi::Token::Uint(U256::from(env.block_height())),
+            ethabi::Token::Uint(U256::from(env.block_timestamp().nanos())),
+            ethabi::Token::FixedBytes(env.random_seed().as_bytes().to_vec()),
+        ]);
+
+        Ok(PrecompileOutput::without_logs(cost, output).into())
+    }
+}
+
+impl Precompile for NearEnvironment {
+    fn required_gas(_input: &[u8]) -> Result<u64, ExitError> {
+        Ok(costs::NEAR_ENVIRONMENT_GAS)
+    }
+
+    #[cfg(not(feature = "contract"))]
+    fn run(
+        &self,
+        input: &[u8],
+        target_gas: Option<u64>,
+        _context: &Context,
+        _is_static: bool,
+    ) -> EvmPrecompileResult {
+        let cost = Self::required_gas(input)?;
+        if let Some(target_gas) = target_gas {
+            if cost > target_gas {
+                return Err(ExitError::OutOfGas);
+            }
+        }
+
+        Ok(PrecompileOutput {
+            cost,
+            ..Default::default()
+        }
+        .into())
+    }
+
+    #[cfg(feature = "contract")]
+    fn run(
+        &self,
+        input: &[u8],
+        target_gas: Option<u64>,
+        _context: &Context,
+        _is_static: bool,
+    ) -> EvmPrecompileResult {
+        self.execute(
+            &crate::prelude::sdk::near_runtime::Runtime,
+            input,
+            target_gas,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NearEnvironment;
+    use crate::prelude::sdk::env::{Env, Timestamp};
+    use crate::prelude::sdk::types::near_account_to_evm_address;
+    use crate::prelude::{vec, AccountId, Cow, String, ToString, TryFrom, H256, U256};
+    use evm::ExitError;
+
+    struct TestEnv;
+
+    /// Environment of a view call: there is no predecessor nor signer.
+    struct ViewEnv;
+
+    impl Env for ViewEnv {
+        fn signer_account_id(&self) -> AccountId {
+            panic!("signer_account_id is not available in view calls")
+        }
+
+        fn current_account_id(&self) -> AccountId {
+            TestEnv.current_account_id()
+        }
+
+        fn predecessor_account_id(&self) -> AccountId {
+            panic!("predecessor_account_id is not available in view calls")
+        }
+
+        fn block_height(&self) -> u64 {
+            TestEnv.block_height()
+        }
+
+        fn block_timestamp(&self) -> Timestamp {
+            TestEnv.block_timestamp()
+        }
+
+        fn attached_deposit(&self) -> u128 {
+            0
+        }
+
+        fn random_seed(&self) -> H256 {
+            TestEnv.random_seed()
+        }
+    }
+
+    impl Env for TestEnv {
+        fn signer_account_id(&self) -> AccountId {
+            AccountId::try_from("signer.near".to_string()).unwrap()
+        }
+
+        fn current_account_id(&self) -> AccountId {
+            AccountId::try_from("aurora".to_string()).unwrap()
+        }
+
+        fn predecessor_account_id(&self) -> AccountId {
+            AccountId::try_from("relayer.near".to_string()).unwrap()
+        }
+
+        fn block_height(&self) -> u64 {
+            42
+        }
+
+        fn block_timestamp(&self) -> Timestamp {
+            Timestamp::new(1_000_000_000)
+        }
+
+        fn attached_deposit(&self) -> u128 {
+            0
+        }
+
+        fn random_seed(&self) -> H256 {
+            H256::repeat_byte(7)
+        }
+    }
+
+    #[test]
+    fn test_precompile_id() {
+        assert_eq!(
+            NearEnvironment::ADDRESS,
+            near_account_to_evm_address("nearEnvironment".as_bytes())
+        );
+    }
+
+    #[test]
+    fn test_near_environment() {
+        let output = NearEnvironment::default()
+            .execute(&TestEnv, &[], None)
+            .unwrap()
+            .output;
+        let expected = ethabi::encode(&[
+            ethabi::Token::String("relayer.near".to_string()),
+            ethabi::Token::String("signer.near".to_string()),
+            ethabi::Token::Uint(U256::from(42)),
+            ethabi::Token::Uint(U256::from(1_000_000_000)),
+            ethabi::Token::FixedBytes(vec![7u8; 32]),
+        ]);
+        assert_eq!(output, expected);
+
+        assert_eq!(
+            NearEnvironment::default()
+                .execute(&TestEnv, &[0], None)
+                .err(),
+            Some(ExitError::Other(Cow::from("ERR_INVALID_INPUT")))
+        );
+    }
+
+    #[test]
+    fn test_near_environment_view() {
+        let output = NearEnvironment::new_view()
+            .execute(&ViewEnv, &[], None)
+            .unwrap()
+            .output;
+        let expected = ethabi::encode(&[
+            ethabi::Token::String(String::new()),
+            ethabi::Token::String(String::new()),
+            ethabi::Token::Uint(U256::from(42)),
+            ethabi::Token::Uint(U256::from(1_000_000_000)),
+            ethabi::Token::FixedBytes(vec![7u8; 32]),
+        ]);
+        assert_eq!(output, expected);
+    }
+}
@@ -0,0 +1,181 @@
+use super::{EvmPrecompileResult, Precompile};
+use crate::prelude::types::PromiseResult;
+use crate::prelude::{Address, Cow, U256};
+use crate::PrecompileOutput;
+use evm::{Context, ExitError};
+
+/// Status of a promise result whose value is not ready yet.
+pub const STATUS_NOT_READY: u8 = 0;
+/// Status of a successful promise result.
+pub const STATUS_SUCCESSFUL: u8 = 1;
+/// Status of a failed promise result.
+pub const STATUS_FAILED: u8 = 2;
+
+mod costs {
+    use crate::prelude::types::Gas;
+
+    // TODO(#51): Determine the correct amount of gas
+    pub(super) const PROMISE_RESULT_BASE_COST: Gas = 125;
+
+    // TODO(#51): Determine the correct amount of gas
+    pub(super) const PROMISE_RESULT_BYTE_COST: Gas = 1;
+}
+
+/// Status and value of a promise result, as returned by the precompile.
+pub fn status_and_data(result: &PromiseResult) -> (u8, &[u8]) {
+    match result {
+        PromiseResult::NotReady => (STATUS_NOT_READY, &[]),
+        PromiseResult::Successful(data) => (STATUS_SUCCESSFUL, data.as_slice()),
+        PromiseResult::Failed => (STATUS_FAILED, &[]),
+    }
+}
+
+/// Gives EVM contracts the results of the promises the engine is called back with, e.g. in the
+/// callback of a NEAR call scheduled through the cross-contract call precompile.
+///
+/// With an empty input, it returns the number of results, ABI-encoded as a `uint256`. With the
+/// ABI-encoded `uint256` index of a result as input, it returns the ABI encoding of
+/// `(uint256 status, bytes data)`, where the status is one of `STATUS_NOT_READY`,
+/// `STATUS_SUCCESSFUL` and `STATUS_FAILED`. Outside of callbacks there are no results.
+pub struct PromiseResults;
+
+impl PromiseResults {
+    /// Promise results precompile address
+    ///
+    /// Address: `0xdb8c359bfe90d4cde46fe1b67c810fc9734f5188`
+    /// This address is computed as: `&keccak("nearPromiseResult")[12..]`
+    pub const ADDRESS: Address =
+        super::make_address(0xdb8c359b, 0xfe90d4cde46fe1b67c810fc9734f5188);
+
+    /// Runs the precompile against the given promise results.
+    pub fn execute<F>(
+        input: &[u8],
+        target_gas: Option<u64>,
+        results_count: u64,
+        promise_result: F,
+    ) -> EvmPrecompileResult
+    where
+        F: Fn(u64) -> Option<PromiseResult>,
+    {
+        let (cost, output) = match input.len() {
+            0 => (
+                Self::required_gas(input)?,
+                ethabi::encode(&[ethabi::Token::Uint(U256::from(results_count))]),
+            ),
+            32 => {
+                let index = U256::from_big_endian(input);
+                if index >= U256::from(results_count) {
+                    return Err(ExitError::Other(Cow::from("ERR_PROMISE_RESULT_INDEX")));
+                }
+                let result = promise_result(index.low_u64())
+                    .ok_or(ExitError::Other(Cow::from("ERR_PROMISE_RESULT_INDEX")))?;
+                let (status, data) = status_and_data(&result);
+                let byte_cost = (data.len() as u64).saturating_mul(costs::PROMISE_RESULT_BYTE_COST);
+                (
+                    Self::required_gas(input)?.saturating_add(byte_cost),
+                    ethabi::encode(&[
+                        ethabi::Token::Uint(U256::from(status)),
+                        ethabi::Token::Bytes(data.to_vec()),
+                    ]),
+                )
+            }
+            _ => return Err(ExitError::Other(Cow::from("ERR_INVALID_INPUT"))),
+        };
+        if let Some(target_gas) = target_gas {
+            if cost > target_gas {
+                return Err(ExitError::OutOfGas);
+            }
+        }
+
+        Ok(PrecompileOutput::without_logs(cost, output).into())
+    }
+}
+
+impl Precompile for PromiseResults {
+    /// The cost of reading a result also depends on its size, which is only known when running.
+    fn required_gas(_input: &[u8]) -> Result<u64, ExitError> {
+        Ok(costs::PROMISE_RESULT_BASE_COST)
+    }
+
+    #[cfg(not(feature = "contract"))]
+    fn run(
+        &self,
+        input: &[u8],
+        target_gas: Option<u64>,
+        _context: &Context,
+        _is_static: bool,
+    ) -> EvmPrecompileResult {
+        Self::execute(input, target_gas, 0, |_| None)
+    }
+
+    #[cfg(feature = "contract")]
+    fn run(
+        &self,
+        input: &[u8],
+        target_gas: Option<u64>,
+        _context: &Context,
+        _is_static: bool,
+    ) -> EvmPrecompileResult {
+        use crate::prelude::sdk::{near_runtime::Runtime, promise::PromiseHandler};
+        let results_count = Runtime.promise_results_count();
+        Self::execute(input, target_gas, results_count, |index| {
+            Runtime.promise_result(index)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PromiseResults, STATUS_FAILED, STATUS_SUCCESSFUL};
+    use crate::prelude::sdk::types::near_account_to_evm_address;
+    use crate::prelude::types::{u256_to_arr, PromiseResult};
+    use crate::prelude::{Cow, Vec, U256};
+    use evm::ExitError;
+
+    #[test]
+    fn test_precompile_id() {
+        assert_eq!(
+            PromiseResults::ADDRESS,
+            near_account_to_evm_address("nearPromiseResult".as_bytes())
+        );
+    }
+
+    #[test]
+    fn test_promise_results() {
+        let results = [
+            PromiseResult::Successful(b"done".to_vec()),
+            PromiseResult::Failed,
+        ];
+        let run = |input: &[u8]| {
+            PromiseResults::execute(input, None, results.len() as u64, |index| {
+                results.get(index as usize).cloned()
+            })
+        };
+
+        let output = run(&[]).unwrap().output;
+        assert_eq!(output, u256_to_arr(&U256::from(2)).to_vec());
+
+        let output = run(&u256_to_arr(&U256::zero())).unwrap().output;
+        let expected = ethabi::encode(&[
+            ethabi::Token::Uint(U256::from(STATUS_SUCCESSFUL)),
+            ethabi::Token::Bytes(b"done".to_vec()),
+        ]);
+        assert_eq!(output, expected);
+
+        let output = run(&u256_to_arr(&U256::one())).unwrap().output;
+        let expected = ethabi::encode(&[
+            ethabi::Token::Uint(U256::from(STATUS_FAILED)),
+            ethabi::Token::Bytes(Vec::new()),
+        ]);
+        assert_eq!(output, expected);
+
+        assert_eq!(
+            run(&u256_to_arr(&U256::from(2))).err(),
+            Some(ExitError::Other(Cow::from("ERR_PROMISE_RESULT_INDEX")))
+        );
+        assert_eq!(
+            run(&[0u8; 4]).err(),
+            Some(ExitError::Other(Cow::from("ERR_INVALID_INPUT")))
+        );
+    }
+}
@@ -1,5 +1,6 @@
 use crate::error::{OneYoctoAttachError, PrivateCallError};
 use aurora_engine_types::account_id::AccountId;
+use aurora_engine_types::H256;
 
 /// Timestamp represented by the number of nanoseconds since the Unix Epoch.
 pub struct Timestamp(u64);
@@ -39,6 +40,8 @@ pub trait Env {
     fn block_timestamp(&self) -> Timestamp;
     /// Amount of NEAR attached to current call
     fn attached_deposit(&self) -> u128;
+    /// Random seed of the current block, derived from the VRF output of its producer.
+    fn random_seed(&self) -> H256;
 
     fn assert_private_call(&self) -> Result<(), PrivateCallError> {
         if self.predecessor_account_id() == self.current_account_id() {
@@ -1,7 +1,8 @@
 use crate::error;
-use crate::prelude::{vec, Vec};
+use crate::prelude::{vec, BTreeMap, Vec};
 use aurora_engine_types::U256;
 use borsh::{BorshDeserialize, BorshSerialize};
+use core::cell::RefCell;
 
 /// The purpose of this trait is to represent a reference to a value that
 /// could be obtained by IO, but without eagerly loading it into memory.
@@ -136,3 +137,119 @@ pub trait IO {
         self.write_storage(key, &bytes)
     }
 }
+
+/// Changes buffered by an `OverlayIO`, with `None` for the removed keys.
+pub type OverlayBuffer = RefCell<BTreeMap<Vec<u8>, Option<Vec<u8>>>>;
+
+/// Copy-on-write layer over an `IO`: the writes and removals are buffered in memory and the
+/// reads are served from that buffer first. The changes are written to the underlying `IO`
+/// by `commit`, or dropped by `discard`.
+///
+/// The buffer is shared by the copies of the overlay, so it can be used by the `Engine` (which
+/// copies its `IO`) and committed or discarded afterwards.
+#[derive(Debug, Clone, Copy)]
+pub struct OverlayIO<'a, I> {
+    inner: I,
+    buffer: &'a OverlayBuffer,
+}
+
+impl<'a, I: IO + Copy> OverlayIO<'a, I> {
+    pub fn new(inner: I, buffer: &'a OverlayBuffer) -> Self {
+        Self { inner, buffer }
+    }
+
+    /// Writes the buffered changes to the underlying `IO`, and empties the buffer.
+    pub fn commit(mut self) {
+        let changes = core::mem::take(&mut *self.buffer.borrow_mut());
+        for (key, value) in changes {
+            match value {
+                Some(value) => self.inner.write_storage(&key, &value),
+                None => self.inner.remove_storage(&key),
+            };
+        }
+    }
+
+    /// Drops the buffered changes.
+    pub fn discard(self) {
+        self.buffer.borrow_mut().clear();
+    }
+
+    fn insert(&self, key: &[u8], value: Option<Vec<u8>>) -> Option<OverlayValue<I::StorageValue>> {
+        let previous = self.read_storage(key);
+        self.buffer.borrow_mut().insert(key.to_vec(), value);
+        previous
+    }
+}
+
+/// A value read from an `OverlayIO`.
+pub enum OverlayValue<V> {
+    /// Value read from the underlying `IO`.
+    Inner(V),
+    /// Value written to the overlay.
+    Buffered(Vec<u8>),
+}
+
+impl<V: StorageIntermediate> StorageIntermediate for OverlayValue<V> {
+    fn len(&self) -> usize {
+        match self {
+            Self::Inner(value) => value.len(),
+            Self::Buffered(value) => value.len(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        match self {
+            Self::Inner(value) => value.is_empty(),
+            Self::Buffered(value) => value.is_empty(),
+        }
+    }
+
+    fn copy_to_slice(&self, buffer: &mut [u8]) {
+        match self {
+            Self::Inner(value) => value.copy_to_slice(buffer),
+            Self::Buffered(value) => buffer.copy_from_slice(value),
+        }
+    }
+}
+
+impl<'a, I: IO + Copy> IO for OverlayIO<'a, I> {
+    type StorageValue = OverlayValue<I::StorageValue>;
+
+    fn read_input(&self) -> Self::StorageValue {
+        OverlayValue::Inner(self.inner.read_input())
+    }
+
+    fn return_output(&mut self, value: &[u8]) {
+        self.inner.return_output(value)
+    }
+
+    fn read_storage(&self, key: &[u8]) -> Option<Self::StorageValue> {
+        match self.buffer.borrow().get(key) {
+            Some(value) => value.clone().map(OverlayValue::Buffered),
+            None => self.inner.read_storage(key).map(OverlayValue::Inner),
+        }
+    }
+
+    fn storage_has_key(&self, key: &[u8]) -> bool {
+        match self.buffer.borrow().get(key) {
+            Some(value) => value.is_some(),
+            None => self.inner.storage_has_key(key),
+        }
+    }
+
+    fn write_storage(&mut self, key: &[u8], value: &[u8]) -> Option<Self::StorageValue> {
+        self.insert(key, Some(value.to_vec()))
+    }
+
+    fn write_storage_direct(
+        &mut self,
+        key: &[u8],
+        value: Self::StorageValue,
+    ) -> Option<Self::StorageValue> {
+        self.insert(key, Some(value.to_vec()))
+    }
+
+    fn remove_storage(&mut self, key: &[u8]) -> Option<Self::StorageValue> {
+        self.insert(key, None)
+    }
+}
@@ -25,6 +25,7 @@ pub fn panic() {
     unsafe { exports::panic() }
 }
 
+#[cfg(feature = "contract")]
 pub fn panic_utf8(bytes: &[u8]) -> ! {
     unsafe {
         exports::panic_utf8(bytes.len() as u64, bytes.as_ptr() as u64);
@@ -32,6 +33,16 @@ pub fn panic_utf8(bytes: &[u8]) -> ! {
     unreachable!()
 }
 
+/// Outside of the NEAR runtime (e.g. a standalone engine) there is no host
+/// function to abort with, so fall back to a regular Rust panic.
+#[cfg(not(feature = "contract"))]
+pub fn panic_utf8(bytes: &[u8]) -> ! {
+    panic!(
+        "{}",
+        core::str::from_utf8(bytes).unwrap_or("ERR_INVALID_UTF8_PANIC_MESSAGE")
+    )
+}
+
 #[allow(dead_code)]
 pub fn log_utf8(bytes: &[u8]) {
     unsafe {
@@ -3,7 +3,7 @@ use crate::promise::PromiseId;
 use aurora_engine_types::account_id::AccountId;
 use aurora_engine_types::parameters::{PromiseAction, PromiseBatchAction, PromiseCreateArgs};
 use aurora_engine_types::types::PromiseResult;
-use aurora_engine_types::TryFrom;
+use aurora_engine_types::{TryFrom, H256};
 
 /// Wrapper type for indices in NEAR's register API.
 pub struct RegisterIndex(u64);
@@ -227,6 +227,15 @@ impl crate::env::Env for Runtime {
             u128::from_le_bytes(data)
         }
     }
+
+    fn random_seed(&self) -> H256 {
+        unsafe {
+            exports::random_seed(Self::ENV_REGISTER_ID.0);
+        }
+        let mut bytes = H256::zero();
+        Self::ENV_REGISTER_ID.copy_to_slice(&mut bytes.0);
+        bytes
+    }
 }
 
 impl crate::promise::PromiseHandler for Runtime {
@@ -382,7 +391,7 @@ pub(crate) mod exports {
         // ############
         // # Math API #
         // ############
-        fn random_seed(register_id: u64);
+        pub(crate) fn random_seed(register_id: u64);
         pub(crate) fn sha256(value_len: u64, value_ptr: u64, register_id: u64);
         pub(crate) fn keccak256(value_len: u64, value_ptr: u64, register_id: u64);
         pub(crate) fn ripemd160(value_len: u64, value_ptr: u64, register_id: u64);
@@ -1,3 +1,3 @@
 pub use aurora_engine_types::types::{PromiseResult, STORAGE_PRICE_PER_BYTE};
-pub use aurora_engine_types::{vec, Address, Vec, H256};
+pub use aurora_engine_types::{vec, Address, BTreeMap, Vec, H256};
 pub use borsh::{BorshDeserialize, BorshSerialize};
@@ -2,16 +2,17 @@ use aurora_engine_types::parameters::{
     PromiseBatchAction, PromiseCreateArgs, PromiseWithCallbackArgs,
 };
 use aurora_engine_types::types::PromiseResult;
+use aurora_engine_types::Vec;
 
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
 pub struct PromiseId(u64);
 
 impl PromiseId {
-    pub(crate) fn new(id: u64) -> Self {
+    pub fn new(id: u64) -> Self {
         Self(id)
     }
 
-    pub(crate) fn raw(self) -> u64 {
+    pub fn raw(self) -> u64 {
         self.0
     }
 }
@@ -34,3 +35,98 @@ pub trait PromiseHandler {
         self.promise_attach_callback(base, &args.callback)
     }
 }
+
+/// A promise action buffered by a `PromiseBuffer`, referring to the buffered promises by their
+/// buffer IDs.
+#[derive(Debug, Clone)]
+enum BufferedPromise {
+    Create(PromiseCreateArgs),
+    Callback {
+        base: PromiseId,
+        callback: PromiseCreateArgs,
+    },
+    Batch(PromiseBatchAction),
+    Return(PromiseId),
+}
+
+/// `PromiseHandler` buffering the promises scheduled through it, the promise counterpart of
+/// `OverlayIO`: they are only scheduled with the underlying handler by `flush`, and are dropped
+/// with the buffer otherwise. The promise results are read from the underlying handler.
+///
+/// The IDs returned for the buffered promises are only meaningful to the buffer.
+pub struct PromiseBuffer<'a, P> {
+    inner: &'a mut P,
+    actions: Vec<BufferedPromise>,
+    /// Number of promises created, the ID of the next one.
+    promises_count: u64,
+}
+
+impl<'a, P: PromiseHandler> PromiseBuffer<'a, P> {
+    pub fn new(inner: &'a mut P) -> Self {
+        Self {
+            inner,
+            actions: Vec::new(),
+            promises_count: 0,
+        }
+    }
+
+    /// Schedules the buffered promises with the underlying handler, in order.
+    pub fn flush(self) {
+        // The IDs given by the underlying handler, indexed by buffer ID.
+        let mut ids: Vec<PromiseId> = Vec::with_capacity(self.promises_count as usize);
+        for action in self.actions {
+            let id = match action {
+                BufferedPromise::Create(args) => self.inner.promise_create_call(&args),
+                BufferedPromise::Callback { base, callback } => self
+                    .inner
+                    .promise_attach_callback(ids[base.raw() as usize], &callback),
+                BufferedPromise::Batch(args) => self.inner.promise_create_batch(&args),
+                BufferedPromise::Return(promise) => {
+                    self.inner.promise_return(ids[promise.raw() as usize]);
+                    continue;
+                }
+            };
+            ids.push(id);
+        }
+    }
+
+    fn push(&mut self, promise: BufferedPromise) -> PromiseId {
+        let id = PromiseId::new(self.promises_count);
+        self.promises_count += 1;
+        self.actions.push(promise);
+        id
+    }
+}
+
+impl<'a, P: PromiseHandler> PromiseHandler for PromiseBuffer<'a, P> {
+    fn promise_results_count(&self) -> u64 {
+        self.inner.promise_results_count()
+    }
+
+    fn promise_result(&self, index: u64) -> Option<PromiseResult> {
+        self.inner.promise_result(index)
+    }
+
+    fn promise_create_call(&mut self, args: &PromiseCreateArgs) -> PromiseId {
+        self.push(BufferedPromise::Create(args.clone()))
+    }
+
+    fn promise_attach_callback(
+        &mut self,
+        base: PromiseId,
+        callback: &PromiseCreateArgs,
+    ) -> PromiseId {
+        self.push(BufferedPromise::Callback {
+            base,
+            callback: callback.clone(),
+        })
+    }
+
+    fn promise_create_batch(&mut self, args: &PromiseBatchAction) -> PromiseId {
+        self.push(BufferedPromise::Batch(args.clone()))
+    }
+
+    fn promise_return(&mut self, promise: PromiseId) {
+        self.actions.push(BufferedPromise::Return(promise));
+    }
+}
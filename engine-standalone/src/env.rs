@@ -0,0 +1,62 @@
+use aurora_engine_sdk::env::{Env, Timestamp};
+use aurora_engine_types::account_id::AccountId;
+use aurora_engine_types::H256;
+
+/// Configurable NEAR context for running the engine standalone.
+/// All values are returned as-is through the `Env` trait.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StandaloneEnv {
+    pub signer_account_id: AccountId,
+    pub current_account_id: AccountId,
+    pub predecessor_account_id: AccountId,
+    pub block_height: u64,
+    /// Timestamp of the block in nanoseconds since the Unix Epoch.
+    pub block_timestamp: u64,
+    pub attached_deposit: u128,
+    pub random_seed: H256,
+}
+
+impl Default for StandaloneEnv {
+    fn default() -> Self {
+        let aurora = AccountId::new("aurora").unwrap();
+        Self {
+            signer_account_id: aurora.clone(),
+            current_account_id: aurora.clone(),
+            predecessor_account_id: aurora,
+            block_height: 0,
+            block_timestamp: 0,
+            attached_deposit: 0,
+            random_seed: H256::zero(),
+        }
+    }
+}
+
+impl Env for StandaloneEnv {
+    fn signer_account_id(&self) -> AccountId {
+        self.signer_account_id.clone()
+    }
+
+    fn current_account_id(&self) -> AccountId {
+        self.current_account_id.clone()
+    }
+
+    fn predecessor_account_id(&self) -> AccountId {
+        self.predecessor_account_id.clone()
+    }
+
+    fn block_height(&self) -> u64 {
+        self.block_height
+    }
+
+    fn block_timestamp(&self) -> Timestamp {
+        Timestamp::new(self.block_timestamp)
+    }
+
+    fn attached_deposit(&self) -> u128 {
+        self.attached_deposit
+    }
+
+    fn random_seed(&self) -> H256 {
+        self.random_seed
+    }
+}
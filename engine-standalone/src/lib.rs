@@ -0,0 +1,16 @@
+//! Runs the Aurora engine outside of the NEAR runtime.
+//!
+//! The engine only interacts with its host through the `IO`, `Env` and `PromiseHandler`
+//! traits of `aurora-engine-sdk`. This crate implements those traits on top of an
+//! in-memory key-value store (which can be persisted to a file), so that transactions can
+//! be replayed, simulated and tested natively without a wasm runtime.
+
+pub mod env;
+pub mod promise;
+pub mod runner;
+pub mod storage;
+
+pub use env::StandaloneEnv;
+pub use promise::{PromiseRecorder, ScheduledPromise};
+pub use runner::{EngineRunner, RunnerError};
+pub use storage::{StandaloneIO, Storage, Value};
@@ -0,0 +1,94 @@
+use aurora_engine_sdk::promise::{PromiseHandler, PromiseId};
+use aurora_engine_types::parameters::{PromiseBatchAction, PromiseCreateArgs};
+use aurora_engine_types::types::PromiseResult;
+use std::mem;
+
+/// A promise scheduled by the engine. Outside of NEAR there is nothing to
+/// execute it, so it is only recorded for the caller to inspect.
+#[derive(Debug, Clone)]
+pub enum ScheduledPromise {
+    Create(PromiseCreateArgs),
+    Callback {
+        base: PromiseId,
+        callback: PromiseCreateArgs,
+    },
+    Batch(PromiseBatchAction),
+}
+
+/// `PromiseHandler` which records the promises created by the engine and
+/// serves pre-configured promise results (e.g. to simulate a callback).
+#[derive(Debug, Default, Clone)]
+pub struct PromiseRecorder {
+    /// Results returned by `promise_result`, indexed by position.
+    pub promise_results: Vec<PromiseResult>,
+    scheduled: Vec<ScheduledPromise>,
+    returned: Option<PromiseId>,
+}
+
+impl PromiseRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_results(promise_results: Vec<PromiseResult>) -> Self {
+        Self {
+            promise_results,
+            ..Self::default()
+        }
+    }
+
+    /// Promises scheduled so far. The `PromiseId` of a promise is its index in this list.
+    pub fn scheduled(&self) -> &[ScheduledPromise] {
+        &self.scheduled
+    }
+
+    /// The promise passed to `promise_return`, if any.
+    pub fn returned(&self) -> Option<PromiseId> {
+        self.returned
+    }
+
+    /// Clears the recorded promises, returning them.
+    pub fn take_scheduled(&mut self) -> Vec<ScheduledPromise> {
+        self.returned = None;
+        mem::take(&mut self.scheduled)
+    }
+
+    fn push(&mut self, promise: ScheduledPromise) -> PromiseId {
+        let id = PromiseId::new(self.scheduled.len() as u64);
+        self.scheduled.push(promise);
+        id
+    }
+}
+
+impl PromiseHandler for PromiseRecorder {
+    fn promise_results_count(&self) -> u64 {
+        self.promise_results.len() as u64
+    }
+
+    fn promise_result(&self, index: u64) -> Option<PromiseResult> {
+        self.promise_results.get(index as usize).cloned()
+    }
+
+    fn promise_create_call(&mut self, args: &PromiseCreateArgs) -> PromiseId {
+        self.push(ScheduledPromise::Create(args.clone()))
+    }
+
+    fn promise_attach_callback(
+        &mut self,
+        base: PromiseId,
+        callback: &PromiseCreateArgs,
+    ) -> PromiseId {
+        self.push(ScheduledPromise::Callback {
+            base,
+            callback: callback.clone(),
+        })
+    }
+
+    fn promise_create_batch(&mut self, args: &PromiseBatchAction) -> PromiseId {
+        self.push(ScheduledPromise::Batch(args.clone()))
+    }
+
+    fn promise_return(&mut self, promise: PromiseId) {
+        self.returned = Some(promise);
+    }
+}
@@ -0,0 +1,317 @@
+use crate::env::StandaloneEnv;
+use crate::promise::PromiseRecorder;
+use crate::storage::{StandaloneIO, Storage};
+use aurora_engine::connector::EthConnectorContract;
+use aurora_engine::engine::{self, Engine, EngineError, EngineStateError};
+use aurora_engine::fungible_token::FungibleTokenMetadata;
+use aurora_engine::logs_index::{self, GetLogsError};
+use aurora_engine::migration;
+use aurora_engine::overrides;
+#[cfg(feature = "evm_bully")]
+use aurora_engine::parameters::BeginBlockArgs;
+use aurora_engine::parameters::{
+    BatchTransactionResult, CallArgs, EstimateGasArgs, EstimateGasResult, FilteredLog, GetLogsArgs,
+    InitCallArgs, NewCallArgs, PendingTransaction, SubmitResult, TransactionReceipt,
+    TransactionStatus, ViewCallArgs, ViewCallWithOverridesArgs,
+};
+#[cfg(feature = "tracing")]
+use aurora_engine::tracing::{self, TraceListener};
+use aurora_engine::transaction_queue;
+use aurora_engine_sdk::io::{OverlayBuffer, OverlayIO};
+use aurora_engine_sdk::types::near_account_to_evm_address;
+use aurora_engine_types::types::Wei;
+use aurora_engine_types::{Address, H256, U256};
+use std::cell::RefCell;
+
+/// Errors returned by the `EngineRunner`.
+#[derive(Debug)]
+pub enum RunnerError {
+    /// The engine state is missing (see `EngineRunner::init`) or corrupted.
+    EngineState(EngineStateError),
+    /// The engine failed to execute the call.
+    Engine(EngineError),
+}
+
+impl From<EngineStateError> for RunnerError {
+    fn from(e: EngineStateError) -> Self {
+        Self::EngineState(e)
+    }
+}
+
+impl From<EngineError> for RunnerError {
+    fn from(e: EngineError) -> Self {
+        Self::Engine(e)
+    }
+}
+
+/// Executes the engine methods natively against a `Storage`, mirroring the
+/// `submit`, `call`, `deploy_code` and `view` methods of the contract.
+///
+/// The origin of `call` and `deploy_code`, as well as the relayer rewarded by
+/// `submit`, are derived from `env.predecessor_account_id` as on NEAR.
+#[derive(Debug, Default)]
+pub struct EngineRunner {
+    pub storage: RefCell<Storage>,
+    pub env: StandaloneEnv,
+    pub promises: PromiseRecorder,
+}
+
+impl EngineRunner {
+    pub fn new(storage: Storage, env: StandaloneEnv) -> Self {
+        Self {
+            storage: RefCell::new(storage),
+            env,
+            promises: PromiseRecorder::default(),
+        }
+    }
+
+    pub fn io(&self) -> StandaloneIO<'_> {
+        StandaloneIO::new(&self.storage)
+    }
+
+    /// Equivalent of calling the `new` and `new_eth_connector` methods of the contract.
+    /// The eth-connector needs to be initialized because the engine keeps the ETH total
+    /// supply up to date when balances are removed.
+    pub fn init(&self, args: NewCallArgs) {
+        let mut io = self.io();
+        engine::set_state(&mut io, args.into());
+        let migrations = migration::registry::<StandaloneIO>();
+        migration::set_version(&mut io, migration::latest_version(&migrations));
+
+        let current_account_id = self.env.current_account_id.clone();
+        let connector_args = InitCallArgs {
+            prover_account: current_account_id.clone(),
+            eth_custodian_address: "0000000000000000000000000000000000000000".to_string(),
+            metadata: FungibleTokenMetadata::default(),
+        };
+        EthConnectorContract::init_contract(io, current_account_id, connector_args);
+    }
+
+    /// Creates (or tops up) an account, minting the given balance through the eth-connector
+    /// so the total supply stays consistent.
+    pub fn mint_account(&self, address: Address, balance: Wei, nonce: U256) {
+        let mut io = self.io();
+        engine::set_nonce(&mut io, &address, &nonce);
+        EthConnectorContract::get_instance(io).internal_add_eth(&address, &balance.raw());
+    }
+
+    /// Process a signed Ethereum transaction, as the `submit` method.
+    pub fn submit(&mut self, transaction_bytes: &[u8]) -> Result<SubmitResult, RunnerError> {
+        let relayer_address = self.predecessor_address();
+        self.atomic(|io, env, promises| {
+            let state = engine::get_state(&io)?;
+            let result = engine::submit(
+                io,
+                env,
+                transaction_bytes,
+                state,
+                env.current_account_id.clone(),
+                relayer_address,
+                promises,
+            )?;
+            Ok(result)
+        })
+    }
+
+    /// Process signed Ethereum transactions in order, as the `submit_batch` method.
+    pub fn submit_batch(
+        &mut self,
+        transactions: &[Vec<u8>],
+    ) -> Result<Vec<BatchTransactionResult>, RunnerError> {
+        let io = StandaloneIO::new(&self.storage);
+        let state = engine::get_state(&io)?;
+        let relayer_address =
+            near_account_to_evm_address(self.env.predecessor_account_id.as_bytes());
+        Ok(engine::submit_batch(
+            io,
+            &self.env,
+            transactions,
+            state,
+            self.env.current_account_id.clone(),
+            relayer_address,
+            &mut self.promises,
+        ))
+    }
+
+    /// Call a contract in the EVM, as the `call` method.
+    pub fn call(&mut self, args: CallArgs) -> Result<SubmitResult, RunnerError> {
+        let origin = self.predecessor_address();
+        self.atomic(|io, env, promises| {
+            let mut engine = Engine::new(origin, env.current_account_id.clone(), io, env)?;
+            let result = engine.call_with_args(args, promises)?;
+            Ok(result)
+        })
+    }
+
+    /// Deploy code into the EVM, as the `deploy_code` method.
+    pub fn deploy_code(&mut self, input: Vec<u8>) -> Result<SubmitResult, RunnerError> {
+        let origin = self.predecessor_address();
+        self.atomic(|io, env, promises| {
+            let mut engine = Engine::new(origin, env.current_account_id.clone(), io, env)?;
+            let result = engine.deploy_code_with_input(input, promises)?;
+            Ok(result)
+        })
+    }
+
+    /// Execute a read-only call in the EVM, as the `view` method.
+    pub fn view(&self, args: ViewCallArgs) -> Result<TransactionStatus, RunnerError> {
+        let engine = Engine::new(
+            Address::from_slice(&args.sender),
+            self.env.current_account_id.clone(),
+            self.io(),
+            &self.env,
+        )?;
+        let result = engine
+            .view_with_args(args)
+            .map_err(|e| RunnerError::Engine(e.into()))?;
+        Ok(result)
+    }
+
+    /// Execute a view call on top of state and block overrides (see the `view` contract
+    /// method), leaving the storage unchanged.
+    pub fn view_with_overrides(
+        &self,
+        args: ViewCallWithOverridesArgs,
+    ) -> Result<TransactionStatus, RunnerError> {
+        let io = self.io();
+        let state = engine::get_state(&io)?;
+        let current_account_id = self.env.current_account_id.clone();
+        overrides::view_with_overrides(io, &self.env, state, current_account_id, args)
+            .map_err(|e| RunnerError::Engine(e.into()))
+    }
+
+    pub fn estimate_gas(&self, args: EstimateGasArgs) -> Result<EstimateGasResult, RunnerError> {
+        let engine = Engine::new(
+            Address::from_slice(&args.sender),
+            self.env.current_account_id.clone(),
+            self.io(),
+            &self.env,
+        )?;
+        let result = engine
+            .estimate_gas(args)
+            .map_err(|e| RunnerError::Engine(e.into()))?;
+        Ok(result)
+    }
+
+    /// Process a signed Ethereum transaction (as `submit`) while forwarding the events of
+    /// the EVM to the given listener (e.g. `StepTracer` or `CallTracer`).
+    #[cfg(feature = "tracing")]
+    pub fn trace_submit<T: TraceListener + 'static>(
+        &mut self,
+        transaction_bytes: &[u8],
+        listener: T,
+    ) -> (T, Result<SubmitResult, RunnerError>) {
+        tracing::traced_call(listener, || self.submit(transaction_bytes))
+    }
+
+    /// Trace a signed Ethereum transaction (as the `debug_trace_transaction` method), leaving
+    /// the storage unchanged.
+    #[cfg(feature = "tracing")]
+    pub fn trace_transaction<T: TraceListener + 'static>(
+        &self,
+        transaction_bytes: &[u8],
+        listener: T,
+    ) -> (T, Result<SubmitResult, RunnerError>) {
+        let io = self.io();
+        let state = match engine::get_state(&io) {
+            Ok(state) => state,
+            Err(e) => return (listener, Err(e.into())),
+        };
+        let (listener, result) = tracing::trace_transaction(
+            io,
+            &self.env,
+            transaction_bytes,
+            state,
+            self.env.current_account_id.clone(),
+            self.predecessor_address(),
+            &mut PromiseRecorder::default(),
+            listener,
+        );
+        (listener, result.map_err(RunnerError::Engine))
+    }
+
+    /// Execute a read-only call (as `view`) while forwarding the events of the EVM to the
+    /// given listener.
+    #[cfg(feature = "tracing")]
+    pub fn trace_view<T: TraceListener + 'static>(
+        &self,
+        args: ViewCallArgs,
+        listener: T,
+    ) -> (T, Result<TransactionStatus, RunnerError>) {
+        tracing::traced_call(listener, || self.view(args))
+    }
+
+    /// Start a new block with the given context (see the `begin_block` contract method).
+    #[cfg(feature = "evm_bully")]
+    pub fn begin_block(&self, args: &BeginBlockArgs) {
+        engine::begin_block(&mut self.io(), args);
+    }
+
+    pub fn get_balance(&self, address: &Address) -> Wei {
+        engine::get_balance(&self.io(), address)
+    }
+
+    pub fn get_nonce(&self, address: &Address) -> U256 {
+        engine::get_nonce(&self.io(), address)
+    }
+
+    pub fn get_code(&self, address: &Address) -> Vec<u8> {
+        engine::get_code(&self.io(), address)
+    }
+
+    pub fn get_storage(&self, address: &Address, key: &H256) -> H256 {
+        let io = self.io();
+        let generation = engine::get_generation(&io, address);
+        engine::get_storage(&io, address, key, generation)
+    }
+
+    /// Receipt of a transaction submitted with `submit`, given the keccak hash of its bytes.
+    pub fn get_transaction_receipt(&self, transaction_hash: &H256) -> Option<TransactionReceipt> {
+        engine::get_transaction_receipt(&self.io(), transaction_hash)
+    }
+
+    /// Transactions of `sender` queued by `submit` until their nonce is reached.
+    pub fn get_pending_transactions(&self, sender: &Address) -> Vec<PendingTransaction> {
+        transaction_queue::get_pending_transactions(&self.io(), sender)
+    }
+
+    /// Logs of the transactions submitted with `submit` matching the query.
+    pub fn get_logs(&self, args: &GetLogsArgs) -> Result<Vec<FilteredLog>, GetLogsError> {
+        logs_index::get_logs(&self.io(), args)
+    }
+
+    /// Total ETH supply on Aurora, as tracked by the eth-connector.
+    pub fn get_total_eth_supply(&self) -> u128 {
+        EthConnectorContract::get_instance(self.io()).ft_total_eth_supply_on_aurora();
+        let output = self.storage.borrow_mut().take_output().unwrap_or_default();
+        String::from_utf8(output)
+            .ok()
+            .and_then(|supply| supply.trim_matches('"').parse().ok())
+            .expect("ERR_TOTAL_SUPPLY_OUTPUT")
+    }
+
+    /// Runs `f` over a copy-on-write layer on top of the storage, whose changes are only
+    /// written to the storage if `f` succeeds. On NEAR, the contract panics on error, which
+    /// reverts the changes made before the error.
+    fn atomic<T, F>(&mut self, f: F) -> Result<T, RunnerError>
+    where
+        F: FnOnce(
+            OverlayIO<'_, StandaloneIO<'_>>,
+            &StandaloneEnv,
+            &mut PromiseRecorder,
+        ) -> Result<T, RunnerError>,
+    {
+        let buffer = OverlayBuffer::default();
+        let overlay = OverlayIO::new(StandaloneIO::new(&self.storage), &buffer);
+        let result = f(overlay, &self.env, &mut self.promises);
+        match result {
+            Ok(_) => overlay.commit(),
+            Err(_) => overlay.discard(),
+        }
+        result
+    }
+
+    fn predecessor_address(&self) -> Address {
+        near_account_to_evm_address(self.env.predecessor_account_id.as_bytes())
+    }
+}
@@ -0,0 +1,160 @@
+use aurora_engine_sdk::io::{StorageIntermediate, IO};
+use borsh::{BorshDeserialize, BorshSerialize};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::{fs, io};
+
+/// In-memory key-value store holding the engine state, together with the
+/// buffers used for the input and output of a method call.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Storage {
+    state: BTreeMap<Vec<u8>, Vec<u8>>,
+    input: Vec<u8>,
+    output: Option<Vec<u8>>,
+}
+
+impl Storage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads a snapshot of the state previously written by `Storage::save`.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let bytes = fs::read(path)?;
+        let state = BTreeMap::<Vec<u8>, Vec<u8>>::try_from_slice(&bytes)?;
+        Ok(Self {
+            state,
+            ..Self::default()
+        })
+    }
+
+    /// Writes a (borsh encoded) snapshot of the state to the given file.
+    /// The input and output buffers are not persisted.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let bytes = self.state.try_to_vec()?;
+        fs::write(path, bytes)
+    }
+
+    pub fn get(&self, key: &[u8]) -> Option<&[u8]> {
+        self.state.get(key).map(Vec::as_slice)
+    }
+
+    pub fn insert(&mut self, key: Vec<u8>, value: Vec<u8>) -> Option<Vec<u8>> {
+        self.state.insert(key, value)
+    }
+
+    pub fn remove(&mut self, key: &[u8]) -> Option<Vec<u8>> {
+        self.state.remove(key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.state.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.state.is_empty()
+    }
+
+    /// Iterates over all entries whose key starts with `prefix`, in key order.
+    pub fn iter_prefix<'a>(
+        &'a self,
+        prefix: &'a [u8],
+    ) -> impl Iterator<Item = (&'a [u8], &'a [u8])> + 'a {
+        self.state
+            .range(prefix.to_vec()..)
+            .take_while(move |(key, _)| key.starts_with(prefix))
+            .map(|(key, value)| (key.as_slice(), value.as_slice()))
+    }
+
+    /// Sets the bytes returned by `IO::read_input`.
+    pub fn set_input(&mut self, input: Vec<u8>) {
+        self.input = input;
+    }
+
+    /// Takes the bytes passed to `IO::return_output` (if any).
+    pub fn take_output(&mut self) -> Option<Vec<u8>> {
+        self.output.take()
+    }
+}
+
+/// Owned bytes read through `StandaloneIO`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Value(Vec<u8>);
+
+impl Value {
+    pub fn into_vec(self) -> Vec<u8> {
+        self.0
+    }
+}
+
+impl StorageIntermediate for Value {
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    fn copy_to_slice(&self, buffer: &mut [u8]) {
+        buffer.copy_from_slice(&self.0)
+    }
+}
+
+/// Implementation of `IO` over a shared `Storage`. The handle is `Copy`
+/// so it can be given to the `Engine` the same way as the NEAR `Runtime`.
+#[derive(Debug, Clone, Copy)]
+pub struct StandaloneIO<'a> {
+    storage: &'a RefCell<Storage>,
+}
+
+impl<'a> StandaloneIO<'a> {
+    pub fn new(storage: &'a RefCell<Storage>) -> Self {
+        Self { storage }
+    }
+}
+
+impl<'a> IO for StandaloneIO<'a> {
+    type StorageValue = Value;
+
+    fn read_input(&self) -> Self::StorageValue {
+        Value(self.storage.borrow().input.clone())
+    }
+
+    fn return_output(&mut self, value: &[u8]) {
+        self.storage.borrow_mut().output = Some(value.to_vec());
+    }
+
+    fn read_storage(&self, key: &[u8]) -> Option<Self::StorageValue> {
+        self.storage.borrow().state.get(key).cloned().map(Value)
+    }
+
+    fn storage_has_key(&self, key: &[u8]) -> bool {
+        self.storage.borrow().state.contains_key(key)
+    }
+
+    fn write_storage(&mut self, key: &[u8], value: &[u8]) -> Option<Self::StorageValue> {
+        self.storage
+            .borrow_mut()
+            .state
+            .insert(key.to_vec(), value.to_vec())
+            .map(Value)
+    }
+
+    fn write_storage_direct(
+        &mut self,
+        key: &[u8],
+        value: Self::StorageValue,
+    ) -> Option<Self::StorageValue> {
+        self.storage
+            .borrow_mut()
+            .state
+            .insert(key.to_vec(), value.0)
+            .map(Value)
+    }
+
+    fn remove_storage(&mut self, key: &[u8]) -> Option<Self::StorageValue> {
+        self.storage.borrow_mut().state.remove(key).map(Value)
+    }
+}
@@ -37,7 +37,10 @@ pub(crate) mod one_inch;
 pub(crate) mod rust;
 pub(crate) mod self_destruct;
 pub(crate) mod solidity;
+pub(crate) mod standalone;
 pub(crate) mod standard_precompiles;
+#[cfg(feature = "evm_bully")]
+pub(crate) mod state_tests;
 pub(crate) mod uniswap;
 
 pub(crate) struct Signer {
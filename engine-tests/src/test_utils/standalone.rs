@@ -0,0 +1,34 @@
+use aurora_engine_standalone::EngineRunner;
+use secp256k1::SecretKey;
+
+use crate::prelude::parameters::{NewCallArgs, SubmitResult};
+use crate::prelude::transaction::legacy::TransactionLegacy;
+use crate::prelude::U256;
+use crate::test_utils::{sign_transaction, str_to_account_id};
+
+pub(crate) const CHAIN_ID: u64 = 1313161556; // NEAR betanet
+
+/// Creates an engine running natively (see `aurora-engine-standalone`),
+/// initialized the same way as `test_utils::deploy_evm`.
+pub(crate) fn deploy_evm() -> EngineRunner {
+    let runner = EngineRunner::default();
+    let args = NewCallArgs {
+        chain_id: crate::prelude::u256_to_arr(&U256::from(CHAIN_ID)),
+        owner_id: str_to_account_id("aurora"),
+        bridge_prover_id: str_to_account_id("bridge_prover.near"),
+        upgrade_delay_blocks: 1,
+    };
+    runner.init(args);
+    runner
+}
+
+pub(crate) fn submit_transaction(
+    runner: &mut EngineRunner,
+    account: &SecretKey,
+    transaction: TransactionLegacy,
+) -> SubmitResult {
+    let signed_tx = sign_transaction(transaction, Some(CHAIN_ID), account);
+    runner
+        .submit(&rlp::encode(&signed_tx))
+        .expect("Failed to submit transaction")
+}
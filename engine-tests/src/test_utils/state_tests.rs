@@ -0,0 +1,609 @@
+//! Runner for the `GeneralStateTests` of the Ethereum consensus test suite
+//! (https://github.com/ethereum/tests).
+//!
+//! Each test is executed on a fresh engine running natively: the pre-state is written with
+//! the engine storage helpers, the block context is set through `begin_block` and the
+//! transaction is executed with `Engine::call`/`Engine::deploy_code`, paying for gas like
+//! `submit` does (the coinbase being the relayer). The state root and the hash of the logs
+//! are then compared with the ones of the fixture.
+
+use aurora_engine::engine::{self, Engine, GasPaymentError};
+use aurora_engine::fee_policy::FeePolicy;
+use aurora_engine::parameters::{BeginBlockArgs, NewCallArgs, ResultLog};
+use aurora_engine_standalone::{EngineRunner, Storage};
+use rlp::RlpStream;
+use secp256k1::SecretKey;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryInto;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::prelude::storage::{KeyPrefix, VersionPrefix};
+use crate::prelude::transaction::eip_2930::AccessTuple;
+use crate::prelude::transaction::NormalizedEthTransaction;
+use crate::prelude::{sdk, u256_to_arr, Address, Wei, H256, U256};
+use crate::test_utils::{address_from_secret_key, str_to_account_id};
+
+/// Forks the engine is checked against.
+pub(crate) const FORKS: [&str; 2] = ["Berlin", "London"];
+
+/// Chain ID used to fill the consensus tests.
+const CHAIN_ID: u64 = 1;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct StateTest {
+    pub env: BlockEnv,
+    pub pre: BTreeMap<String, AccountState>,
+    pub transaction: TransactionParts,
+    pub post: HashMap<String, Vec<PostState>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BlockEnv {
+    pub current_coinbase: String,
+    pub current_difficulty: String,
+    pub current_gas_limit: String,
+    pub current_number: String,
+    pub current_timestamp: String,
+    pub current_base_fee: Option<String>,
+    pub previous_hash: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct AccountState {
+    pub balance: String,
+    pub code: String,
+    pub nonce: String,
+    pub storage: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TransactionParts {
+    pub data: Vec<String>,
+    pub gas_limit: Vec<String>,
+    pub value: Vec<String>,
+    pub nonce: String,
+    pub secret_key: String,
+    pub to: String,
+    pub gas_price: Option<String>,
+    pub max_fee_per_gas: Option<String>,
+    pub max_priority_fee_per_gas: Option<String>,
+    pub access_lists: Option<Vec<Option<Vec<AccessListItem>>>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AccessListItem {
+    pub address: String,
+    pub storage_keys: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PostState {
+    pub hash: String,
+    pub logs: String,
+    pub indexes: PostIndexes,
+    pub expect_exception: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct PostIndexes {
+    pub data: usize,
+    pub gas: usize,
+    pub value: usize,
+}
+
+/// Result of running one post-state entry of a test.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Outcome {
+    Passed,
+    Failed(String),
+    /// The entry expects the transaction to be invalid, or cannot be represented in the engine.
+    Skipped(String),
+}
+
+/// Pass/fail counts of a fork.
+#[derive(Debug, Default)]
+pub(crate) struct ForkReport {
+    pub passed: usize,
+    pub failed: Vec<String>,
+    pub skipped: Vec<String>,
+}
+
+/// Per-fork report of a test run.
+#[derive(Debug, Default)]
+pub(crate) struct Report {
+    pub forks: BTreeMap<String, ForkReport>,
+}
+
+impl Report {
+    pub(crate) fn record(&mut self, fork: &str, test_name: String, outcome: Outcome) {
+        let fork_report = self.forks.entry(fork.to_string()).or_default();
+        match outcome {
+            Outcome::Passed => fork_report.passed += 1,
+            Outcome::Failed(reason) => fork_report
+                .failed
+                .push(format!("{}: {}", test_name, reason)),
+            Outcome::Skipped(reason) => fork_report
+                .skipped
+                .push(format!("{}: {}", test_name, reason)),
+        }
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (fork, report) in &self.forks {
+            writeln!(
+                f,
+                "{}: {} passed, {} failed, {} skipped",
+                fork,
+                report.passed,
+                report.failed.len(),
+                report.skipped.len()
+            )?;
+            for failure in &report.failed {
+                writeln!(f, "  FAILED {}", failure)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Lists the JSON fixtures under `dir`, recursively and in path order.
+pub(crate) fn find_fixtures(dir: &Path) -> Vec<PathBuf> {
+    let mut result = Vec::new();
+    let mut entries: Vec<PathBuf> = std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    entries.sort();
+    for path in entries {
+        if path.is_dir() {
+            result.extend(find_fixtures(&path));
+        } else if path.extension().map(|ext| ext == "json").unwrap_or(false) {
+            result.push(path);
+        }
+    }
+    result
+}
+
+pub(crate) fn load_fixture(path: &Path) -> BTreeMap<String, StateTest> {
+    let reader = std::io::BufReader::new(std::fs::File::open(path).unwrap());
+    serde_json::from_reader(reader).unwrap()
+}
+
+/// Runs all the post-state entries of the given forks in a test.
+pub(crate) fn run_state_test(name: &str, test: &StateTest, report: &mut Report) {
+    for fork in FORKS.iter() {
+        let post_states = match test.post.get(*fork) {
+            Some(post_states) => post_states,
+            None => continue,
+        };
+        for post_state in post_states {
+            let test_name = format!(
+                "{}[d{},g{},v{}]",
+                name, post_state.indexes.data, post_state.indexes.gas, post_state.indexes.value
+            );
+            let outcome = run_post_state(test, post_state);
+            report.record(fork, test_name, outcome);
+        }
+    }
+}
+
+fn run_post_state(test: &StateTest, post_state: &PostState) -> Outcome {
+    if let Some(exception) = &post_state.expect_exception {
+        return Outcome::Skipped(exception.clone());
+    }
+
+    let runner = match setup_runner(test) {
+        Ok(runner) => runner,
+        Err(reason) => return Outcome::Skipped(reason),
+    };
+    let transaction = match normalize_transaction(&test.transaction, &post_state.indexes) {
+        Ok(transaction) => transaction,
+        Err(reason) => return Outcome::Skipped(reason),
+    };
+    let coinbase = parse_address(&test.env.current_coinbase);
+    let logs = match execute_transaction(&runner, transaction, coinbase) {
+        Ok(logs) => logs,
+        Err(reason) => return Outcome::Failed(reason),
+    };
+
+    let expected_root = parse_h256(&post_state.hash);
+    let expected_logs = parse_h256(&post_state.logs);
+    let root = state_root(&runner.storage.borrow());
+    let logs = logs_hash(&logs);
+    if root != expected_root {
+        Outcome::Failed(format!(
+            "state root 0x{} instead of 0x{}",
+            hex::encode(root),
+            hex::encode(expected_root)
+        ))
+    } else if logs != expected_logs {
+        Outcome::Failed(format!(
+            "logs hash 0x{} instead of 0x{}",
+            hex::encode(logs),
+            hex::encode(expected_logs)
+        ))
+    } else {
+        Outcome::Passed
+    }
+}
+
+/// Creates an engine holding the pre-state and the block context of the test.
+fn setup_runner(test: &StateTest) -> Result<EngineRunner, String> {
+    let mut runner = EngineRunner::default();
+    runner.init(NewCallArgs {
+        chain_id: u256_to_arr(&U256::from(CHAIN_ID)),
+        owner_id: str_to_account_id("aurora"),
+        bridge_prover_id: str_to_account_id("bridge_prover.near"),
+        upgrade_delay_blocks: 1,
+    });
+
+    let number = parse_u256(&test.env.current_number);
+    runner.env.block_height = number.low_u64();
+    runner.begin_block(&BeginBlockArgs {
+        hash: test
+            .env
+            .previous_hash
+            .as_deref()
+            .map(|hash| parse_h256(hash).0)
+            .unwrap_or_default(),
+        coinbase: parse_address(&test.env.current_coinbase).0,
+        timestamp: u256_to_arr(&parse_u256(&test.env.current_timestamp)),
+        number: u256_to_arr(&number),
+        difficulty: u256_to_arr(&parse_u256(&test.env.current_difficulty)),
+        gaslimit: u256_to_arr(&parse_u256(&test.env.current_gas_limit)),
+    });
+    if let Some(base_fee) = &test.env.current_base_fee {
+        let mut io = runner.io();
+        let mut state = engine::get_state(&io).unwrap();
+        state.base_fee.base_fee_per_gas = u256_to_arr(&parse_u256(base_fee));
+        engine::set_state(&mut io, state);
+    }
+
+    for (address, account) in &test.pre {
+        let address = parse_address(address);
+        let balance = parse_u256(&account.balance);
+        if balance > U256::from(u128::MAX) {
+            return Err(format!("balance of {:?} exceeds the ETH supply", address));
+        }
+        runner.mint_account(address, Wei::new(balance), parse_u256(&account.nonce));
+        let mut io = runner.io();
+        engine::set_code(&mut io, &address, &parse_bytes(&account.code));
+        for (key, value) in &account.storage {
+            let key = H256(u256_to_arr(&parse_u256(key)));
+            let value = H256(u256_to_arr(&parse_u256(value)));
+            engine::set_storage(&mut io, &address, &key, &value, 0);
+        }
+    }
+    Ok(runner)
+}
+
+fn normalize_transaction(
+    transaction: &TransactionParts,
+    indexes: &PostIndexes,
+) -> Result<NormalizedEthTransaction, String> {
+    let secret_key = SecretKey::parse_slice(&parse_bytes(&transaction.secret_key))
+        .map_err(|_| "invalid secret key".to_string())?;
+    let (max_priority_fee_per_gas, max_fee_per_gas) = match &transaction.gas_price {
+        Some(gas_price) => (parse_u256(gas_price), parse_u256(gas_price)),
+        None => (
+            parse_u256(transaction.max_priority_fee_per_gas.as_ref().unwrap()),
+            parse_u256(transaction.max_fee_per_gas.as_ref().unwrap()),
+        ),
+    };
+    let access_list = transaction
+        .access_lists
+        .as_ref()
+        .and_then(|access_lists| access_lists[indexes.data].as_ref())
+        .map(|access_list| {
+            access_list
+                .iter()
+                .map(|item| AccessTuple {
+                    address: parse_address(&item.address),
+                    storage_keys: item
+                        .storage_keys
+                        .iter()
+                        .map(|key| parse_h256(key))
+                        .collect(),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    Ok(NormalizedEthTransaction {
+        address: Some(address_from_secret_key(&secret_key)),
+        chain_id: Some(CHAIN_ID),
+        nonce: parse_u256(&transaction.nonce),
+        gas_limit: parse_u256(&transaction.gas_limit[indexes.gas]),
+        max_priority_fee_per_gas,
+        max_fee_per_gas,
+        to: if transaction.to.is_empty() {
+            None
+        } else {
+            Some(parse_address(&transaction.to))
+        },
+        value: Wei::new(parse_u256(&transaction.value[indexes.value])),
+        data: parse_bytes(&transaction.data[indexes.data]),
+        access_list,
+    })
+}
+
+/// Executes the transaction like `engine::submit`, without the checks done before charging
+/// for gas (the consensus tests expecting an invalid transaction are skipped).
+fn execute_transaction(
+    runner: &EngineRunner,
+    transaction: NormalizedEthTransaction,
+    coinbase: Address,
+) -> Result<Vec<ResultLog>, String> {
+    let mut io = runner.io();
+    let mut promises = aurora_engine_standalone::PromiseRecorder::default();
+    let state = engine::get_state(&io).unwrap();
+    let sender = transaction.address.unwrap();
+    let current_account_id = runner.env.current_account_id.clone();
+    let mut engine = Engine::new_with_state(state, sender, current_account_id, io, &runner.env);
+
+    let gas_result = match engine.charge_gas(&sender, &transaction) {
+        Ok(gas_result) => gas_result,
+        Err(GasPaymentError::OutOfFund) => {
+            engine::increment_nonce(&mut io, &sender);
+            return Ok(Vec::new());
+        }
+        Err(e) => return Err(format!("failed to charge gas: {:?}", e)),
+    };
+    let gas_limit: u64 = transaction
+        .gas_limit
+        .try_into()
+        .map_err(|_| "gas limit overflow".to_string())?;
+    let access_list = transaction
+        .access_list
+        .into_iter()
+        .map(|a| (a.address, a.storage_keys))
+        .collect();
+    let result = match transaction.to {
+        Some(receiver) => engine.call(
+            sender,
+            receiver,
+            transaction.value,
+            transaction.data,
+            gas_limit,
+            access_list,
+            &mut promises,
+        ),
+        None => engine.deploy_code(
+            sender,
+            transaction.value,
+            transaction.data,
+            gas_limit,
+            access_list,
+            &mut promises,
+        ),
+    };
+    let (gas_used, logs) = match result {
+        Ok(submit_result) => (submit_result.gas_used, submit_result.logs),
+        Err(e) => (e.gas_used, Vec::new()),
+    };
+    engine::refund_unused_gas(
+        &mut io,
+        &sender,
+        gas_used,
+        gas_result,
+        &coinbase,
+        &FeePolicy::default(),
+    )
+    .map_err(|e| format!("failed to refund gas: {:?}", e))?;
+    Ok(logs)
+}
+
+/// Root of the secure Merkle Patricia trie of the accounts of the engine. Empty accounts
+/// are left out, as they are removed at the end of a transaction since EIP-161.
+pub(crate) fn state_root(storage: &Storage) -> H256 {
+    let mut addresses = std::collections::BTreeSet::new();
+    for prefix in [KeyPrefix::Nonce, KeyPrefix::Balance, KeyPrefix::Code].iter() {
+        let key_prefix = [VersionPrefix::V1 as u8, *prefix as u8];
+        for (key, _) in storage.iter_prefix(&key_prefix) {
+            addresses.insert(Address::from_slice(&key[2..22]));
+        }
+    }
+
+    let mut accounts = Vec::new();
+    for address in addresses {
+        let nonce = read_u256(storage, KeyPrefix::Nonce, &address);
+        let balance = read_u256(storage, KeyPrefix::Balance, &address);
+        let code = storage
+            .get(&account_key(KeyPrefix::Code, &address))
+            .unwrap_or_default();
+        let storage_root = storage_root(storage, &address);
+        if nonce.is_zero() && balance.is_zero() && code.is_empty() && storage_root == EMPTY_ROOT {
+            continue;
+        }
+
+        let mut stream = RlpStream::new_list(4);
+        stream.append(&nonce);
+        stream.append(&balance);
+        stream.append(&storage_root);
+        stream.append(&sdk::keccak(code));
+        accounts.push((
+            sdk::keccak(address.as_bytes()).0.to_vec(),
+            stream.out().to_vec(),
+        ));
+    }
+    trie_root(accounts)
+}
+
+fn storage_root(storage: &Storage, address: &Address) -> H256 {
+    let generation = storage
+        .get(&account_key(KeyPrefix::Generation, address))
+        .map(|value| u32::from_be_bytes(value.try_into().unwrap()))
+        .unwrap_or(0);
+    let mut prefix = account_key(KeyPrefix::Storage, address).to_vec();
+    if generation > 0 {
+        prefix.extend_from_slice(&generation.to_le_bytes());
+    }
+    // The keys of the first generation are a prefix of the ones of the following ones.
+    let key_len = prefix.len() + 32;
+    let slots = storage
+        .iter_prefix(&prefix)
+        .filter(|(key, value)| key.len() == key_len && value.iter().any(|b| *b != 0))
+        .map(|(key, value)| {
+            let slot = &key[key_len - 32..];
+            (
+                sdk::keccak(slot).0.to_vec(),
+                rlp::encode(&U256::from_big_endian(value)).to_vec(),
+            )
+        })
+        .collect();
+    trie_root(slots)
+}
+
+fn account_key(prefix: KeyPrefix, address: &Address) -> [u8; 22] {
+    crate::prelude::storage::address_to_key(prefix, address)
+}
+
+fn read_u256(storage: &Storage, prefix: KeyPrefix, address: &Address) -> U256 {
+    storage
+        .get(&account_key(prefix, address))
+        .map(U256::from_big_endian)
+        .unwrap_or_default()
+}
+
+/// Hash of the RLP encoded list of logs, as in the consensus tests.
+pub(crate) fn logs_hash(logs: &[ResultLog]) -> H256 {
+    let mut stream = RlpStream::new_list(logs.len());
+    for log in logs {
+        stream.begin_list(3);
+        stream.append(&Address(log.address));
+        stream.begin_list(log.topics.len());
+        for topic in &log.topics {
+            stream.append(&H256(*topic));
+        }
+        stream.append(&log.data);
+    }
+    sdk::keccak(&stream.out())
+}
+
+/// Root of the empty trie: `keccak(rlp(""))`.
+const EMPTY_ROOT: H256 = H256([
+    0x56, 0xe8, 0x1f, 0x17, 0x1b, 0xcc, 0x55, 0xa6, 0xff, 0x83, 0x45, 0xe6, 0x92, 0xc0, 0xf8, 0x6e,
+    0x5b, 0x48, 0xe0, 0x1b, 0x99, 0x6c, 0xad, 0xc0, 0x01, 0x62, 0x2f, 0xb5, 0xe3, 0x63, 0xb4, 0x21,
+]);
+
+/// Root hash of the Merkle Patricia trie holding the given (key, RLP encoded value) pairs.
+pub(crate) fn trie_root(entries: Vec<(Vec<u8>, Vec<u8>)>) -> H256 {
+    if entries.is_empty() {
+        return EMPTY_ROOT;
+    }
+    let mut entries: Vec<(Vec<u8>, Vec<u8>)> = entries
+        .into_iter()
+        .map(|(key, value)| (to_nibbles(&key), value))
+        .collect();
+    entries.sort();
+    sdk::keccak(&encode_node(&entries, 0))
+}
+
+fn to_nibbles(bytes: &[u8]) -> Vec<u8> {
+    bytes.iter().flat_map(|b| vec![b >> 4, b & 0x0f]).collect()
+}
+
+/// Hex-prefix encoding of a path (see appendix C of the yellow paper).
+fn hex_prefix(nibbles: &[u8], is_leaf: bool) -> Vec<u8> {
+    let flag = if is_leaf { 2 } else { 0 };
+    let mut result = Vec::with_capacity(nibbles.len() / 2 + 1);
+    let rest = if nibbles.len() % 2 == 1 {
+        result.push(((flag + 1) << 4) | nibbles[0]);
+        &nibbles[1..]
+    } else {
+        result.push(flag << 4);
+        nibbles
+    };
+    result.extend(rest.chunks(2).map(|pair| (pair[0] << 4) | pair[1]));
+    result
+}
+
+/// RLP encoding of the node holding the given sorted entries, whose keys share their
+/// first `depth` nibbles.
+fn encode_node(entries: &[(Vec<u8>, Vec<u8>)], depth: usize) -> Vec<u8> {
+    if entries.len() == 1 {
+        let (key, value) = &entries[0];
+        let mut stream = RlpStream::new_list(2);
+        stream.append(&hex_prefix(&key[depth..], true));
+        stream.append(value);
+        return stream.out().to_vec();
+    }
+
+    // Since the entries are sorted, the common prefix is the one of the first and last keys.
+    let first = &entries[0].0;
+    let last = &entries[entries.len() - 1].0;
+    let common_len = first[depth..]
+        .iter()
+        .zip(last[depth..].iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    if common_len > 0 {
+        let mut stream = RlpStream::new_list(2);
+        stream.append(&hex_prefix(&first[depth..depth + common_len], false));
+        append_child(&mut stream, &encode_node(entries, depth + common_len));
+        return stream.out().to_vec();
+    }
+
+    let mut stream = RlpStream::new_list(17);
+    let (terminating, children): (Vec<_>, Vec<_>) =
+        entries.iter().partition(|(key, _)| key.len() == depth);
+    for nibble in 0..16u8 {
+        let branch: Vec<(Vec<u8>, Vec<u8>)> = children
+            .iter()
+            .filter(|(key, _)| key[depth] == nibble)
+            .map(|entry| (*entry).clone())
+            .collect();
+        if branch.is_empty() {
+            stream.append_empty_data();
+        } else {
+            append_child(&mut stream, &encode_node(&branch, depth + 1));
+        }
+    }
+    match terminating.first() {
+        Some((_, value)) => stream.append(value),
+        None => stream.append_empty_data(),
+    };
+    stream.out().to_vec()
+}
+
+/// Nodes shorter than a hash are embedded in their parent.
+fn append_child(stream: &mut RlpStream, node: &[u8]) {
+    if node.len() < 32 {
+        stream.append_raw(node, 1);
+    } else {
+        stream.append(&sdk::keccak(node));
+    }
+}
+
+fn strip_hex_prefix(value: &str) -> &str {
+    value.strip_prefix("0x").unwrap_or(value)
+}
+
+fn parse_bytes(value: &str) -> Vec<u8> {
+    hex::decode(strip_hex_prefix(value)).unwrap()
+}
+
+fn parse_u256(value: &str) -> U256 {
+    let value = strip_hex_prefix(value);
+    if value.is_empty() {
+        U256::zero()
+    } else {
+        U256::from_str_radix(value, 16).unwrap()
+    }
+}
+
+fn parse_h256(value: &str) -> H256 {
+    H256::from_slice(&parse_bytes(value))
+}
+
+fn parse_address(value: &str) -> Address {
+    Address::from_slice(&parse_bytes(value))
+}
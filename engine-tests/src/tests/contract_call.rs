@@ -5,6 +5,10 @@ use crate::test_utils;
 use crate::test_utils::exit_precompile::{Tester, TesterConstructor, DEST_ACCOUNT, DEST_ADDRESS};
 
 fn setup_test() -> (AuroraRunner, Signer, [u8; 20], Tester) {
+    setup_test_with(TesterConstructor::load())
+}
+
+fn setup_test_with(tester_ctr: TesterConstructor) -> (AuroraRunner, Signer, [u8; 20], Tester) {
     let mut runner = AuroraRunner::new();
     let token = runner.deploy_erc20_token(&"tt.testnet".to_string());
     let mut signer = test_utils::Signer::random();
@@ -14,7 +18,6 @@ fn setup_test() -> (AuroraRunner, Signer, [u8; 20], Tester) {
         U256::zero(),
     );
 
-    let tester_ctr = TesterConstructor::load();
     let nonce = signer.use_nonce();
 
     let tester: Tester = runner
@@ -92,7 +95,7 @@ fn withdraw() {
                 value: ethabi::Token::Address(address),
             }
         };
-        let expected_event = vec![
+        let mut expected_event = vec![
             ethabi::LogParam {
                 name: "sender".to_string(),
                 value: ethabi::Token::Address(token),
@@ -107,6 +110,9 @@ fn withdraw() {
                 value: ethabi::Token::Uint(1.into()),
             },
         ];
+        if is_to_near {
+            expected_event.extend(exit_to_near_options("", ""));
+        }
         assert_eq!(&expected_event, &exit_events[0].params);
 
         // One promise is scheduled
@@ -203,12 +209,14 @@ fn withdraw_eth() {
             value: ethabi::Token::Uint(amount.raw()),
         },
     ];
+    expected_event.extend(exit_to_near_options("", ""));
     let exit_events = parse_exit_events(result, &schema);
 
     assert!(exit_events.len() == 1);
     assert_eq!(&expected_event, &exit_events[0].params);
 
     // exit to ethereum
+    expected_event.truncate(4);
     let amount = Wei::new_u64(42);
     let result = tester
         .withdraw_eth(&mut runner, &mut signer, false, amount)
@@ -228,6 +236,49 @@ fn withdraw_eth() {
     assert_eq!(&expected_event, &exit_events[0].params);
 }
 
+#[test]
+fn withdraw_eth_legacy_format() {
+    let (mut runner, mut signer, _token, tester) =
+        setup_test_with(TesterConstructor::load_legacy());
+    let amount = Wei::new_u64(10);
+
+    let result = tester
+        .withdraw_eth(&mut runner, &mut signer, true, amount)
+        .unwrap();
+    let scheduled = aurora_engine_precompiles::native::events::exit_scheduled_schema().signature();
+    let scheduled_count = result
+        .logs
+        .iter()
+        .filter(|log| log.topics.first() == Some(&scheduled.0))
+        .count();
+    let schema = aurora_engine_precompiles::native::events::exit_to_near_schema();
+    let exit_events = parse_exit_events(result, &schema);
+
+    // The recipient is not misread as a refund address
+    assert_eq!(exit_events.len(), 1);
+    let dest = aurora_engine_sdk::keccak(&ethabi::encode(&[ethabi::Token::String(
+        DEST_ACCOUNT.to_string(),
+    )]));
+    assert_eq!(
+        exit_events[0].params[2].value,
+        ethabi::Token::FixedBytes(dest.as_bytes().to_vec())
+    );
+    assert_eq!(
+        exit_events[0].params[3].value,
+        ethabi::Token::Uint(amount.raw())
+    );
+
+    // The transfer is scheduled with a refund to the caller, and the exit gets an ID
+    let transfer = format!("call_contract {}.ft_transfer", runner.aurora_account_id);
+    assert!(runner.previous_logs.contains(&transfer));
+    let refund = format!(
+        "callback_call_contract {}.refund_on_error",
+        runner.aurora_account_id
+    );
+    assert!(runner.previous_logs.contains(&refund));
+    assert_eq!(scheduled_count, 1);
+}
+
 fn parse_exit_events(result: SubmitResult, schema: &ethabi::Event) -> Vec<ethabi::Log> {
     let signature = schema.signature();
     result
@@ -248,3 +299,17 @@ fn parse_exit_events(result: SubmitResult, schema: &ethabi::Event) -> Vec<ethabi
         })
         .collect()
 }
+
+/// The `memo` and `msg` parameters of an `ExitToNear` event.
+fn exit_to_near_options(memo: &str, msg: &str) -> Vec<ethabi::LogParam> {
+    vec![
+        ethabi::LogParam {
+            name: "memo".to_string(),
+            value: ethabi::Token::String(memo.to_string()),
+        },
+        ethabi::LogParam {
+            name: "msg".to_string(),
+            value: ethabi::Token::String(msg.to_string()),
+        },
+    ]
+}
@@ -1,10 +1,13 @@
 use crate::prelude::Wei;
-use crate::prelude::{H256, U256};
-use crate::test_utils;
+use crate::prelude::{u256_to_arr, H256, U256};
+use crate::test_utils::{self, standalone};
+use aurora_engine::base_fee::{BaseFeeMode, BaseFeeState};
+use aurora_engine::engine::{self, EngineErrorKind, GasPaymentError};
 use aurora_engine::parameters::SubmitResult;
 use aurora_engine::transaction::eip_1559::{self, SignedTransaction1559, Transaction1559};
 use aurora_engine::transaction::eip_2930::AccessTuple;
 use aurora_engine::transaction::EthTransactionKind;
+use aurora_engine_standalone::RunnerError;
 use borsh::BorshDeserialize;
 use std::convert::TryFrom;
 use std::iter;
@@ -48,7 +51,7 @@ fn test_eip_1559_tx_encoding_decoding() {
 }
 
 // Test inspired by https://github.com/ethereum/tests/blob/develop/GeneralStateTests/stExample/eip1559.json
-// but modified slightly because our BASEFEE is always 0.
+// but modified slightly because our BASEFEE is 0 by default.
 #[test]
 fn test_eip_1559_example() {
     let mut runner = test_utils::deploy_evm();
@@ -106,6 +109,73 @@ fn test_eip_1559_example() {
     assert_eq!(runner.get_balance(coinbase), Wei::new_u64(0x73834));
 }
 
+#[test]
+fn test_eip_1559_base_fee_is_burned() {
+    let mut runner = standalone::deploy_evm();
+    let mut signer = exmaple_signer();
+    let signer_address = test_utils::address_from_secret_key(&signer.secret_key);
+    let receiver = test_utils::address_from_hex(CONTRACT_ADDRESS);
+    let relayer = aurora_engine_sdk::types::near_account_to_evm_address(b"aurora");
+    let base_fee_per_gas = U256::from(0x0100);
+    let transfer_amount = Wei::new_u64(0x1000);
+
+    runner.mint_account(signer_address, INITIAL_BALANCE, signer.nonce.into());
+    let mut io = runner.io();
+    let mut state = engine::get_state(&io).unwrap();
+    state.base_fee = BaseFeeState {
+        mode: BaseFeeMode::Fixed,
+        base_fee_per_gas: u256_to_arr(&base_fee_per_gas),
+        ..Default::default()
+    };
+    engine::set_state(&mut io, state);
+    let initial_supply = runner.get_total_eth_supply();
+
+    // A maximum fee below the base fee is rejected
+    let mut transaction = Transaction1559 {
+        chain_id: standalone::CHAIN_ID,
+        nonce: U256::from(signer.nonce),
+        gas_limit: U256::from(0x3d0900),
+        max_fee_per_gas: base_fee_per_gas - 1,
+        max_priority_fee_per_gas: U256::zero(),
+        to: Some(receiver),
+        value: transfer_amount,
+        data: Vec::new(),
+        access_list: Vec::new(),
+    };
+    let signed_tx = test_utils::sign_eip_1559_transaction(transaction.clone(), &signer.secret_key);
+    match runner.submit(&encode_tx(&signed_tx)) {
+        Err(RunnerError::Engine(e)) => assert_eq!(
+            e.kind,
+            EngineErrorKind::GasPayment(GasPaymentError::MaxFeeTooLow)
+        ),
+        other => panic!("Unexpected result {:?}", other),
+    }
+
+    // Otherwise the base fee is burned and the priority fee goes to the relayer
+    let max_priority_fee_per_gas = U256::from(0x0a);
+    transaction.max_fee_per_gas = U256::from(0x07d0);
+    transaction.max_priority_fee_per_gas = max_priority_fee_per_gas;
+    signer.use_nonce();
+    let signed_tx = test_utils::sign_eip_1559_transaction(transaction, &signer.secret_key);
+    let result = runner.submit(&encode_tx(&signed_tx)).unwrap();
+    assert!(result.status.is_ok());
+
+    let gas_used = U256::from(result.gas_used);
+    let burned = gas_used * base_fee_per_gas;
+    let reward = gas_used * max_priority_fee_per_gas;
+    assert_eq!(
+        runner.get_balance(&signer_address),
+        Wei::new(INITIAL_BALANCE.raw() - transfer_amount.raw() - burned - reward)
+    );
+    assert_eq!(runner.get_balance(&receiver), transfer_amount);
+    assert_eq!(runner.get_balance(&relayer), Wei::new(reward));
+    assert_eq!(
+        runner.get_total_eth_supply(),
+        initial_supply - burned.as_u128()
+    );
+    assert_eq!(runner.get_nonce(&signer_address), signer.nonce.into());
+}
+
 fn encode_tx(signed_tx: &SignedTransaction1559) -> Vec<u8> {
     iter::once(eip_1559::TYPE_BYTE)
         .chain(rlp::encode(signed_tx).into_iter())
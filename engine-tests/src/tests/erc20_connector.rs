@@ -391,6 +391,7 @@ mod sim_tests {
     use crate::test_utils::erc20::{ERC20Constructor, ERC20};
     use crate::test_utils::exit_precompile::TesterConstructor;
     use crate::tests::state_migration::{deploy_evm, AuroraAccount};
+    use aurora_engine::exit_status::ExitStatus;
     use aurora_engine::parameters::{
         CallArgs, DeployErc20TokenArgs, FunctionCallArgsV2, SubmitResult,
     };
@@ -438,6 +439,7 @@ mod sim_tests {
             erc20_balance(&erc20, ft_owner_address, &aurora),
             (FT_TRANSFER_AMOUNT - FT_EXIT_AMOUNT).into()
         );
+        assert_eq!(exit_status(1, &aurora), Some(ExitStatus::Completed));
     }
 
     #[test]
@@ -469,17 +471,11 @@ mod sim_tests {
             nep_141_balance_of(aurora.contract.account_id.as_str(), &nep_141, &aurora),
             FT_TRANSFER_AMOUNT
         );
-        #[cfg(feature = "error_refund")]
         assert_eq!(
             erc20_balance(&erc20, ft_owner_address, &aurora),
             FT_TRANSFER_AMOUNT.into()
         );
-        // If the refund feature is not enabled then there is no refund in the EVM
-        #[cfg(not(feature = "error_refund"))]
-        assert_eq!(
-            erc20_balance(&erc20, ft_owner_address, &aurora),
-            (FT_TRANSFER_AMOUNT - FT_EXIT_AMOUNT).into()
-        );
+        assert_eq!(exit_status(1, &aurora), Some(ExitStatus::Refunded));
     }
 
     #[test]
@@ -526,6 +522,7 @@ mod sim_tests {
             eth_balance_of(signer_address, &aurora),
             Wei::new_u64(INITIAL_ETH_BALANCE - ETH_EXIT_AMOUNT)
         );
+        assert_eq!(exit_status(1, &aurora), Some(ExitStatus::Completed));
     }
 
     #[test]
@@ -578,17 +575,12 @@ mod sim_tests {
             nep_141_balance_of(exit_account_id.as_str(), &aurora.contract, &aurora),
             0
         );
-        #[cfg(feature = "error_refund")]
         assert_eq!(
             eth_balance_of(signer_address, &aurora),
             Wei::new_u64(INITIAL_ETH_BALANCE)
         );
-        // If the refund feature is not enabled then there is no refund in the EVM
-        #[cfg(not(feature = "error_refund"))]
-        assert_eq!(
-            eth_balance_of(signer_address, &aurora),
-            Wei::new_u64(INITIAL_ETH_BALANCE - ETH_EXIT_AMOUNT)
-        );
+        assert_eq!(exit_status(1, &aurora), Some(ExitStatus::Refunded));
+        assert_eq!(exit_status(2, &aurora), None);
     }
 
     fn test_exit_to_near_eth_common() -> TestExitToNearEthContext {
@@ -782,6 +774,11 @@ mod sim_tests {
         }
     }
 
+    fn exit_status(exit_id: u64, aurora: &AuroraAccount) -> Option<ExitStatus> {
+        let result = aurora.call("get_exit_status", &exit_id.try_to_vec().unwrap());
+        result.unwrap_borsh()
+    }
+
     fn erc20_balance(erc20: &ERC20, address: Address, aurora: &AuroraAccount) -> U256 {
         let balance_tx = erc20.balance_of(address, 0.into());
         let call_args = CallArgs::V2(FunctionCallArgsV2 {
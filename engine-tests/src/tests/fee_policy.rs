@@ -0,0 +1,176 @@
+use crate::prelude::transaction::legacy::TransactionLegacy;
+use crate::prelude::{sdk, u256_to_arr, Wei, H256, U256};
+use crate::test_utils::{self, standalone};
+use aurora_engine::engine::{self, EngineErrorKind, GasPaymentError};
+use aurora_engine::fee_policy::{
+    self, FeePolicy, FeePolicyError, GasPriceExemptions, FEE_DISTRIBUTED_SIGNATURE,
+};
+use aurora_engine_sdk::types::near_account_to_evm_address;
+use aurora_engine_standalone::RunnerError;
+
+const INITIAL_BALANCE: Wei = Wei::new_u64(1_000_000_000);
+const TRANSFER_AMOUNT: Wei = Wei::new_u64(123);
+
+#[test]
+fn test_fee_policy_distribution() {
+    let mut runner = standalone::deploy_evm();
+    let mut signer = test_utils::Signer::random();
+    let source = test_utils::address_from_secret_key(&signer.secret_key);
+    let dest = test_utils::address_from_hex("0x000000000000000000000000000000000000000a");
+    let treasury = test_utils::address_from_hex("0x000000000000000000000000000000000000000b");
+    let relayer = near_account_to_evm_address(runner.env.predecessor_account_id.as_bytes());
+    runner.mint_account(source, INITIAL_BALANCE, U256::zero());
+
+    let mut io = runner.io();
+    let mut state = engine::get_state(&io).unwrap();
+    state.fee_policy = FeePolicy {
+        treasury_bps: 2_000,
+        burn_bps: 1_000,
+        treasury_address: treasury.0,
+        min_gas_price: u256_to_arr(&U256::from(5)),
+    };
+    engine::set_state(&mut io, state);
+    let initial_supply = runner.get_total_eth_supply();
+
+    let transfer = |nonce: u64, gas_price: u64| TransactionLegacy {
+        gas_price: gas_price.into(),
+        gas_limit: 30_000.into(),
+        ..test_utils::transfer(dest, TRANSFER_AMOUNT, nonce.into())
+    };
+
+    // A gas price below the minimum is rejected
+    let signed_tx = test_utils::sign_transaction(
+        transfer(signer.nonce, 4),
+        Some(standalone::CHAIN_ID),
+        &signer.secret_key,
+    );
+    match runner.submit(&rlp::encode(&signed_tx)) {
+        Err(RunnerError::Engine(e)) => assert_eq!(
+            e.kind,
+            EngineErrorKind::GasPayment(GasPaymentError::GasPriceTooLow)
+        ),
+        other => panic!("Unexpected result {:?}", other),
+    }
+
+    // Otherwise the fee is split between the relayer, the treasury and a burn
+    let gas_price = U256::from(10);
+    let result = standalone::submit_transaction(
+        &mut runner,
+        &signer.secret_key,
+        transfer(signer.use_nonce(), gas_price.low_u64()),
+    );
+    assert!(result.status.is_ok());
+
+    let fee = U256::from(result.gas_used) * gas_price;
+    let treasury_amount = fee * 2_000 / 10_000;
+    let burned = fee * 1_000 / 10_000;
+    let relayer_amount = fee - treasury_amount - burned;
+    assert_eq!(
+        runner.get_balance(&source),
+        Wei::new(INITIAL_BALANCE.raw() - TRANSFER_AMOUNT.raw() - fee)
+    );
+    assert_eq!(runner.get_balance(&relayer), Wei::new(relayer_amount));
+    assert_eq!(runner.get_balance(&treasury), Wei::new(treasury_amount));
+    assert_eq!(
+        runner.get_total_eth_supply(),
+        initial_supply - burned.as_u128()
+    );
+
+    // The distribution is logged by the engine
+    let log = result.logs.last().unwrap();
+    assert_eq!(
+        log.address,
+        engine::current_address(&runner.env.current_account_id).0
+    );
+    assert_eq!(
+        log.topics,
+        vec![
+            sdk::keccak(FEE_DISTRIBUTED_SIGNATURE).0,
+            H256::from(relayer).0,
+            H256::from(treasury).0,
+        ]
+    );
+    let amounts: Vec<u8> = [relayer_amount, treasury_amount, burned]
+        .iter()
+        .flat_map(|amount| u256_to_arr(amount).to_vec())
+        .collect();
+    assert_eq!(log.data, amounts);
+}
+
+#[test]
+fn test_gas_price_exemptions() {
+    let mut runner = standalone::deploy_evm();
+    let mut signer = test_utils::Signer::random();
+    let source = test_utils::address_from_secret_key(&signer.secret_key);
+    let dest = test_utils::address_from_hex("0x000000000000000000000000000000000000000a");
+    runner.mint_account(source, INITIAL_BALANCE, U256::zero());
+
+    let mut io = runner.io();
+    let mut state = engine::get_state(&io).unwrap();
+    state.fee_policy.min_gas_price = u256_to_arr(&U256::from(5));
+    engine::set_state(&mut io, state);
+
+    // Free transactions are rejected before being executed
+    let transfer = test_utils::transfer(dest, TRANSFER_AMOUNT, signer.nonce.into());
+    let signed_tx =
+        test_utils::sign_transaction(transfer, Some(standalone::CHAIN_ID), &signer.secret_key);
+    match runner.submit(&rlp::encode(&signed_tx)) {
+        Err(RunnerError::Engine(e)) => assert_eq!(e.kind.to_str(), "ERR_GAS_PRICE_TOO_LOW"),
+        other => panic!("Unexpected result {:?}", other),
+    }
+    assert_eq!(runner.get_nonce(&source), U256::zero());
+
+    // unless they are submitted by an exempt account
+    let exemptions = GasPriceExemptions {
+        addresses: Vec::new(),
+        accounts: vec![runner.env.predecessor_account_id.clone()],
+    };
+    fee_policy::set_gas_price_exemptions(&mut runner.io(), &exemptions);
+    let transfer = test_utils::transfer(dest, TRANSFER_AMOUNT, signer.use_nonce().into());
+    let result = standalone::submit_transaction(&mut runner, &signer.secret_key, transfer);
+    assert!(result.status.is_ok());
+
+    // or signed by an exempt address
+    let exemptions = GasPriceExemptions {
+        addresses: vec![source.0],
+        accounts: Vec::new(),
+    };
+    fee_policy::set_gas_price_exemptions(&mut runner.io(), &exemptions);
+    let transfer = test_utils::transfer(dest, TRANSFER_AMOUNT, signer.use_nonce().into());
+    let result = standalone::submit_transaction(&mut runner, &signer.secret_key, transfer);
+    assert!(result.status.is_ok());
+    assert_eq!(runner.get_balance(&dest), TRANSFER_AMOUNT + TRANSFER_AMOUNT);
+}
+
+#[test]
+fn test_fee_policy_validation() {
+    let treasury = test_utils::address_from_hex("0x000000000000000000000000000000000000000b");
+    let policy = FeePolicy {
+        treasury_bps: 6_000,
+        burn_bps: 4_000,
+        treasury_address: treasury.0,
+        min_gas_price: [0u8; 32],
+    };
+    assert_eq!(policy.validate(), Ok(()));
+
+    let too_large = FeePolicy {
+        burn_bps: 4_001,
+        ..policy.clone()
+    };
+    assert_eq!(too_large.validate(), Err(FeePolicyError::InvalidShares));
+
+    // The treasury share would be paid to the zero address.
+    let no_treasury = FeePolicy {
+        treasury_address: [0u8; 20],
+        ..policy.clone()
+    };
+    assert_eq!(
+        no_treasury.validate(),
+        Err(FeePolicyError::MissingTreasuryAddress)
+    );
+    let burn_only = FeePolicy {
+        treasury_bps: 0,
+        ..no_treasury
+    };
+    assert_eq!(burn_only.validate(), Ok(()));
+}
@@ -4,11 +4,16 @@ mod eip1559;
 mod erc20;
 mod erc20_connector;
 mod eth_connector;
+mod fee_policy;
 #[cfg(feature = "meta-call")]
 mod meta_parsing;
 mod one_inch;
 mod sanity;
 mod self_destruct_state;
+mod standalone;
 mod standard_precompiles;
 mod state_migration;
+#[cfg(feature = "evm_bully")]
+mod state_tests;
+mod tracing;
 pub(crate) mod uniswap;
@@ -1,4 +1,4 @@
-use crate::prelude::{Address, U256};
+use crate::prelude::{Address, H256, U256};
 use crate::prelude::{Wei, ERC20_MINT_SELECTOR};
 use crate::test_utils;
 use crate::tests::state_migration;
@@ -33,6 +33,17 @@ fn test_state_format() {
         "04000000626f7373",
         "1300000070726f7665725f6d6370726f76795f66616365",
         "0300000000000000",
+        // The default fee policy: no treasury or burn shares, nor minimum gas price.
+        "0000",
+        "0000",
+        "0000000000000000000000000000000000000000",
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        // The default base fee: fixed at zero.
+        "00",
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        "0000000000000000",
+        "0000000000000000",
     ]
     .concat();
     assert_eq!(hex::encode(state.try_to_vec().unwrap()), expected_hex);
@@ -503,6 +514,45 @@ fn test_eth_transfer_charging_gas_not_enough_balance() {
     test_utils::validate_address_balance_and_nonce(&runner, relayer, Wei::zero(), 0.into());
 }
 
+#[test]
+fn test_sstore_gas_uses_original_value() {
+    // PUSH1 <value> PUSH1 0x00 SSTORE STOP, on a contract where slot 0 holds 1
+    fn sstore_gas_used(value: u8) -> u64 {
+        let mut runner = test_utils::standalone::deploy_evm();
+        let mut signer = test_utils::Signer::random();
+        let source = test_utils::address_from_secret_key(&signer.secret_key);
+        let contract = test_utils::address_from_hex("0xcccccccccccccccccccccccccccccccccccccccc");
+        let code = [0x60, value, 0x60, 0x00, 0x55, 0x00];
+        runner.mint_account(source, INITIAL_BALANCE, INITIAL_NONCE.into());
+        {
+            let mut io = runner.io();
+            aurora_engine::engine::set_code(&mut io, &contract, &code);
+            aurora_engine::engine::set_storage(
+                &mut io,
+                &contract,
+                &H256::zero(),
+                &H256::from_low_u64_be(1),
+                0,
+            );
+        }
+
+        let tx = test_utils::transfer(contract, Wei::zero(), signer.use_nonce().into());
+        let result =
+            test_utils::standalone::submit_transaction(&mut runner, &signer.secret_key, tx);
+        assert!(result.status.is_ok());
+        assert_eq!(
+            runner.get_storage(&contract, &H256::zero()),
+            H256::from_low_u64_be(value as u64)
+        );
+        result.gas_used
+    }
+
+    // 21000 (intrinsic) + 2 * 3 (PUSH1) + 2900 (SSTORE on a clean non-zero slot) + 2100 (cold slot)
+    assert_eq!(sstore_gas_used(2), 26_006);
+    // Clearing the slot is refunded 4800 gas (EIP-3529)
+    assert_eq!(sstore_gas_used(0), 26_006 - 4_800);
+}
+
 fn initialize_transfer() -> (test_utils::AuroraRunner, test_utils::Signer, Address) {
     // set up Aurora runner and accounts
     let mut runner = test_utils::deploy_evm();
@@ -567,6 +617,292 @@ fn test_block_hash_api() {
     );
 }
 
+#[test]
+fn test_block_hash_ring_buffer() {
+    let mut runner = test_utils::standalone::deploy_evm();
+    let mut signer = test_utils::Signer::random();
+    let source = test_utils::address_from_secret_key(&signer.secret_key);
+    let chain_id = crate::prelude::u256_to_arr(&test_utils::standalone::CHAIN_ID.into());
+    let account_id = runner.env.current_account_id.clone();
+    runner.mint_account(source, INITIAL_BALANCE, INITIAL_NONCE.into());
+
+    // The hashes which can be derived are not recorded when a transaction is executed
+    runner.env.block_height = 0x0201;
+    let tx = test_utils::transfer(Address([1; 20]), TRANSFER_AMOUNT, signer.use_nonce().into());
+    test_utils::standalone::submit_transaction(&mut runner, &signer.secret_key, tx);
+    assert_eq!(
+        aurora_engine::engine::get_block_hash(&runner.io(), 0x0201),
+        None
+    );
+
+    // so BLOCKHASH derives them:
+    // PUSH2 0x0201 BLOCKHASH PUSH1 0x00 MSTORE PUSH1 0x20 PUSH1 0x00 RETURN
+    let contract = Address([2; 20]);
+    let code = hex::decode("6102014060005260206000f3").unwrap();
+    aurora_engine::engine::set_code(&mut runner.io(), &contract, &code);
+    runner.env.block_height = 0x0202;
+    let view = |runner: &aurora_engine_standalone::EngineRunner| {
+        runner
+            .view(aurora_engine::parameters::ViewCallArgs {
+                sender: source.0,
+                address: contract.0,
+                amount: [0; 32],
+                input: Vec::new(),
+            })
+            .unwrap()
+    };
+    let expected_hash =
+        aurora_engine::engine::compute_block_hash(chain_id, 0x0201, account_id.as_bytes());
+    assert_eq!(
+        view(&runner),
+        TransactionStatus::Succeed(expected_hash.0.to_vec())
+    );
+
+    // unless a hash was recorded for the block (see `begin_block`),
+    let recorded_hash = H256([0xab; 32]);
+    aurora_engine::engine::set_block_hash(&mut runner.io(), 0x0201, &recorded_hash);
+    assert_eq!(
+        view(&runner),
+        TransactionStatus::Succeed(recorded_hash.0.to_vec())
+    );
+
+    // which is replaced 256 blocks later.
+    aurora_engine::engine::set_block_hash(
+        &mut runner.io(),
+        0x0201 + aurora_engine::engine::BLOCK_HASH_HISTORY,
+        &H256([0xef; 32]),
+    );
+    assert_eq!(
+        aurora_engine::engine::get_block_hash(&runner.io(), 0x0201),
+        None
+    );
+}
+
+#[test]
+fn test_near_environment_in_view_call() {
+    use aurora_engine_precompiles::near_environment::NearEnvironment;
+    use near_primitives_core::config::ViewConfig;
+
+    let mut runner = test_utils::deploy_evm();
+    // Returns the output of a static call to the NEAR environment precompile.
+    let code = [
+        &hex::decode("600060006000600073").unwrap()[..],
+        NearEnvironment::ADDRESS.as_bytes(),
+        &hex::decode("5afa503d600060003e3d6000f3").unwrap(),
+    ]
+    .concat();
+    let contract = Address([4; 20]);
+    runner.create_address_with_code(contract, Wei::zero(), U256::zero(), code);
+
+    // A view call has no predecessor nor signer: reading them would abort it.
+    runner.context.view_config = Some(ViewConfig {
+        max_gas_burnt: runner.wasm_config.limit_config.max_gas_burnt,
+    });
+    let result = runner
+        .view_call(aurora_engine::parameters::ViewCallArgs {
+            sender: [0; 20],
+            address: contract.0,
+            amount: [0; 32],
+            input: Vec::new(),
+        })
+        .unwrap();
+    let output = match result {
+        TransactionStatus::Succeed(output) => output,
+        other => panic!("Unexpected status: {:?}", other),
+    };
+    let tokens = ethabi::decode(
+        &[
+            ethabi::ParamType::String,
+            ethabi::ParamType::String,
+            ethabi::ParamType::Uint(256),
+            ethabi::ParamType::Uint(256),
+            ethabi::ParamType::FixedBytes(32),
+        ],
+        &output,
+    )
+    .unwrap();
+    assert_eq!(tokens[0], ethabi::Token::String(String::new()));
+    assert_eq!(tokens[1], ethabi::Token::String(String::new()));
+}
+
+#[test]
+fn test_storage_staking() {
+    use aurora_engine::storage_staking;
+
+    let mut runner = test_utils::standalone::deploy_evm();
+    let mut signer = test_utils::Signer::random();
+    let source = test_utils::address_from_secret_key(&signer.secret_key);
+    runner.mint_account(source, INITIAL_BALANCE, INITIAL_NONCE.into());
+    storage_staking::set_price_per_byte(&mut runner.io(), &U256::one());
+
+    // PUSH1 0x00 CALLDATALOAD PUSH1 0x00 SSTORE STOP
+    let contract = Address([2; 20]);
+    let code = hex::decode("60003560005500").unwrap();
+    aurora_engine::engine::set_code(&mut runner.io(), &contract, &code);
+    let store = |value: u64, nonce: u64| {
+        let mut tx = test_utils::transfer(contract, Wei::zero(), nonce.into());
+        tx.data = crate::prelude::u256_to_arr(&U256::from(value)).to_vec();
+        tx
+    };
+
+    // Adding a slot stakes for it (as well as for the nonce and balance of the contract)
+    let tx = store(1, signer.use_nonce());
+    test_utils::standalone::submit_transaction(&mut runner, &signer.secret_key, tx);
+    let stake = storage_staking::get_total_stake(&runner.io());
+    assert!(!stake.is_zero());
+    assert_eq!(runner.get_balance(&source), INITIAL_BALANCE - stake);
+
+    // and removing it gives back the stake of the slot.
+    let slot_bytes = 54 + 32 + storage_staking::STORAGE_ENTRY_OVERHEAD;
+    let tx = store(0, signer.use_nonce());
+    test_utils::standalone::submit_transaction(&mut runner, &signer.secret_key, tx);
+    let slot_stake = Wei::new_u64(slot_bytes);
+    assert_eq!(
+        storage_staking::get_total_stake(&runner.io()),
+        stake - slot_stake
+    );
+    assert_eq!(
+        runner.get_balance(&source),
+        INITIAL_BALANCE - stake + slot_stake
+    );
+
+    // Storage cannot be added without the funds to stake for it.
+    storage_staking::set_price_per_byte(&mut runner.io(), &U256::from(INITIAL_BALANCE.raw()));
+    let tx = store(1, signer.use_nonce());
+    let signed_tx = test_utils::sign_transaction(
+        tx,
+        Some(test_utils::standalone::CHAIN_ID),
+        &signer.secret_key,
+    );
+    let nonce = runner.get_nonce(&source);
+    let error = runner.submit(&rlp::encode(&signed_tx)).unwrap_err();
+    assert!(format!("{:?}", error).contains("InsufficientFunds"));
+    // and the failed transaction leaves the storage unchanged.
+    assert_eq!(runner.get_storage(&contract, &H256::zero()), H256::zero());
+    assert_eq!(runner.get_nonce(&source), nonce);
+}
+
+#[test]
+fn test_storage_stake_refunded_to_staker() {
+    use aurora_engine::storage_staking;
+
+    let mut runner = test_utils::standalone::deploy_evm();
+    let mut staker = test_utils::Signer::random();
+    let mut other = test_utils::Signer::random();
+    let staker_address = test_utils::address_from_secret_key(&staker.secret_key);
+    let other_address = test_utils::address_from_secret_key(&other.secret_key);
+    runner.mint_account(staker_address, INITIAL_BALANCE, INITIAL_NONCE.into());
+    runner.mint_account(other_address, INITIAL_BALANCE, INITIAL_NONCE.into());
+    storage_staking::set_price_per_byte(&mut runner.io(), &U256::one());
+
+    // PUSH1 0x00 CALLDATALOAD PUSH1 0x00 SSTORE STOP
+    let contract = Address([2; 20]);
+    let code = hex::decode("60003560005500").unwrap();
+    aurora_engine::engine::set_code(&mut runner.io(), &contract, &code);
+    let store = |value: u64, nonce: u64| {
+        let mut tx = test_utils::transfer(contract, Wei::zero(), nonce.into());
+        tx.data = crate::prelude::u256_to_arr(&U256::from(value)).to_vec();
+        tx
+    };
+
+    let tx = store(1, staker.use_nonce());
+    test_utils::standalone::submit_transaction(&mut runner, &staker.secret_key, tx);
+    let staker_balance = runner.get_balance(&staker_address);
+
+    // The slot freed by another account, after a price change, refunds what the staker paid.
+    storage_staking::set_price_per_byte(&mut runner.io(), &U256::from(2));
+    let tx = store(0, other.use_nonce());
+    test_utils::standalone::submit_transaction(&mut runner, &other.secret_key, tx);
+    let slot_stake = Wei::new_u64(54 + 32 + storage_staking::STORAGE_ENTRY_OVERHEAD);
+    assert_eq!(
+        runner.get_balance(&staker_address),
+        staker_balance + slot_stake
+    );
+    assert_eq!(runner.get_balance(&other_address), INITIAL_BALANCE);
+}
+
+#[test]
+fn test_storage_staking_exempt_internal_calls() {
+    use aurora_engine::engine::Engine;
+    use aurora_engine::storage_staking;
+    use aurora_engine_precompiles::native::ExitToNear;
+    use aurora_engine_standalone::PromiseRecorder;
+
+    let runner = test_utils::standalone::deploy_evm();
+    // The escrow of the exits to NEAR, refunding an exit to a new account
+    let exit_address = ExitToNear::ADDRESS;
+    runner.mint_account(exit_address, INITIAL_BALANCE, U256::zero());
+    storage_staking::set_price_per_byte(&mut runner.io(), &U256::one());
+
+    let recipient = Address([3; 20]);
+    let amount = Wei::new_u64(10);
+    let mut engine = Engine::new(
+        exit_address,
+        runner.env.current_account_id.clone(),
+        runner.io(),
+        &runner.env,
+    )
+    .unwrap();
+    engine.exempt_from_storage_staking();
+    engine
+        .transfer(
+            exit_address,
+            recipient,
+            amount,
+            u64::MAX,
+            &mut PromiseRecorder::default(),
+        )
+        .unwrap();
+
+    // Nothing is taken out of the escrow for the storage of the recipient
+    assert_eq!(runner.get_balance(&recipient), amount);
+    assert_eq!(runner.get_balance(&exit_address), INITIAL_BALANCE - amount);
+    assert!(storage_staking::get_total_stake(&runner.io()).is_zero());
+}
+
+#[cfg(feature = "evm_bully")]
+#[test]
+fn test_begin_block_overrides_block_context() {
+    let runner = test_utils::standalone::deploy_evm();
+    let u256 = |x: u64| crate::prelude::u256_to_arr(&U256::from(x));
+    let block_hash = H256([0xcd; 32]);
+    runner.begin_block(&aurora_engine::parameters::BeginBlockArgs {
+        hash: block_hash.0,
+        coinbase: [0x11; 20],
+        timestamp: u256(1_600_000_000),
+        number: u256(1_000_000),
+        difficulty: u256(2_000_000),
+        gaslimit: u256(12_500_000),
+    });
+    assert_eq!(
+        aurora_engine::engine::get_block_hash(&runner.io(), 1_000_000),
+        Some(block_hash)
+    );
+
+    // Returns COINBASE, TIMESTAMP, NUMBER, DIFFICULTY and GASLIMIT as 32-byte words:
+    // COINBASE PUSH1 0x00 MSTORE TIMESTAMP PUSH1 0x20 MSTORE NUMBER PUSH1 0x40 MSTORE
+    // DIFFICULTY PUSH1 0x60 MSTORE GASLIMIT PUSH1 0x80 MSTORE PUSH1 0xa0 PUSH1 0x00 RETURN
+    let contract = Address([2; 20]);
+    let code = hex::decode("416000524260205243604052446060524560805260a06000f3").unwrap();
+    aurora_engine::engine::set_code(&mut runner.io(), &contract, &code);
+    let status = runner
+        .view(aurora_engine::parameters::ViewCallArgs {
+            sender: [0; 20],
+            address: contract.0,
+            amount: [0; 32],
+            input: Vec::new(),
+        })
+        .unwrap();
+
+    let mut expected = vec![0u8; 12];
+    expected.extend_from_slice(&[0x11; 20]);
+    expected.extend_from_slice(&u256(1_600_000_000));
+    expected.extend_from_slice(&u256(1_000_000));
+    expected.extend_from_slice(&u256(2_000_000));
+    expected.extend_from_slice(&u256(12_500_000));
+    assert_eq!(status, TransactionStatus::Succeed(expected));
+}
+
 #[test]
 fn test_block_hash_contract() {
     let (mut runner, mut source_account, _) = initialize_transfer();
@@ -0,0 +1,939 @@
+use aurora_engine::bloom::Bloom;
+use aurora_engine::engine::{self, Engine};
+use aurora_engine::logs_index::{self, GetLogsError};
+use aurora_engine::transaction_queue;
+use aurora_engine_sdk::io::{OverlayBuffer, OverlayIO, StorageIntermediate, IO};
+use aurora_engine_standalone::{EngineRunner, PromiseRecorder, RunnerError, StandaloneIO, Storage};
+
+use crate::prelude::parameters::{
+    AccountOverride, BatchTransactionResult, BlockOverrides, EstimateGasArgs, FilteredLog,
+    GetLogsArgs, ResultLog, StorageOverride, TransactionStatus, ViewCallArgs,
+    ViewCallWithOverridesArgs,
+};
+use crate::prelude::transaction::legacy::TransactionLegacy;
+use crate::prelude::{sdk, Address, Wei, H256, U256};
+use crate::test_utils::{self, standalone};
+
+const INITIAL_BALANCE: Wei = Wei::new_u64(1_000_000);
+const INITIAL_NONCE: u64 = 0;
+const TRANSFER_AMOUNT: Wei = Wei::new_u64(123);
+
+#[test]
+fn test_standalone_transfer() {
+    let mut runner = standalone::deploy_evm();
+    let mut signer = test_utils::Signer::random();
+    let source = test_utils::address_from_secret_key(&signer.secret_key);
+    let dest = test_utils::address_from_hex("0x000000000000000000000000000000000000000a");
+    runner.mint_account(source, INITIAL_BALANCE, INITIAL_NONCE.into());
+
+    let nonce = signer.use_nonce();
+    let tx = test_utils::transfer(dest, TRANSFER_AMOUNT, nonce.into());
+    let result = standalone::submit_transaction(&mut runner, &signer.secret_key, tx);
+
+    assert!(result.status.is_ok());
+    assert_eq!(runner.get_balance(&source), Wei::new_u64(1_000_000 - 123));
+    assert_eq!(runner.get_balance(&dest), TRANSFER_AMOUNT);
+    assert_eq!(runner.get_nonce(&source), U256::one());
+
+    // Replaying the same transaction must fail the nonce check.
+    let tx = test_utils::transfer(dest, TRANSFER_AMOUNT, nonce.into());
+    let signed_tx =
+        test_utils::sign_transaction(tx, Some(standalone::CHAIN_ID), &signer.secret_key);
+    assert!(runner.submit(&rlp::encode(&signed_tx)).is_err());
+}
+
+#[test]
+fn test_standalone_deploy_and_view() {
+    let mut runner = standalone::deploy_evm();
+    let mut signer = test_utils::Signer::random();
+    let source = test_utils::address_from_secret_key(&signer.secret_key);
+    runner.mint_account(source, INITIAL_BALANCE, INITIAL_NONCE.into());
+
+    // PUSH1 0x2a PUSH1 0x00 MSTORE PUSH1 0x20 PUSH1 0x00 RETURN
+    let code = hex::decode("602a60005260206000f3").unwrap();
+    let tx = test_utils::create_deploy_transaction(code.clone(), signer.use_nonce().into());
+    let result = standalone::submit_transaction(&mut runner, &signer.secret_key, tx);
+    let address = Address::from_slice(&test_utils::unwrap_success(result));
+    assert_eq!(runner.get_code(&address), code);
+
+    let status = runner
+        .view(ViewCallArgs {
+            sender: source.0,
+            address: address.0,
+            amount: [0u8; 32],
+            input: Vec::new(),
+        })
+        .unwrap();
+    assert_eq!(
+        status,
+        TransactionStatus::Succeed(crate::prelude::u256_to_arr(&U256::from(42)).to_vec())
+    );
+}
+
+#[test]
+fn test_standalone_transaction_receipt() {
+    let mut runner = standalone::deploy_evm();
+    let mut signer = test_utils::Signer::random();
+    let source = test_utils::address_from_secret_key(&signer.secret_key);
+    let dest = test_utils::address_from_hex("0x000000000000000000000000000000000000000a");
+    runner.mint_account(source, INITIAL_BALANCE, INITIAL_NONCE.into());
+
+    let mut tx = test_utils::transfer(dest, TRANSFER_AMOUNT, signer.use_nonce().into());
+    tx.gas_limit = 21_000.into();
+    tx.gas_price = U256::one();
+    let signed_tx = rlp::encode(&test_utils::sign_transaction(
+        tx,
+        Some(standalone::CHAIN_ID),
+        &signer.secret_key,
+    ));
+    let transaction_hash = sdk::keccak(&signed_tx);
+    assert!(runner.get_transaction_receipt(&transaction_hash).is_none());
+    let result = runner.submit(&signed_tx).unwrap();
+
+    let receipt = runner.get_transaction_receipt(&transaction_hash).unwrap();
+    assert_eq!(receipt.transaction_hash, transaction_hash.0);
+    assert_eq!(receipt.from, source.0);
+    assert_eq!(receipt.to, Some(dest.0));
+    assert!(receipt.status);
+    assert_eq!(receipt.gas_used, result.gas_used);
+    assert_eq!(
+        receipt.effective_gas_price,
+        crate::prelude::u256_to_arr(&U256::one())
+    );
+    assert_eq!(receipt.contract_address, None);
+    assert_eq!(receipt.logs_bloom, Bloom::default());
+    assert_eq!(receipt.block_height, runner.env.block_height);
+
+    // PUSH1 0x00 PUSH1 0x00 LOG0: a deployment emitting a log, leaving an empty contract.
+    let tx = TransactionLegacy {
+        nonce: signer.use_nonce().into(),
+        gas_price: U256::zero(),
+        gas_limit: u64::MAX.into(),
+        to: None,
+        value: Wei::zero(),
+        data: hex::decode("60006000a0").unwrap(),
+    };
+    let signed_tx = rlp::encode(&test_utils::sign_transaction(
+        tx,
+        Some(standalone::CHAIN_ID),
+        &signer.secret_key,
+    ));
+    let result = runner.submit(&signed_tx).unwrap();
+    let address = Address::from_slice(&test_utils::unwrap_success(result));
+
+    let receipt = runner
+        .get_transaction_receipt(&sdk::keccak(&signed_tx))
+        .unwrap();
+    assert!(receipt.status);
+    assert_eq!(receipt.to, None);
+    assert_eq!(receipt.contract_address, Some(address.0));
+    assert!(receipt.logs_bloom.contains_input(address.as_bytes()));
+}
+
+#[test]
+fn test_standalone_get_logs() {
+    let mut runner = standalone::deploy_evm();
+    let mut signer = test_utils::Signer::random();
+    let source = test_utils::address_from_secret_key(&signer.secret_key);
+    runner.mint_account(source, INITIAL_BALANCE, INITIAL_NONCE.into());
+
+    // PUSH1 0x2a PUSH1 0x00 PUSH1 0x00 LOG1 STOP: emits an empty log with the topic 42.
+    let code = hex::decode("602a60006000a100").unwrap();
+    let tx = test_utils::create_deploy_transaction(code, signer.use_nonce().into());
+    let result = standalone::submit_transaction(&mut runner, &signer.secret_key, tx);
+    let address = Address::from_slice(&test_utils::unwrap_success(result));
+
+    let first_block = runner.env.block_height;
+    let mut transaction_hashes = Vec::new();
+    for _ in 0..2 {
+        runner.env.block_height += 1;
+        let tx = test_utils::transfer(address, Wei::zero(), signer.use_nonce().into());
+        let signed_tx = rlp::encode(&test_utils::sign_transaction(
+            tx,
+            Some(standalone::CHAIN_ID),
+            &signer.secret_key,
+        ));
+        transaction_hashes.push(sdk::keccak(&signed_tx));
+        runner.submit(&signed_tx).unwrap();
+    }
+    let last_block = runner.env.block_height;
+
+    let topic = crate::prelude::u256_to_arr(&U256::from(42));
+    let query = |addresses: Vec<[u8; 20]>, topics: Vec<Vec<[u8; 32]>>| GetLogsArgs {
+        from_block: first_block,
+        to_block: last_block,
+        addresses,
+        topics,
+    };
+
+    let logs = runner
+        .get_logs(&query(vec![address.0], vec![vec![topic]]))
+        .unwrap();
+    let expected_log = ResultLog {
+        address: address.0,
+        topics: vec![topic],
+        data: Vec::new(),
+    };
+    assert_eq!(
+        logs,
+        vec![
+            FilteredLog {
+                block_height: first_block + 1,
+                transaction_hash: transaction_hashes[0].0,
+                log_index: 0,
+                log: expected_log.clone(),
+            },
+            FilteredLog {
+                block_height: last_block,
+                transaction_hash: transaction_hashes[1].0,
+                log_index: 0,
+                log: expected_log,
+            },
+        ]
+    );
+    assert_eq!(
+        runner.get_logs(&query(Vec::new(), Vec::new())).unwrap(),
+        logs
+    );
+
+    // The block bloom has the address and topic of the logs of the block.
+    let block_logs = logs_index::get_block_logs(&runner.io(), last_block);
+    assert!(block_logs.bloom.contains_input(address.as_bytes()));
+    assert!(block_logs.bloom.contains_input(&topic));
+    assert_eq!(block_logs.transactions_count, 1);
+    let transaction_logs = logs_index::get_transaction_logs(&runner.io(), &block_logs, 0).unwrap();
+    assert_eq!(transaction_logs.transaction_hash, transaction_hashes[1].0);
+
+    let other_topic = crate::prelude::u256_to_arr(&U256::from(43));
+    assert!(runner
+        .get_logs(&query(Vec::new(), vec![vec![other_topic]]))
+        .unwrap()
+        .is_empty());
+    assert!(runner
+        .get_logs(&query(vec![source.0], Vec::new()))
+        .unwrap()
+        .is_empty());
+    assert!(runner
+        .get_logs(&query(Vec::new(), vec![Vec::new(), vec![topic]]))
+        .unwrap()
+        .is_empty());
+
+    let args = GetLogsArgs {
+        from_block: last_block,
+        to_block: first_block,
+        addresses: Vec::new(),
+        topics: Vec::new(),
+    };
+    assert_eq!(runner.get_logs(&args), Err(GetLogsError::InvalidBlockRange));
+    let args = GetLogsArgs {
+        from_block: 0,
+        to_block: logs_index::MAX_BLOCK_RANGE,
+        addresses: Vec::new(),
+        topics: Vec::new(),
+    };
+    assert_eq!(
+        runner.get_logs(&args),
+        Err(GetLogsError::BlockRangeTooLarge)
+    );
+}
+
+#[test]
+fn test_standalone_logs_retention() {
+    let mut runner = standalone::deploy_evm();
+    let mut signer = test_utils::Signer::random();
+    let source = test_utils::address_from_secret_key(&signer.secret_key);
+    runner.mint_account(source, INITIAL_BALANCE, INITIAL_NONCE.into());
+
+    // PUSH1 0x2a PUSH1 0x00 PUSH1 0x00 LOG1 STOP: emits an empty log with the topic 42.
+    let code = hex::decode("602a60006000a100").unwrap();
+    let tx = test_utils::create_deploy_transaction(code, signer.use_nonce().into());
+    let result = standalone::submit_transaction(&mut runner, &signer.secret_key, tx);
+    let address = Address::from_slice(&test_utils::unwrap_success(result));
+
+    let mut submit = |runner: &mut EngineRunner| {
+        let tx = test_utils::transfer(address, Wei::zero(), signer.use_nonce().into());
+        let signed_tx = rlp::encode(&test_utils::sign_transaction(
+            tx,
+            Some(standalone::CHAIN_ID),
+            &signer.secret_key,
+        ));
+        runner.submit(&signed_tx).unwrap();
+        sdk::keccak(&signed_tx)
+    };
+    let first_block = runner.env.block_height + 1;
+    runner.env.block_height = first_block;
+    let first_hash = submit(&mut runner);
+    let query = |block_height: u64| GetLogsArgs {
+        from_block: block_height,
+        to_block: block_height,
+        addresses: Vec::new(),
+        topics: Vec::new(),
+    };
+    assert_eq!(runner.get_logs(&query(first_block)).unwrap().len(), 1);
+
+    // A block reusing the slot of the first one removes its logs and receipts.
+    let last_block = first_block + logs_index::RETENTION_BLOCKS;
+    runner.env.block_height = last_block;
+    let last_hash = submit(&mut runner);
+    assert!(runner.get_logs(&query(first_block)).unwrap().is_empty());
+    assert!(runner.get_transaction_receipt(&first_hash).is_none());
+    assert_eq!(
+        logs_index::get_block_logs(&runner.io(), first_block).transactions_count,
+        0
+    );
+
+    let logs = runner.get_logs(&query(last_block)).unwrap();
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs[0].transaction_hash, last_hash.0);
+    assert!(runner.get_transaction_receipt(&last_hash).is_some());
+}
+
+#[test]
+fn test_standalone_logs_pruning_is_bounded() {
+    let mut runner = standalone::deploy_evm();
+    let mut signer = test_utils::Signer::random();
+    let source = test_utils::address_from_secret_key(&signer.secret_key);
+    let dest = test_utils::address_from_hex("0x000000000000000000000000000000000000000a");
+    runner.mint_account(source, INITIAL_BALANCE, INITIAL_NONCE.into());
+
+    let mut submit = |runner: &mut EngineRunner| {
+        let tx = test_utils::transfer(dest, TRANSFER_AMOUNT, signer.use_nonce().into());
+        let signed_tx = rlp::encode(&test_utils::sign_transaction(
+            tx,
+            Some(standalone::CHAIN_ID),
+            &signer.secret_key,
+        ));
+        runner.submit(&signed_tx).unwrap();
+        sdk::keccak(&signed_tx)
+    };
+    let first_block = runner.env.block_height + 1;
+    runner.env.block_height = first_block;
+    let batch = logs_index::PRUNE_BATCH as usize;
+    let old_hashes: Vec<H256> = (0..2 * batch + 2).map(|_| submit(&mut runner)).collect();
+
+    // Each transaction of the block reusing the slot removes a bounded number of the old ones.
+    runner.env.block_height = first_block + logs_index::RETENTION_BLOCKS;
+    submit(&mut runner);
+    let kept: Vec<bool> = old_hashes
+        .iter()
+        .map(|hash| runner.get_transaction_receipt(hash).is_some())
+        .collect();
+    assert_eq!(kept.iter().filter(|kept| !**kept).count(), 1 + batch);
+    assert!(!kept[0]);
+
+    submit(&mut runner);
+    submit(&mut runner);
+    assert!(old_hashes
+        .iter()
+        .all(|hash| runner.get_transaction_receipt(hash).is_none()));
+    let block_logs = logs_index::get_block_logs(&runner.io(), runner.env.block_height);
+    assert_eq!(block_logs.transactions_count, 3);
+    assert_eq!(block_logs.stale_count, 0);
+}
+
+#[test]
+fn test_standalone_estimate_gas() {
+    let mut runner = standalone::deploy_evm();
+    let mut signer = test_utils::Signer::random();
+    let source = test_utils::address_from_secret_key(&signer.secret_key);
+    runner.mint_account(source, INITIAL_BALANCE, INITIAL_NONCE.into());
+
+    let mut deploy = |runner: &mut EngineRunner, code: Vec<u8>| {
+        let tx = test_utils::create_deploy_transaction(code, signer.use_nonce().into());
+        let result = standalone::submit_transaction(runner, &signer.secret_key, tx);
+        Address::from_slice(&test_utils::unwrap_success(result))
+    };
+    // PUSH1 0x01 PUSH1 0x00 SSTORE PUSH1 0x00 PUSH1 0x00 SSTORE STOP: sets a slot and clears
+    // it, so a part of the gas is refunded.
+    let refunding = deploy(&mut runner, hex::decode("6001600055600060005500").unwrap());
+    // Calls `refunding` with all the gas left (but the 1/64 kept by the caller), and reverts
+    // if the call fails.
+    let caller = deploy(
+        &mut runner,
+        [
+            hex::decode("60006000600060006000").unwrap(),
+            vec![0x73],
+            refunding.0.to_vec(),
+            hex::decode("5af160295760006000fd5b00").unwrap(),
+        ]
+        .concat(),
+    );
+    let dest = test_utils::address_from_hex("0x000000000000000000000000000000000000000a");
+
+    let estimate_args =
+        |to: Option<Address>, data: Vec<u8>, gas_limit: Option<u64>| EstimateGasArgs {
+            sender: source.0,
+            to: to.map(|address| address.0),
+            value: [0u8; 32],
+            data,
+            access_list: Vec::new(),
+            gas_limit,
+        };
+    let estimate = |runner: &EngineRunner, to: Option<Address>, data: Vec<u8>| {
+        let result = runner.estimate_gas(estimate_args(to, data, None)).unwrap();
+        assert!(result.status.is_ok());
+        result.gas
+    };
+    let submit = |runner: &mut EngineRunner,
+                  signer: &mut test_utils::Signer,
+                  to: Option<Address>,
+                  data: Vec<u8>,
+                  gas_limit: u64| {
+        let tx = TransactionLegacy {
+            nonce: signer.use_nonce().into(),
+            gas_price: U256::zero(),
+            gas_limit: gas_limit.into(),
+            to,
+            value: Wei::zero(),
+            data,
+        };
+        standalone::submit_transaction(runner, &signer.secret_key, tx)
+    };
+
+    assert_eq!(estimate(&runner, Some(dest), Vec::new()), 21_000);
+
+    // the gas limit of the transaction bounds the search
+    let result = runner
+        .estimate_gas(estimate_args(Some(dest), Vec::new(), Some(30_000)))
+        .unwrap();
+    assert!(result.status.is_ok());
+    assert_eq!(result.gas, 21_000);
+    let result = runner.estimate_gas(estimate_args(Some(dest), Vec::new(), Some(20_000)));
+    assert!(!matches!(result, Ok(result) if result.status.is_ok()));
+
+    let init_code =
+        test_utils::create_deploy_transaction(hex::decode("602a").unwrap(), U256::zero()).data;
+    for (to, data) in [
+        (Some(refunding), Vec::new()),
+        (Some(caller), Vec::new()),
+        (None, init_code),
+    ] {
+        let gas = estimate(&runner, to, data.clone());
+        let result = submit(&mut runner, &mut signer, to, data.clone(), gas - 1);
+        assert!(!result.status.is_ok());
+        let result = submit(&mut runner, &mut signer, to, data, gas);
+        assert!(result.status.is_ok());
+        if to == Some(refunding) {
+            assert!(gas > result.gas_used);
+        }
+    }
+}
+
+#[test]
+fn test_standalone_view_with_overrides() {
+    let mut runner = standalone::deploy_evm();
+    let mut signer = test_utils::Signer::random();
+    let source = test_utils::address_from_secret_key(&signer.secret_key);
+    runner.mint_account(source, INITIAL_BALANCE, INITIAL_NONCE.into());
+
+    // PUSH1 0x00 SLOAD PUSH1 0x00 MSTORE PUSH1 0x20 PUSH1 0x00 RETURN
+    let code = hex::decode("60005460005260206000f3").unwrap();
+    let tx = test_utils::create_deploy_transaction(code, signer.use_nonce().into());
+    let result = standalone::submit_transaction(&mut runner, &signer.secret_key, tx);
+    let contract = Address::from_slice(&test_utils::unwrap_success(result));
+    let slot = |value: u64| H256(crate::prelude::u256_to_arr(&U256::from(value)));
+    let generation = engine::get_generation(&runner.io(), &contract);
+    engine::set_storage(&mut runner.io(), &contract, &slot(0), &slot(7), generation);
+
+    let view = |to: Address,
+                state_overrides: Vec<([u8; 20], AccountOverride)>,
+                block_overrides: BlockOverrides| {
+        let args = ViewCallWithOverridesArgs {
+            call: ViewCallArgs {
+                sender: source.0,
+                address: to.0,
+                amount: [0u8; 32],
+                input: Vec::new(),
+            },
+            state_overrides,
+            block_overrides,
+        };
+        match runner.view_with_overrides(args).unwrap() {
+            TransactionStatus::Succeed(output) => U256::from_big_endian(&output),
+            status => panic!("Unexpected status {:?}", status),
+        }
+    };
+    let storage_override = |storage: StorageOverride| AccountOverride {
+        storage: Some(storage),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        view(contract, Vec::new(), Default::default()),
+        U256::from(7)
+    );
+    let diff = StorageOverride::Diff(vec![(slot(0).0, slot(42).0)]);
+    assert_eq!(
+        view(
+            contract,
+            vec![(contract.0, storage_override(diff))],
+            Default::default()
+        ),
+        U256::from(42)
+    );
+    let replace = StorageOverride::Replace(vec![(slot(1).0, slot(42).0)]);
+    assert_eq!(
+        view(
+            contract,
+            vec![(contract.0, storage_override(replace))],
+            Default::default()
+        ),
+        U256::zero()
+    );
+
+    // Overridden code: CALLER BALANCE, NUMBER and TIMESTAMP, followed by
+    // PUSH1 0x00 MSTORE PUSH1 0x20 PUSH1 0x00 RETURN
+    let other = test_utils::address_from_hex("0x000000000000000000000000000000000000000c");
+    let code_override = |code: &str| AccountOverride {
+        code: Some(hex::decode(code).unwrap()),
+        ..Default::default()
+    };
+    let balance_override = AccountOverride {
+        balance: Some(crate::prelude::u256_to_arr(&U256::from(5))),
+        ..Default::default()
+    };
+    assert_eq!(
+        view(
+            other,
+            vec![
+                (other.0, code_override("333160005260206000f3")),
+                (source.0, balance_override)
+            ],
+            Default::default()
+        ),
+        U256::from(5)
+    );
+    let block_overrides = BlockOverrides {
+        number: Some(1234),
+        timestamp: Some(5678),
+    };
+    assert_eq!(
+        view(
+            other,
+            vec![(other.0, code_override("4360005260206000f3"))],
+            block_overrides.clone()
+        ),
+        U256::from(1234)
+    );
+    assert_eq!(
+        view(
+            other,
+            vec![(other.0, code_override("4260005260206000f3"))],
+            block_overrides
+        ),
+        U256::from(5678)
+    );
+
+    // The overrides were never written to the storage.
+    let io = runner.io();
+    assert_eq!(
+        engine::get_storage(&io, &contract, &slot(0), generation),
+        slot(7)
+    );
+    assert_eq!(engine::get_generation(&io, &contract), generation);
+    assert_eq!(runner.get_balance(&source), INITIAL_BALANCE);
+    assert!(runner.get_code(&other).is_empty());
+}
+
+#[test]
+fn test_standalone_overlay_io() {
+    let runner = standalone::deploy_evm();
+    let source = test_utils::address_from_hex("0x000000000000000000000000000000000000000b");
+    let dest = test_utils::address_from_hex("0x000000000000000000000000000000000000000a");
+    runner.mint_account(source, INITIAL_BALANCE, INITIAL_NONCE.into());
+    let storage_before = runner.storage.borrow().clone();
+
+    let transfer = |io: OverlayIO<StandaloneIO>| {
+        let mut engine = Engine::new(
+            source,
+            runner.env.current_account_id.clone(),
+            io,
+            &runner.env,
+        )
+        .unwrap();
+        let result = engine
+            .call(
+                source,
+                dest,
+                TRANSFER_AMOUNT,
+                Vec::new(),
+                u64::MAX,
+                Vec::new(),
+                &mut PromiseRecorder::default(),
+            )
+            .unwrap();
+        assert!(result.status.is_ok());
+    };
+
+    // The changes are served by the overlay, but not written to the storage.
+    let buffer = OverlayBuffer::default();
+    let mut overlay = OverlayIO::new(runner.io(), &buffer);
+    transfer(overlay);
+    assert_eq!(engine::get_balance(&overlay, &dest), TRANSFER_AMOUNT);
+    assert_eq!(*runner.storage.borrow(), storage_before);
+
+    let key = b"some_key";
+    overlay.write_storage(key, b"some_value");
+    assert_eq!(overlay.read_storage(key).unwrap().to_vec(), b"some_value");
+    assert!(overlay.remove_storage(key).is_some());
+    assert!(!overlay.storage_has_key(key));
+    assert!(overlay.read_storage(key).is_none());
+
+    overlay.discard();
+    assert_eq!(engine::get_balance(&overlay, &dest), Wei::zero());
+    assert_eq!(*runner.storage.borrow(), storage_before);
+
+    // Committing writes the changes, including the removals.
+    let mut overlay = OverlayIO::new(runner.io(), &buffer);
+    runner.io().write_storage(key, b"some_value");
+    overlay.remove_storage(key);
+    transfer(overlay);
+    overlay.commit();
+    assert!(buffer.borrow().is_empty());
+    assert!(!runner.io().storage_has_key(key));
+    assert_eq!(runner.get_balance(&dest), TRANSFER_AMOUNT);
+    assert_eq!(
+        runner.get_balance(&source),
+        INITIAL_BALANCE - TRANSFER_AMOUNT
+    );
+}
+
+#[test]
+fn test_standalone_promise_buffer() {
+    use crate::prelude::PromiseCreateArgs;
+    use aurora_engine_sdk::promise::{PromiseBuffer, PromiseHandler};
+    use aurora_engine_standalone::ScheduledPromise;
+
+    let promise = |method: &str| PromiseCreateArgs {
+        target_account_id: "token.near".parse().unwrap(),
+        method: method.to_string(),
+        args: Vec::new(),
+        attached_balance: 0,
+        attached_gas: 0,
+    };
+    let mut recorder = PromiseRecorder::default();
+    recorder.promise_create_call(&promise("before"));
+
+    // The promises of a discarded buffer are never scheduled.
+    let mut buffer = PromiseBuffer::new(&mut recorder);
+    buffer.promise_create_call(&promise("discarded"));
+    drop(buffer);
+    assert_eq!(recorder.scheduled().len(), 1);
+
+    // Flushing schedules them in order, with the IDs of the underlying handler.
+    let mut buffer = PromiseBuffer::new(&mut recorder);
+    let base = buffer.promise_create_call(&promise("ft_transfer"));
+    let callback = buffer.promise_attach_callback(base, &promise("refund_on_error"));
+    buffer.promise_return(callback);
+    buffer.flush();
+
+    let scheduled = recorder.scheduled();
+    assert_eq!(scheduled.len(), 3);
+    match (&scheduled[1], &scheduled[2]) {
+        (ScheduledPromise::Create(base), ScheduledPromise::Callback { base: id, callback }) => {
+            assert_eq!(base.method, "ft_transfer");
+            assert_eq!(id.raw(), 1);
+            assert_eq!(callback.method, "refund_on_error");
+        }
+        other => panic!("Unexpected promises: {:?}", other),
+    }
+    assert_eq!(recorder.returned().map(|id| id.raw()), Some(2));
+}
+
+#[cfg(feature = "integration-test")]
+#[test]
+fn test_standalone_impersonated_transaction() {
+    use crate::prelude::transaction::eip_1559::Transaction1559;
+    use crate::prelude::transaction::impersonated::{self, ImpersonatedTransaction};
+
+    let mut runner = standalone::deploy_evm();
+    // No key is known for this address.
+    let sender = test_utils::address_from_hex("0x000000000000000000000000000000000000000b");
+    let dest = test_utils::address_from_hex("0x000000000000000000000000000000000000000a");
+    runner.mint_account(sender, INITIAL_BALANCE, INITIAL_NONCE.into());
+
+    let tx = ImpersonatedTransaction {
+        sender,
+        transaction: Transaction1559 {
+            chain_id: standalone::CHAIN_ID,
+            nonce: INITIAL_NONCE.into(),
+            max_priority_fee_per_gas: U256::zero(),
+            max_fee_per_gas: U256::zero(),
+            gas_limit: u64::MAX.into(),
+            to: Some(dest),
+            value: TRANSFER_AMOUNT,
+            data: Vec::new(),
+            access_list: Vec::new(),
+        },
+    };
+    let mut bytes = vec![impersonated::TYPE_BYTE];
+    bytes.extend_from_slice(&rlp::encode(&tx));
+    let result = runner.submit(&bytes).unwrap();
+
+    assert!(result.status.is_ok());
+    assert_eq!(runner.get_balance(&dest), TRANSFER_AMOUNT);
+    assert_eq!(runner.get_nonce(&sender), U256::one());
+}
+
+#[test]
+fn test_standalone_submit_batch() {
+    let mut runner = standalone::deploy_evm();
+    let mut signer = test_utils::Signer::random();
+    let source = test_utils::address_from_secret_key(&signer.secret_key);
+    let dest = test_utils::address_from_hex("0x000000000000000000000000000000000000000a");
+    runner.mint_account(source, INITIAL_BALANCE, INITIAL_NONCE.into());
+
+    // INVALID
+    let tx = test_utils::create_deploy_transaction(vec![0xfe], signer.use_nonce().into());
+    let result = standalone::submit_transaction(&mut runner, &signer.secret_key, tx);
+    let invalid = Address::from_slice(&test_utils::unwrap_success(result));
+
+    let nonce = U256::from(signer.use_nonce());
+    let sign = |tx| {
+        rlp::encode(&test_utils::sign_transaction(
+            tx,
+            Some(standalone::CHAIN_ID),
+            &signer.secret_key,
+        ))
+        .to_vec()
+    };
+    let transactions = vec![
+        sign(test_utils::transfer(dest, TRANSFER_AMOUNT, nonce)),
+        // Replays the first transaction.
+        sign(test_utils::transfer(dest, TRANSFER_AMOUNT, nonce)),
+        // Fails after incrementing the nonce, which must be discarded as well.
+        sign(test_utils::transfer(invalid, Wei::zero(), nonce + 1)),
+        sign(test_utils::transfer(dest, TRANSFER_AMOUNT, nonce + 1)),
+        Vec::new(),
+    ];
+    let results = runner.submit_batch(&transactions).unwrap();
+
+    let outcomes: Vec<Result<bool, &str>> = results
+        .iter()
+        .map(|result| match result {
+            BatchTransactionResult::Executed(result) => Ok(result.status.is_ok()),
+            BatchTransactionResult::Failed(e) => Err(e.as_str()),
+        })
+        .collect();
+    assert_eq!(
+        outcomes,
+        vec![
+            Ok(true),
+            Err("ERR_INCORRECT_NONCE"),
+            Err("ERR_DESIGNATED_INVALID"),
+            Ok(true),
+            Err("ERR_EMPTY_TX"),
+        ]
+    );
+    assert_eq!(runner.get_nonce(&source), nonce + 2);
+    assert_eq!(runner.get_balance(&dest), TRANSFER_AMOUNT + TRANSFER_AMOUNT);
+}
+
+#[test]
+fn test_standalone_transaction_queue() {
+    let mut runner = standalone::deploy_evm();
+    let signer = test_utils::Signer::random();
+    let source = test_utils::address_from_secret_key(&signer.secret_key);
+    let dest = test_utils::address_from_hex("0x000000000000000000000000000000000000000a");
+    runner.mint_account(source, INITIAL_BALANCE, INITIAL_NONCE.into());
+    transaction_queue::set_limit(&mut runner.io(), 2);
+
+    let sign = |nonce: u64, gas_price: u64| {
+        let tx = TransactionLegacy {
+            gas_price: gas_price.into(),
+            gas_limit: 30_000.into(),
+            ..test_utils::transfer(dest, TRANSFER_AMOUNT, nonce.into())
+        };
+        rlp::encode(&test_utils::sign_transaction(
+            tx,
+            Some(standalone::CHAIN_ID),
+            &signer.secret_key,
+        ))
+        .to_vec()
+    };
+    let error = |result: Result<_, RunnerError>| match result {
+        Err(RunnerError::Engine(e)) => e.kind.to_str().to_string(),
+        other => panic!("Unexpected result: {:?}", other),
+    };
+
+    let result = runner.submit(&sign(1, 10)).unwrap();
+    assert_eq!(result.status, TransactionStatus::Queued);
+    assert_eq!(runner.get_nonce(&source), U256::zero());
+
+    // A replacement must pay at least 10% more.
+    assert_eq!(
+        error(runner.submit(&sign(1, 10))),
+        "ERR_REPLACEMENT_UNDERPRICED"
+    );
+    let replacement = sign(1, 11);
+    let result = runner.submit(&replacement).unwrap();
+    assert_eq!(result.status, TransactionStatus::Queued);
+
+    // The balance must cover all the queued transactions, not only the new one.
+    assert_eq!(error(runner.submit(&sign(2, 23))), "ERR_OUT_OF_FUND");
+    runner.submit(&sign(2, 10)).unwrap();
+    assert_eq!(error(runner.submit(&sign(3, 10))), "ERR_TX_QUEUE_FULL");
+    let pending = runner.get_pending_transactions(&source);
+    assert_eq!(
+        pending.iter().map(|tx| tx.nonce).collect::<Vec<_>>(),
+        vec![1, 2]
+    );
+    assert_eq!(pending[0].transaction, replacement);
+
+    // Filling the gap executes the queued transactions.
+    let result = runner.submit(&sign(0, 10)).unwrap();
+    assert!(result.status.is_ok());
+    assert_eq!(runner.get_nonce(&source), U256::from(3));
+    assert_eq!(
+        runner.get_balance(&dest),
+        TRANSFER_AMOUNT + TRANSFER_AMOUNT + TRANSFER_AMOUNT
+    );
+    assert!(runner.get_pending_transactions(&source).is_empty());
+    assert!(runner
+        .get_transaction_receipt(&sdk::keccak(&replacement))
+        .is_some());
+}
+
+#[test]
+fn test_standalone_transaction_queue_storage_stake() {
+    use aurora_engine::storage_staking;
+
+    let signer = test_utils::Signer::random();
+    let source = test_utils::address_from_secret_key(&signer.secret_key);
+    let dest = test_utils::address_from_hex("0x000000000000000000000000000000000000000a");
+    let new_runner = || {
+        let runner = standalone::deploy_evm();
+        runner.mint_account(source, INITIAL_BALANCE, INITIAL_NONCE.into());
+        transaction_queue::set_limit(&mut runner.io(), 1);
+        storage_staking::set_price_per_byte(&mut runner.io(), &U256::one());
+        runner
+    };
+    let sign = |nonce: u64| {
+        rlp::encode(&test_utils::sign_transaction(
+            test_utils::transfer(dest, TRANSFER_AMOUNT, nonce.into()),
+            Some(standalone::CHAIN_ID),
+            &signer.secret_key,
+        ))
+        .to_vec()
+    };
+
+    // Queuing a transaction stakes for its entries.
+    let mut runner = new_runner();
+    runner.submit(&sign(1)).unwrap();
+    let stake = storage_staking::get_total_stake(&runner.io());
+    assert!(stake > Wei::zero());
+    assert_eq!(runner.get_balance(&source), INITIAL_BALANCE - stake);
+
+    // Taking the transaction from the queue refunds the stake: the outcome is the same as
+    // submitting the transactions in order.
+    runner.submit(&sign(0)).unwrap();
+    assert!(runner.get_pending_transactions(&source).is_empty());
+    let mut expected = new_runner();
+    expected.submit(&sign(0)).unwrap();
+    expected.submit(&sign(1)).unwrap();
+    assert_eq!(
+        storage_staking::get_total_stake(&runner.io()),
+        storage_staking::get_total_stake(&expected.io())
+    );
+    assert_eq!(runner.get_balance(&source), expected.get_balance(&source));
+}
+
+#[test]
+fn test_standalone_transaction_queue_limits() {
+    let mut runner = standalone::deploy_evm();
+    let signer = test_utils::Signer::random();
+    let source = test_utils::address_from_secret_key(&signer.secret_key);
+    let dest = test_utils::address_from_hex("0x000000000000000000000000000000000000000a");
+    runner.mint_account(source, INITIAL_BALANCE, INITIAL_NONCE.into());
+    transaction_queue::set_limit(&mut runner.io(), 6);
+
+    let sign = |nonce: u64| {
+        rlp::encode(&test_utils::sign_transaction(
+            test_utils::transfer(dest, TRANSFER_AMOUNT, nonce.into()),
+            Some(standalone::CHAIN_ID),
+            &signer.secret_key,
+        ))
+        .to_vec()
+    };
+    let pending_nonces = |runner: &EngineRunner| {
+        runner
+            .get_pending_transactions(&source)
+            .iter()
+            .map(|tx| tx.nonce)
+            .collect::<Vec<_>>()
+    };
+
+    for nonce in 1..=6 {
+        runner.submit(&sign(nonce)).unwrap();
+    }
+
+    // Filling the gap executes a bounded number of queued transactions.
+    runner.submit(&sign(0)).unwrap();
+    let executed = 1 + transaction_queue::MAX_QUEUED_EXECUTIONS as u64;
+    assert_eq!(runner.get_nonce(&source), U256::from(executed));
+    assert_eq!(pending_nonces(&runner), (executed..=6).collect::<Vec<_>>());
+
+    // The next submit of the sender executes the rest.
+    runner.submit(&sign(8)).unwrap();
+    assert_eq!(runner.get_nonce(&source), U256::from(7));
+    assert_eq!(pending_nonces(&runner), vec![8]);
+
+    // An expired transaction is dropped instead of being executed.
+    runner.env.block_height += transaction_queue::QUEUE_TTL_BLOCKS;
+    runner.submit(&sign(7)).unwrap();
+    assert_eq!(runner.get_nonce(&source), U256::from(8));
+    assert!(pending_nonces(&runner).is_empty());
+}
+
+#[test]
+fn test_standalone_storage_persistence() {
+    let runner = standalone::deploy_evm();
+    let address = test_utils::address_from_hex("0x000000000000000000000000000000000000000b");
+    runner.mint_account(address, INITIAL_BALANCE, INITIAL_NONCE.into());
+
+    let path =
+        std::env::temp_dir().join(format!("aurora-standalone-{}.borsh", hex::encode(address)));
+    runner.storage.borrow().save(&path).unwrap();
+    let storage = Storage::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let restored = EngineRunner::new(storage, runner.env.clone());
+    assert_eq!(restored.get_balance(&address), INITIAL_BALANCE);
+    assert_eq!(*restored.storage.borrow(), *runner.storage.borrow());
+}
+
+#[test]
+fn test_cross_contract_call_allowance() {
+    use crate::prelude::parameters::{CrossContractCallPermission, PromiseArgs};
+    use crate::prelude::PromiseCreateArgs;
+    use aurora_engine::cross_contract_call;
+
+    let runner = standalone::deploy_evm();
+    let mut io = runner.io();
+    let caller = test_utils::address_from_hex("0x000000000000000000000000000000000000000c");
+    let permission = CrossContractCallPermission {
+        max_attached_balance: 10,
+        max_attached_gas: 10,
+        deposit_allowance: 25,
+    };
+    cross_contract_call::set_permission(&mut io, &caller, Some(&permission));
+
+    let call = |attached_balance: u128| {
+        PromiseArgs::Create(PromiseCreateArgs {
+            target_account_id: "target.near".parse().unwrap(),
+            method: "method".to_string(),
+            args: Vec::new(),
+            attached_balance,
+            attached_gas: 10,
+        })
+    };
+    cross_contract_call::consume_allowance(&mut io, &caller, &call(10));
+    cross_contract_call::consume_allowance(&mut io, &caller, &call(0));
+    assert_eq!(
+        cross_contract_call::get_permission(&io, &caller),
+        Some(CrossContractCallPermission {
+            deposit_allowance: 15,
+            ..permission
+        })
+    );
+}
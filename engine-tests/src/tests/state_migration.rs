@@ -1,27 +1,294 @@
+use crate::prelude::storage::{bytes_to_key, KeyPrefix};
 use crate::prelude::U256;
-use crate::test_utils::{self, str_to_account_id, AuroraRunner};
-use aurora_engine::parameters::{InitCallArgs, NewCallArgs};
+use crate::test_utils::{self, standalone, str_to_account_id, AuroraRunner};
+use aurora_engine::base_fee::BaseFeeState;
+use aurora_engine::engine;
+use aurora_engine::fee_policy::FeePolicy;
+use aurora_engine::migration::{self, Migration, MigrationError, MigrationOutcome, StepStatus};
+use aurora_engine::parameters::{DeployUpgradeArgs, InitCallArgs, NewCallArgs, StageUpgradeArgs};
+use aurora_engine_sdk::io::IO;
+use aurora_engine_standalone::{StandaloneEnv, StandaloneIO};
 use borsh::BorshSerialize;
 use near_sdk_sim::{ExecutionResult, UserAccount};
+use sha2::Digest;
 use std::fs;
 use std::path::Path;
 
 #[test]
 fn test_state_migration() {
     let aurora = deploy_evm();
+    // the upgraded contract appends two steps to the migrations of the engine
+    let engine_version = migration::latest_version(&migration::registry::<StandaloneIO>());
 
     // do upgrade
     let upgraded_contract_bytes = contract_bytes();
     aurora
-        .call("stage_upgrade", &upgraded_contract_bytes)
+        .call(
+            "stage_upgrade",
+            &stage_upgrade_args(&upgraded_contract_bytes, true),
+        )
+        .assert_success();
+    aurora
+        .call("deploy_upgrade", &deploy_upgrade_args(true))
         .assert_success();
-    aurora.call("deploy_upgrade", &[]).assert_success();
 
     // upgraded contract as some_new_fancy_function
     let result = aurora.call("some_new_fancy_function", &[]);
     result.assert_success();
     let some_numbers: [u32; 7] = result.unwrap_borsh();
     assert_eq!(some_numbers, [3, 1, 4, 1, 5, 9, 2]);
+
+    // the migration writing entries is done in chunks of 4 entries: only the first one was
+    // done right after the upgrade
+    assert_eq!(
+        aurora.call("get_state_version", &[]).unwrap_borsh::<u32>(),
+        engine_version + 1
+    );
+    assert_eq!(aurora.call("count_entries", &[]).unwrap_borsh::<u32>(), 4);
+
+    // so the owner resumes it
+    let result = aurora.call("state_migration", &[]);
+    assert_eq!(
+        result.unwrap_borsh::<MigrationOutcome>(),
+        MigrationOutcome::Pending(engine_version + 1)
+    );
+    assert_eq!(aurora.call("count_entries", &[]).unwrap_borsh::<u32>(), 8);
+    let result = aurora.call("state_migration", &[]);
+    assert_eq!(
+        result.unwrap_borsh::<MigrationOutcome>(),
+        MigrationOutcome::Done(engine_version + 2)
+    );
+    assert_eq!(aurora.call("count_entries", &[]).unwrap_borsh::<u32>(), 10);
+
+    // running the migrations again does nothing
+    let result = aurora.call("state_migration", &[]);
+    assert_eq!(
+        result.unwrap_borsh::<MigrationOutcome>(),
+        MigrationOutcome::Done(engine_version + 2)
+    );
+    assert_eq!(
+        aurora.call("get_state_version", &[]).unwrap_borsh::<u32>(),
+        engine_version + 2
+    );
+
+    // and nobody else can run them
+    let other = aurora
+        .user
+        .create_user("other.root".parse().unwrap(), near_sdk_sim::STORAGE_AMOUNT);
+    let result = other.call(
+        aurora.contract.account_id.clone(),
+        "state_migration",
+        &[],
+        near_sdk_sim::DEFAULT_GAS,
+        0,
+    );
+    assert!(!result.is_ok());
+}
+
+#[test]
+fn test_staged_upgrade() {
+    let aurora = deploy_evm();
+
+    // with validation, the staged code must be a wasm module exporting the methods required to
+    // upgrade
+    let invalid_code = b"definitely not wasm";
+    let result = aurora.call("stage_upgrade", &stage_upgrade_args(invalid_code, true));
+    assert!(!result.is_ok());
+    let result = aurora.call("get_staged_upgrade_size", &[]);
+    assert!(!result.is_ok());
+
+    // without it, the code is staged as is, but can still be checked when deployed
+    aurora
+        .call("stage_upgrade", &stage_upgrade_args(invalid_code, false))
+        .assert_success();
+    let result = aurora.call("deploy_upgrade", &deploy_upgrade_args(true));
+    assert!(!result.is_ok());
+    aurora.call("cancel_upgrade", &[]).assert_success();
+
+    let upgraded_contract_bytes = contract_bytes();
+    aurora
+        .call(
+            "stage_upgrade",
+            &stage_upgrade_args(&upgraded_contract_bytes, true),
+        )
+        .assert_success();
+    let result = aurora.call("get_staged_upgrade_hash", &[]);
+    result.assert_success();
+    assert_eq!(
+        result.unwrap(),
+        sha2::Sha256::digest(&upgraded_contract_bytes).to_vec()
+    );
+    let result = aurora.call("get_staged_upgrade_size", &[]);
+    result.assert_success();
+    assert_eq!(
+        result.unwrap(),
+        (upgraded_contract_bytes.len() as u64)
+            .to_le_bytes()
+            .to_vec()
+    );
+
+    // once cancelled, there is nothing left to deploy
+    aurora.call("cancel_upgrade", &[]).assert_success();
+    assert!(!aurora
+        .call("deploy_upgrade", &deploy_upgrade_args(false))
+        .is_ok());
+    assert!(!aurora.call("cancel_upgrade", &[]).is_ok());
+}
+
+#[test]
+fn test_state_version_of_new_deployment() {
+    let aurora = deploy_evm();
+    let version: u32 = aurora.call("get_state_version", &[]).unwrap_borsh();
+    let migrations = migration::registry::<StandaloneIO>();
+    assert_eq!(version, migration::latest_version(&migrations));
+
+    // there is nothing to migrate
+    let result = aurora.call("state_migration", &[]);
+    assert_eq!(
+        result.unwrap_borsh::<MigrationOutcome>(),
+        MigrationOutcome::Done(version)
+    );
+}
+
+#[test]
+fn test_migrate_resumes_pending_step() {
+    let runner = standalone::deploy_evm();
+    let mut io = runner.io();
+    let mut migrations = migration::registry();
+    let version = migration::latest_version(&migrations);
+    migrations.push(Migration {
+        version: version + 1,
+        name: "chunked",
+        run: increment_counter,
+    });
+    migration::set_version(&mut io, version);
+
+    assert_eq!(
+        migration::migrate(&mut io, &migrations),
+        Ok(MigrationOutcome::Pending(version))
+    );
+    assert_eq!(
+        migration::migrate(&mut io, &migrations),
+        Ok(MigrationOutcome::Pending(version))
+    );
+    assert_eq!(
+        migration::migrate(&mut io, &migrations),
+        Ok(MigrationOutcome::Done(version + 1))
+    );
+    assert_eq!(migration::get_version(&io), version + 1);
+    assert_eq!(migration::get_cursor(&io), None);
+    assert_eq!(io.read_storage(COUNTER_KEY).unwrap().into_vec(), vec![5]);
+
+    // Running the migrations again does nothing.
+    assert_eq!(
+        migration::migrate(&mut io, &migrations),
+        Ok(MigrationOutcome::Done(version + 1))
+    );
+    assert_eq!(io.read_storage(COUNTER_KEY).unwrap().into_vec(), vec![5]);
+
+    // A state newer than the code cannot be migrated.
+    migration::set_version(&mut io, version + 2);
+    assert_eq!(
+        migration::migrate(&mut io, &migrations),
+        Err(MigrationError::UnknownVersion)
+    );
+    migrations.reverse();
+    assert_eq!(
+        migration::migrate(&mut io, &migrations),
+        Err(MigrationError::InvalidRegistry)
+    );
+}
+
+#[test]
+fn test_fee_policy_and_base_fee_migration() {
+    let runner = standalone::deploy_evm();
+    let mut io = runner.io();
+    let state = engine::get_state(&io).unwrap();
+
+    // The state as stored before the fee policy and the base fee were added to it.
+    let legacy_state = (
+        state.chain_id,
+        state.owner_id.clone(),
+        state.bridge_prover_id.clone(),
+        state.upgrade_delay_blocks,
+    );
+    io.write_storage(
+        &bytes_to_key(KeyPrefix::Config, b"STATE"),
+        &legacy_state.try_to_vec().unwrap(),
+    );
+    assert!(engine::get_state(&io).is_err());
+
+    migration::set_version(&mut io, 1);
+    let migrations = migration::registry();
+    let version = migration::latest_version(&migrations);
+    assert_eq!(
+        migration::migrate(&mut io, &migrations),
+        Ok(MigrationOutcome::Done(version))
+    );
+    let migrated = engine::get_state(&io).unwrap();
+    assert_eq!(migrated.chain_id, state.chain_id);
+    assert_eq!(migrated.owner_id, state.owner_id);
+    assert_eq!(migrated.upgrade_delay_blocks, state.upgrade_delay_blocks);
+    assert_eq!(migrated.fee_policy, FeePolicy::default());
+    assert_eq!(migrated.base_fee, BaseFeeState::default());
+}
+
+#[test]
+fn test_owner_can_migrate_legacy_state() {
+    let runner = standalone::deploy_evm();
+    let mut io = runner.io();
+    let state = engine::get_state(&io).unwrap();
+
+    // The state as stored before the fee policy and the base fee were added to it.
+    let legacy_state = (
+        state.chain_id,
+        state.owner_id.clone(),
+        state.bridge_prover_id.clone(),
+        state.upgrade_delay_blocks,
+    );
+    io.write_storage(
+        &bytes_to_key(KeyPrefix::Config, b"STATE"),
+        &legacy_state.try_to_vec().unwrap(),
+    );
+    migration::set_version(&mut io, 1);
+
+    // Only the owner of the legacy state is allowed to run the migrations.
+    let migrations = migration::registry();
+    let mut env = StandaloneEnv {
+        current_account_id: str_to_account_id("engine.near"),
+        predecessor_account_id: str_to_account_id("other.near"),
+        ..Default::default()
+    };
+    assert_eq!(
+        migration::state_migration(&mut io, &env, &migrations),
+        Err(MigrationError::NotAllowed)
+    );
+    env.predecessor_account_id = state.owner_id.clone();
+    assert_eq!(
+        migration::state_migration(&mut io, &env, &migrations),
+        Ok(MigrationOutcome::Done(migration::latest_version(
+            &migrations
+        )))
+    );
+    assert_eq!(engine::get_state(&io).unwrap().owner_id, state.owner_id);
+}
+
+const COUNTER_KEY: &[u8] = b"COUNTER";
+
+/// Increments a counter 5 times, 2 at a time.
+fn increment_counter(io: &mut StandaloneIO, cursor: Option<&[u8]>) -> StepStatus {
+    let start = cursor.map(|cursor| cursor[0]).unwrap_or(0);
+    let end = (start + 2).min(5);
+    let counter = io
+        .read_storage(COUNTER_KEY)
+        .map(|value| value.into_vec()[0])
+        .unwrap_or(0);
+    io.write_storage(COUNTER_KEY, &[counter + end - start]);
+    if end == 5 {
+        StepStatus::Done
+    } else {
+        StepStatus::Pending(vec![end])
+    }
 }
 
 pub fn deploy_evm() -> AuroraAccount {
@@ -85,6 +352,19 @@ impl AuroraAccount {
     }
 }
 
+fn stage_upgrade_args(code: &[u8], validate: bool) -> Vec<u8> {
+    StageUpgradeArgs {
+        code: code.to_vec(),
+        validate,
+    }
+    .try_to_vec()
+    .unwrap()
+}
+
+fn deploy_upgrade_args(validate: bool) -> Vec<u8> {
+    DeployUpgradeArgs { validate }.try_to_vec().unwrap()
+}
+
 fn contract_bytes() -> Vec<u8> {
     let base_path = Path::new("../etc").join("state-migration-test");
     let output_path = base_path
@@ -0,0 +1,51 @@
+use crate::test_utils::state_tests::{self, Report};
+use std::path::PathBuf;
+
+/// Runs the `GeneralStateTests` of a local checkout of https://github.com/ethereum/tests,
+/// given by the `ETHEREUM_TESTS_DIR` environment variable, and prints a per-fork report.
+/// A single directory (or file) of the suite can be selected with `ETHEREUM_TESTS_FILTER`.
+#[test]
+#[ignore]
+fn test_general_state_tests() {
+    let tests_dir =
+        PathBuf::from(std::env::var("ETHEREUM_TESTS_DIR").expect("ETHEREUM_TESTS_DIR must be set"));
+    let mut fixtures_dir = tests_dir.join("GeneralStateTests");
+    if let Ok(filter) = std::env::var("ETHEREUM_TESTS_FILTER") {
+        fixtures_dir = fixtures_dir.join(filter);
+    }
+    let fixtures = if fixtures_dir.is_dir() {
+        state_tests::find_fixtures(&fixtures_dir)
+    } else {
+        vec![fixtures_dir]
+    };
+
+    let mut report = Report::default();
+    for path in fixtures {
+        for (name, test) in state_tests::load_fixture(&path) {
+            state_tests::run_state_test(&name, &test, &mut report);
+        }
+    }
+    println!("{}", report);
+
+    let failed: usize = report.forks.values().map(|r| r.failed.len()).sum();
+    assert_eq!(failed, 0, "{} state tests failed", failed);
+}
+
+#[test]
+fn test_trie_root() {
+    assert_eq!(
+        hex::encode(state_tests::trie_root(Vec::new())),
+        "56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421"
+    );
+
+    // Example of the Ethereum wiki, also used by the `trietest.json` consensus tests.
+    let entries = vec![
+        (b"doe".to_vec(), b"reindeer".to_vec()),
+        (b"dog".to_vec(), b"puppy".to_vec()),
+        (b"dogglesworth".to_vec(), b"cat".to_vec()),
+    ];
+    assert_eq!(
+        hex::encode(state_tests::trie_root(entries)),
+        "8aad789dff2f538bca5d8ea56e8abe10f4c7ba3a5dea95fea4cd6e7c3a1168d3"
+    );
+}
@@ -0,0 +1,98 @@
+use aurora_engine::tracing::{CallTracer, CallType, StepTracer};
+use aurora_engine_standalone::EngineRunner;
+
+use crate::prelude::parameters::ViewCallArgs;
+use crate::prelude::{Address, Wei, H256, U256};
+use crate::test_utils::{self, standalone};
+
+const INITIAL_BALANCE: Wei = Wei::new_u64(1_000_000);
+
+#[test]
+fn test_trace_view_steps() {
+    let (runner, signer, address) = initialize();
+    let source = test_utils::address_from_secret_key(&signer.secret_key);
+
+    let args = ViewCallArgs {
+        sender: source.0,
+        address: address.0,
+        amount: [0u8; 32],
+        input: Vec::new(),
+    };
+    let (tracer, status) = runner.trace_view(args, StepTracer::default());
+    let trace = tracer.finish(&status.unwrap());
+
+    let ops: Vec<u8> = trace.struct_logs.iter().map(|step| step.opcode).collect();
+    assert_eq!(ops, vec![0x60, 0x60, 0x52, 0x60, 0x60, 0xf3]);
+    let costs: Vec<u64> = trace.struct_logs.iter().map(|step| step.gas_cost).collect();
+    // MSTORE costs 3 plus 3 for expanding the memory to one word.
+    assert_eq!(costs, vec![3, 3, 6, 3, 3, 0]);
+    for pair in trace.struct_logs.windows(2) {
+        assert_eq!(pair[0].gas - pair[0].gas_cost, pair[1].gas);
+    }
+    assert!(trace.struct_logs.iter().all(|step| step.depth == 1));
+    assert_eq!(
+        trace.struct_logs[2].stack,
+        Some(vec![H256::from_low_u64_be(42), H256::zero()])
+    );
+    assert!(!trace.failed);
+    assert_eq!(
+        trace.return_value,
+        crate::prelude::u256_to_arr(&U256::from(42)).to_vec()
+    );
+}
+
+#[test]
+fn test_trace_submit_call_tree() {
+    let (mut runner, mut signer, address) = initialize();
+    let source = test_utils::address_from_secret_key(&signer.secret_key);
+
+    let tx = test_utils::transfer(address, Wei::zero(), signer.use_nonce().into());
+    let signed_tx =
+        test_utils::sign_transaction(tx, Some(standalone::CHAIN_ID), &signer.secret_key);
+    let (tracer, result) = runner.trace_submit(&rlp::encode(&signed_tx), CallTracer::new());
+    let result = result.unwrap();
+    let call = tracer.finish().unwrap();
+
+    assert_eq!(call.call_type, CallType::Call);
+    assert_eq!(call.from, source);
+    assert_eq!(call.to, address);
+    assert_eq!(call.error, None);
+    assert!(call.calls.is_empty());
+    assert_eq!(call.output, test_utils::unwrap_success(result));
+}
+
+#[test]
+fn test_trace_transaction_without_effects() {
+    let (runner, mut signer, address) = initialize();
+    let source = test_utils::address_from_secret_key(&signer.secret_key);
+    let storage = runner.storage.borrow().clone();
+
+    let tx = test_utils::transfer(address, Wei::new_u64(7), signer.use_nonce().into());
+    let signed_tx =
+        test_utils::sign_transaction(tx, Some(standalone::CHAIN_ID), &signer.secret_key);
+    let (tracer, result) =
+        runner.trace_transaction(&rlp::encode(&signed_tx), StepTracer::default());
+    let trace = tracer.finish(&result.unwrap().status);
+
+    let ops: Vec<u8> = trace.struct_logs.iter().map(|step| step.opcode).collect();
+    assert_eq!(ops, vec![0x60, 0x60, 0x52, 0x60, 0x60, 0xf3]);
+    assert!(!trace.failed);
+    assert_eq!(*runner.storage.borrow(), storage);
+    assert_eq!(runner.get_nonce(&source), U256::one());
+    assert_eq!(runner.get_balance(&address), Wei::zero());
+}
+
+fn initialize() -> (EngineRunner, test_utils::Signer, Address) {
+    let mut runner = standalone::deploy_evm();
+    let mut signer = test_utils::Signer::random();
+    let source = test_utils::address_from_secret_key(&signer.secret_key);
+    runner.mint_account(source, INITIAL_BALANCE, signer.nonce.into());
+
+    // PUSH1 0x2a PUSH1 0x00 MSTORE PUSH1 0x20 PUSH1 0x00 RETURN
+    let code = hex::decode("602a60005260206000f3").unwrap();
+    let tx = test_utils::create_deploy_transaction(code, signer.use_nonce().into());
+    let result = standalone::submit_transaction(&mut runner, &signer.secret_key, tx);
+    let address = Address::from_slice(&test_utils::unwrap_success(result));
+
+    (runner, signer, address)
+}
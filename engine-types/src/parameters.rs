@@ -11,7 +11,7 @@ pub enum PromiseArgs {
 }
 
 #[must_use]
-#[derive(Debug, BorshSerialize, BorshDeserialize)]
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
 pub struct PromiseCreateArgs {
     pub target_account_id: AccountId,
     pub method: String,
@@ -27,7 +27,7 @@ pub struct PromiseWithCallbackArgs {
     pub callback: PromiseCreateArgs,
 }
 
-#[derive(Debug, BorshSerialize, BorshDeserialize)]
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
 pub enum PromiseAction {
     Transfer {
         amount: u128,
@@ -44,12 +44,58 @@ pub enum PromiseAction {
 }
 
 #[must_use]
-#[derive(Debug, BorshSerialize, BorshDeserialize)]
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
 pub struct PromiseBatchAction {
     pub target_account_id: AccountId,
     pub actions: Vec<PromiseAction>,
 }
 
+/// Borsh-encoded input of the cross-contract call precompile.
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
+pub struct CrossContractCallArgs {
+    /// The NEAR function call to schedule.
+    pub call: PromiseCreateArgs,
+    /// Function of the calling contract to call with the result of `call`, once it is done.
+    pub callback: Option<EvmCallbackArgs>,
+}
+
+/// The callback is always made to the contract which scheduled the call, so that contract
+/// cannot make the engine call another one on its behalf.
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct EvmCallbackArgs {
+    /// Selector of the function of the calling contract to call.
+    pub selector: [u8; 4],
+}
+
+/// Internal log of the cross-contract call precompile: the promise to schedule, along with the
+/// EVM contract scheduling it, whose deposit allowance pays for it.
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+pub struct CrossContractCallLog {
+    pub caller: EthAddress,
+    pub promise: PromiseArgs,
+}
+
+/// Arguments of the engine method calling back an EVM contract with the result of a NEAR call
+/// scheduled by the cross-contract call precompile.
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct CrossContractCallbackArgs {
+    /// The EVM contract which scheduled the NEAR call.
+    pub caller: EthAddress,
+    pub callback: EvmCallbackArgs,
+}
+
+/// Bounds of the NEAR calls an EVM contract is allowed to schedule through the cross-contract
+/// call precompile. Contracts without one cannot use the precompile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct CrossContractCallPermission {
+    /// Maximum NEAR deposit, in yoctoNEAR, attached to a call. It is paid by the engine account.
+    pub max_attached_balance: u128,
+    pub max_attached_gas: u64,
+    /// Total NEAR deposit, in yoctoNEAR, the contract can still attach to its calls. Each call
+    /// scheduled consumes its deposit, and the owner tops it up by setting the permission again.
+    pub deposit_allowance: u128,
+}
+
 /// withdraw NEAR eth-connector call args
 #[derive(BorshSerialize, BorshDeserialize)]
 pub struct WithdrawCallArgs {
@@ -58,9 +104,58 @@ pub struct WithdrawCallArgs {
 }
 
 /// withdraw NEAR eth-connector call args
-#[derive(BorshSerialize, BorshDeserialize)]
+#[derive(BorshSerialize)]
 pub struct RefundCallArgs {
     pub recipient_address: EthAddress,
     pub erc20_address: Option<EthAddress>,
     pub amount: RawU256,
+    /// ID of the exit, assigned by the engine when it schedules the exit promise.
+    /// The callbacks scheduled before the exits had an ID lack it, and decode with an ID of 0.
+    pub exit_id: u64,
+}
+
+impl BorshDeserialize for RefundCallArgs {
+    fn deserialize(buf: &mut &[u8]) -> borsh::maybestd::io::Result<Self> {
+        let recipient_address = EthAddress::deserialize(buf)?;
+        let erc20_address = Option::<EthAddress>::deserialize(buf)?;
+        let amount = RawU256::deserialize(buf)?;
+        let exit_id = if buf.is_empty() {
+            0
+        } else {
+            u64::deserialize(buf)?
+        };
+        Ok(Self {
+            recipient_address,
+            erc20_address,
+            amount,
+            exit_id,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_refund_call_args_legacy_layout() {
+        let recipient_address = [1u8; 20];
+        let erc20_address = Some([2u8; 20]);
+        let amount = [3u8; 32];
+
+        // Layout of the callbacks scheduled before the exits had an ID
+        let legacy_bytes = (recipient_address, erc20_address, amount)
+            .try_to_vec()
+            .unwrap();
+        let args = RefundCallArgs::try_from_slice(&legacy_bytes).unwrap();
+        assert_eq!(args.recipient_address, recipient_address);
+        assert_eq!(args.erc20_address, erc20_address);
+        assert_eq!(args.amount, amount);
+        assert_eq!(args.exit_id, 0);
+
+        let args = RefundCallArgs { exit_id: 7, ..args };
+        let bytes = args.try_to_vec().unwrap();
+        assert_eq!(bytes.len(), legacy_bytes.len() + 8);
+        assert_eq!(RefundCallArgs::try_from_slice(&bytes).unwrap().exit_id, 7);
+    }
 }
@@ -20,6 +20,13 @@ pub enum KeyPrefix {
     Generation = 0x7,
     Nep141Erc20Map = 0x8,
     Erc20Nep141Map = 0x9,
+    BlockHash = 0xa,
+    Receipt = 0xb,
+    Logs = 0xc,
+    TransactionQueue = 0xd,
+    CrossContractCall = 0xe,
+    ExitStatus = 0xf,
+    StorageStake = 0x10,
 }
 
 /// Enum used to differentiate different storage keys used by eth-connector
@@ -50,6 +57,13 @@ impl From<KeyPrefixU8> for KeyPrefix {
             0x7 => Self::Generation,
             0x8 => Self::Nep141Erc20Map,
             0x9 => Self::Erc20Nep141Map,
+            0xa => Self::BlockHash,
+            0xb => Self::Receipt,
+            0xc => Self::Logs,
+            0xd => Self::TransactionQueue,
+            0xe => Self::CrossContractCall,
+            0xf => Self::ExitStatus,
+            0x10 => Self::StorageStake,
             _ => unreachable!(),
         }
     }
@@ -1,4 +1,4 @@
-use super::{str, vec, Add, Address, String, Sub, Vec, U256};
+use super::{format, str, vec, Add, Address, String, Sub, Vec, U256};
 use borsh::{BorshDeserialize, BorshSerialize};
 
 pub type Balance = u128;
@@ -141,6 +141,7 @@ pub struct StorageBalanceBounds {
 }
 
 /// promise results structure
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum PromiseResult {
     NotReady,
     Successful(Vec<u8>),
@@ -180,6 +181,22 @@ pub fn bytes_to_hex(v: &[u8]) -> String {
     result
 }
 
+/// Encodes `value` as a JSON string, escaping the quotes, backslashes and control characters.
+pub fn json_string(value: &str) -> String {
+    let mut result = String::with_capacity(value.len() + 2);
+    result.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            c if (c as u32) < 0x20 => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
 #[derive(Default)]
 pub struct Stack<T> {
     stack: Vec<T>,
@@ -223,6 +240,15 @@ pub fn str_from_slice(inp: &[u8]) -> &str {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_json_string() {
+        assert_eq!(json_string("abc"), r#""abc""#);
+        assert_eq!(
+            json_string("say \"hi\"\\\n"),
+            r#""say \"hi\"\\\u000a""#
+        );
+    }
+
     #[test]
     fn test_hex() {
         assert_eq!(
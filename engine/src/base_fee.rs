@@ -0,0 +1,199 @@
+//! EIP-1559 base fee of the engine.
+//!
+//! The base fee state is part of `EngineState`. By default it is fixed at zero.
+
+use crate::engine;
+use crate::prelude::{u256_to_arr, BorshDeserialize, BorshSerialize, RawU256, U256};
+use aurora_engine_sdk::io::IO;
+
+/// Bounds the amount the base fee can change between blocks (EIP-1559).
+const BASE_FEE_MAX_CHANGE_DENOMINATOR: u64 = 8;
+
+/// Upper bound on the number of empty blocks taken into account when catching up the base
+/// fee. Even the largest `u128` base fee stops decreasing long before that.
+const MAX_EMPTY_BLOCKS: u64 = 1024;
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BaseFeeMode {
+    /// The base fee only changes when set by the owner.
+    Fixed,
+    /// The base fee follows the EIP-1559 update rule, targeting `gas_target` gas per block.
+    Dynamic { gas_target: u64 },
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct BaseFeeState {
+    pub mode: BaseFeeMode,
+    /// Lowest value the base fee can reach in the `Dynamic` mode.
+    pub min_base_fee_per_gas: RawU256,
+    /// Base fee of the block at `block_height`.
+    pub base_fee_per_gas: RawU256,
+    pub block_height: u64,
+    /// Gas used by the transactions submitted in the block at `block_height`.
+    pub block_gas_used: u64,
+}
+
+impl Default for BaseFeeState {
+    fn default() -> Self {
+        Self {
+            mode: BaseFeeMode::Fixed,
+            min_base_fee_per_gas: [0u8; 32],
+            base_fee_per_gas: [0u8; 32],
+            block_height: 0,
+            block_gas_used: 0,
+        }
+    }
+}
+
+impl BaseFeeState {
+    /// Base fee of the block at `block_height`, derived from the last recorded block.
+    pub fn base_fee_at(&self, block_height: u64) -> U256 {
+        let base_fee = U256::from(self.base_fee_per_gas);
+        let gas_target = match self.mode {
+            BaseFeeMode::Dynamic { gas_target } if block_height > self.block_height => gas_target,
+            _ => return base_fee,
+        };
+        let min_base_fee = U256::from(self.min_base_fee_per_gas);
+
+        let mut base_fee = next_base_fee(base_fee, self.block_gas_used, gas_target, min_base_fee);
+        // Blocks without any transaction in between lower the base fee further.
+        let empty_blocks = (block_height - self.block_height - 1).min(MAX_EMPTY_BLOCKS);
+        for _ in 0..empty_blocks {
+            let next = next_base_fee(base_fee, 0, gas_target, min_base_fee);
+            if next == base_fee {
+                break;
+            }
+            base_fee = next;
+        }
+        base_fee
+    }
+
+    /// Accounts the gas used by a transaction executed in the block at `block_height`.
+    pub fn record_gas_used(&mut self, block_height: u64, gas_used: u64) {
+        if block_height > self.block_height {
+            self.base_fee_per_gas = u256_to_arr(&self.base_fee_at(block_height));
+            self.block_height = block_height;
+            self.block_gas_used = 0;
+        }
+        self.block_gas_used = self.block_gas_used.saturating_add(gas_used);
+    }
+}
+
+/// Base fee of the block following a block with the given base fee and gas used.
+pub fn next_base_fee(
+    parent_base_fee: U256,
+    parent_gas_used: u64,
+    gas_target: u64,
+    min_base_fee: U256,
+) -> U256 {
+    let denominator = U256::from(gas_target) * U256::from(BASE_FEE_MAX_CHANGE_DENOMINATOR);
+    let next_base_fee = if parent_gas_used > gas_target {
+        let gas_delta = U256::from(parent_gas_used - gas_target);
+        let delta = (parent_base_fee.saturating_mul(gas_delta) / denominator).max(U256::one());
+        parent_base_fee.saturating_add(delta)
+    } else {
+        let gas_delta = U256::from(gas_target - parent_gas_used);
+        let delta = parent_base_fee.saturating_mul(gas_delta) / denominator;
+        parent_base_fee.saturating_sub(delta)
+    };
+    next_base_fee.max(min_base_fee)
+}
+
+/// Updates the base fee state with the gas used by a transaction executed in the block at
+/// `block_height`. Nothing is written while the base fee is fixed.
+///
+/// The engine state is read again rather than taken from the caller, since the ones executing
+/// several transactions (e.g. `submit_batch`) hold a copy from before the first of them.
+pub fn record_gas_used<I: IO>(io: &mut I, block_height: u64, gas_used: u64) {
+    let mut state = match engine::get_state(io) {
+        Ok(state) => state,
+        Err(_) => return,
+    };
+    if state.base_fee.mode == BaseFeeMode::Fixed {
+        return;
+    }
+    state.base_fee.record_gas_used(block_height, gas_used);
+    engine::set_state(io, state);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GAS_TARGET: u64 = 15_000_000;
+
+    fn dynamic_state(base_fee: u64, block_gas_used: u64) -> BaseFeeState {
+        BaseFeeState {
+            mode: BaseFeeMode::Dynamic {
+                gas_target: GAS_TARGET,
+            },
+            min_base_fee_per_gas: u256_to_arr(&U256::from(7)),
+            base_fee_per_gas: u256_to_arr(&U256::from(base_fee)),
+            block_height: 10,
+            block_gas_used,
+        }
+    }
+
+    #[test]
+    fn test_next_base_fee() {
+        let base_fee = U256::from(1_000_000_000);
+        let min = U256::zero();
+        assert_eq!(
+            next_base_fee(base_fee, GAS_TARGET, GAS_TARGET, min),
+            base_fee
+        );
+        // Full block (twice the target): +12.5%
+        assert_eq!(
+            next_base_fee(base_fee, 2 * GAS_TARGET, GAS_TARGET, min),
+            U256::from(1_125_000_000)
+        );
+        // Empty block: -12.5%
+        assert_eq!(
+            next_base_fee(base_fee, 0, GAS_TARGET, min),
+            U256::from(875_000_000)
+        );
+        // The base fee always increases when the target is exceeded.
+        assert_eq!(
+            next_base_fee(U256::one(), GAS_TARGET + 1, GAS_TARGET, min),
+            U256::from(2)
+        );
+        // But never decreases below the minimum.
+        assert_eq!(
+            next_base_fee(U256::from(8), 0, GAS_TARGET, U256::from(7)),
+            U256::from(7)
+        );
+    }
+
+    #[test]
+    fn test_base_fee_at() {
+        let state = dynamic_state(1_000_000_000, 2 * GAS_TARGET);
+        assert_eq!(state.base_fee_at(10), U256::from(1_000_000_000));
+        assert_eq!(state.base_fee_at(11), U256::from(1_125_000_000));
+        // Followed by an empty block.
+        assert_eq!(state.base_fee_at(12), U256::from(984_375_000));
+        // Many empty blocks end up at the minimum.
+        assert_eq!(state.base_fee_at(10_000), U256::from(7));
+
+        let fixed = BaseFeeState {
+            mode: BaseFeeMode::Fixed,
+            ..state
+        };
+        assert_eq!(fixed.base_fee_at(11), U256::from(1_000_000_000));
+    }
+
+    #[test]
+    fn test_record_gas_used() {
+        let mut state = dynamic_state(1_000_000_000, 0);
+        state.record_gas_used(10, GAS_TARGET);
+        state.record_gas_used(10, GAS_TARGET);
+        assert_eq!(state.block_gas_used, 2 * GAS_TARGET);
+
+        state.record_gas_used(11, 21_000);
+        assert_eq!(state.block_height, 11);
+        assert_eq!(state.block_gas_used, 21_000);
+        assert_eq!(
+            U256::from(state.base_fee_per_gas),
+            U256::from(1_125_000_000)
+        );
+    }
+}
@@ -0,0 +1,104 @@
+//! Logs bloom filter, as defined in section 4.3.1 of the yellow paper.
+
+use crate::parameters::ResultLog;
+use crate::prelude::{sdk, BorshDeserialize, BorshSerialize};
+
+/// Size of a bloom filter, in bytes.
+pub const BLOOM_SIZE: usize = 256;
+
+/// Number of bits set for each input.
+const BLOOM_BITS: usize = 3;
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bloom(pub [u8; BLOOM_SIZE]);
+
+impl Default for Bloom {
+    fn default() -> Self {
+        Self([0u8; BLOOM_SIZE])
+    }
+}
+
+impl Bloom {
+    /// Bloom of the addresses and topics of the given logs.
+    pub fn from_logs(logs: &[ResultLog]) -> Self {
+        let mut bloom = Self::default();
+        for log in logs {
+            bloom.accrue_log(log);
+        }
+        bloom
+    }
+
+    pub fn accrue_log(&mut self, log: &ResultLog) {
+        self.accrue(&log.address);
+        for topic in &log.topics {
+            self.accrue(topic);
+        }
+    }
+
+    pub fn accrue(&mut self, input: &[u8]) {
+        for (index, mask) in Self::bits(input).iter() {
+            self.0[*index] |= mask;
+        }
+    }
+
+    /// Adds all the inputs of another bloom to this one.
+    pub fn accrue_bloom(&mut self, other: &Self) {
+        for (byte, other_byte) in self.0.iter_mut().zip(other.0.iter()) {
+            *byte |= other_byte;
+        }
+    }
+
+    /// Whether the input may have been added to the bloom (false positives are possible,
+    /// false negatives are not).
+    pub fn contains_input(&self, input: &[u8]) -> bool {
+        Self::bits(input)
+            .iter()
+            .all(|(index, mask)| self.0[*index] & mask != 0)
+    }
+
+    /// Byte index and mask of the bits set for `input`: each of the first three pairs of
+    /// bytes of its keccak hash gives the (11 bits) position of a bit, from the end.
+    fn bits(input: &[u8]) -> [(usize, u8); BLOOM_BITS] {
+        let hash = sdk::keccak(input);
+        let mut bits = [(0, 0); BLOOM_BITS];
+        for (i, bit) in bits.iter_mut().enumerate() {
+            let position =
+                (usize::from(hash[2 * i]) << 8 | usize::from(hash[2 * i + 1])) % (BLOOM_SIZE * 8);
+            *bit = (BLOOM_SIZE - 1 - position / 8, 1u8 << (position % 8));
+        }
+        bits
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::vec;
+
+    #[test]
+    fn test_bloom() {
+        let address = hex::decode("ef2d6d194084c2de36e0dabfce45d046b37d1106").unwrap();
+        let topic = hex::decode("02c69be41d0b7e40352fc85be1cd65eb03d40ef8427a0ca4596b1ead9a00e9fc")
+            .unwrap();
+        let mut address_arr = [0u8; 20];
+        address_arr.copy_from_slice(&address);
+        let mut topic_arr = [0u8; 32];
+        topic_arr.copy_from_slice(&topic);
+
+        let bloom = Bloom::from_logs(&[ResultLog {
+            address: address_arr,
+            topics: vec![topic_arr],
+            data: vec![],
+        }]);
+
+        assert!(bloom.contains_input(&address));
+        assert!(bloom.contains_input(&topic));
+        assert!(!bloom.contains_input(b"not in the bloom"));
+
+        let mut other = Bloom::default();
+        other.accrue(b"not in the bloom");
+        other.accrue_bloom(&bloom);
+        assert!(other.contains_input(&address));
+        assert!(other.contains_input(b"not in the bloom"));
+    }
+}
@@ -368,12 +368,31 @@ impl<I: IO + Copy> EthConnectorContract<I> {
         }
     }
 
+    /// Internal ETH deposit logic, used to fund accounts outside of the bridge flow
+    /// (e.g. by a standalone engine), while keeping the total supply in sync.
+    pub fn internal_add_eth(&mut self, address: &Address, amount: &U256) {
+        self.mint_eth_on_aurora(address.0, amount.as_u128());
+        self.save_ft_contract();
+    }
+
     /// Internal ETH withdraw ETH logic
     pub(crate) fn internal_remove_eth(&mut self, address: &Address, amount: &U256) {
         self.burn_eth_on_aurora(address.0, amount.as_u128());
         self.save_ft_contract();
     }
 
+    /// Removes ETH from the total supply on Aurora without changing any balance, for amounts
+    /// already taken from an account (e.g. the burned base fee of a transaction).
+    pub(crate) fn internal_burn_eth_supply(&mut self, amount: &U256) {
+        sdk::log!(&format!("Burn {} ETH tokens from the supply", amount));
+        self.ft.total_eth_supply_on_aurora = self
+            .ft
+            .total_eth_supply_on_aurora
+            .checked_sub(amount.as_u128())
+            .expect("ERR_TOTAL_SUPPLY_OVERFLOW");
+        self.save_ft_contract();
+    }
+
     /// Record used proof as hash key
     fn record_proof(&mut self, key: &str) {
         sdk::log!(&format!("Record proof: {}", key));
@@ -0,0 +1,118 @@
+//! Permissions of the EVM contracts using the cross-contract call precompile, and the input of
+//! the EVM callbacks of the NEAR calls they schedule.
+//!
+//! The owner gives each contract allowed to schedule NEAR calls a `CrossContractCallPermission`
+//! bounding the gas and deposit attached to them, along with the total deposit they can attach.
+//! The permissions are stored under the contract address, where the precompile reads them.
+
+use crate::prelude::parameters::{
+    CrossContractCallPermission, CrossContractCallbackArgs, PromiseArgs,
+};
+use crate::prelude::precompiles::promise_result;
+use crate::prelude::storage::{bytes_to_key, KeyPrefix};
+use crate::prelude::{Address, BorshDeserialize, BorshSerialize, PromiseResult, Vec, U256};
+use aurora_engine_sdk::io::{StorageIntermediate, IO};
+
+fn permission_key(address: &Address) -> Vec<u8> {
+    bytes_to_key(KeyPrefix::CrossContractCall, address.as_bytes())
+}
+
+pub fn get_permission<I: IO>(io: &I, address: &Address) -> Option<CrossContractCallPermission> {
+    io.read_storage(&permission_key(address))
+        .and_then(|bytes| CrossContractCallPermission::try_from_slice(&bytes.to_vec()).ok())
+}
+
+/// Sets the permission of the contract at `address`, or revokes it if `permission` is `None`.
+pub fn set_permission<I: IO>(
+    io: &mut I,
+    address: &Address,
+    permission: Option<&CrossContractCallPermission>,
+) {
+    let key = permission_key(address);
+    match permission {
+        Some(permission) => {
+            io.write_storage(&key, &permission.try_to_vec().expect("ERR_SER"));
+        }
+        None => {
+            io.remove_storage(&key);
+        }
+    }
+}
+
+/// Deducts the deposit attached to `promise` from the allowance of the contract which
+/// scheduled it. The precompile already checked the allowance covers it.
+pub fn consume_allowance<I: IO>(io: &mut I, caller: &Address, promise: &PromiseArgs) {
+    let attached_balance = match promise {
+        PromiseArgs::Create(call) => call.attached_balance,
+        PromiseArgs::Callback(call) => call
+            .base
+            .attached_balance
+            .saturating_add(call.callback.attached_balance),
+    };
+    if attached_balance == 0 {
+        return;
+    }
+    if let Some(mut permission) = get_permission(io, caller) {
+        permission.deposit_allowance = permission
+            .deposit_allowance
+            .saturating_sub(attached_balance);
+        set_permission(io, caller, Some(&permission));
+    }
+}
+
+/// Input of the EVM callback of a NEAR call with the given result: the selector given when
+/// scheduling the call, followed by the ABI encoding of
+/// `(address caller, uint256 status, bytes data)`, with the status and data returned by the
+/// promise results precompile.
+pub fn callback_input(args: &CrossContractCallbackArgs, result: &PromiseResult) -> Vec<u8> {
+    let (status, data) = promise_result::status_and_data(result);
+    let encoded = ethabi::encode(&[
+        ethabi::Token::Address(Address(args.caller)),
+        ethabi::Token::Uint(U256::from(status)),
+        ethabi::Token::Bytes(data.to_vec()),
+    ]);
+    [&args.callback.selector[..], encoded.as_slice()].concat()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::callback_input;
+    use crate::prelude::parameters::{CrossContractCallbackArgs, EvmCallbackArgs};
+    use crate::prelude::{Address, PromiseResult, U256};
+
+    #[test]
+    fn test_callback_input() {
+        let caller = Address([1u8; 20]);
+        let args = CrossContractCallbackArgs {
+            caller: caller.0,
+            callback: EvmCallbackArgs {
+                selector: [0xde, 0xad, 0xbe, 0xef],
+            },
+        };
+        let result = PromiseResult::Successful(b"hello".to_vec());
+
+        let input = callback_input(&args, &result);
+        assert_eq!(&input[..4], &[0xde, 0xad, 0xbe, 0xef]);
+        let tokens = ethabi::decode(
+            &[
+                ethabi::ParamType::Address,
+                ethabi::ParamType::Uint(256),
+                ethabi::ParamType::Bytes,
+            ],
+            &input[4..],
+        )
+        .unwrap();
+        assert_eq!(
+            tokens,
+            vec![
+                ethabi::Token::Address(caller),
+                ethabi::Token::Uint(U256::from(1)),
+                ethabi::Token::Bytes(b"hello".to_vec()),
+            ]
+        );
+
+        let input = callback_input(&args, &PromiseResult::Failed);
+        let status = U256::from_big_endian(&input[36..68]);
+        assert_eq!(status, U256::from(2));
+    }
+}
@@ -1,24 +1,41 @@
-use crate::parameters::{CallArgs, NEP141FtOnTransferArgs, ResultLog, SubmitResult, ViewCallArgs};
+use crate::parameters::{
+    CallArgs, EstimateGasArgs, EstimateGasResult, NEP141FtOnTransferArgs, ResultLog, SubmitResult,
+    TransactionReceipt, ViewCallArgs,
+};
+use core::cell::RefCell;
 use core::mem;
 use evm::backend::{Apply, ApplyBackend, Backend, Basic, Log};
 use evm::executor;
 use evm::{Config, CreateScheme, ExitError, ExitFatal, ExitReason};
 
+use crate::base_fee::{self, BaseFeeState};
+use crate::bloom::Bloom;
 use crate::connector::EthConnectorContract;
+use crate::cross_contract_call;
+use crate::exit_status;
+use crate::fee_policy::{self, FeePolicy};
+use crate::logs_index;
 use crate::map::BijectionMap;
+use crate::storage_staking::{self, StorageChanges, StorageStakingError};
+use crate::transaction_queue::{self, TransactionQueueError};
 use aurora_engine_sdk::env::Env;
-use aurora_engine_sdk::io::{StorageIntermediate, IO};
-use aurora_engine_sdk::promise::{PromiseHandler, PromiseId};
+use aurora_engine_sdk::io::{OverlayBuffer, OverlayIO, StorageIntermediate, IO};
+use aurora_engine_sdk::promise::{PromiseBuffer, PromiseHandler, PromiseId};
 
+#[cfg(feature = "evm_bully")]
+use crate::parameters::BeginBlockArgs;
 use crate::parameters::{NewCallArgs, TransactionStatus};
+use crate::prelude::parameters::CrossContractCallLog;
+use crate::prelude::precompiles::cross_contract_call::CrossContractCall;
 use crate::prelude::precompiles::native::{ExitToEthereum, ExitToNear};
 use crate::prelude::precompiles::Precompiles;
 use crate::prelude::{
-    address_to_key, bytes_to_key, sdk, storage_to_key, u256_to_arr, AccountId, Address,
-    BorshDeserialize, BorshSerialize, KeyPrefix, PromiseArgs, PromiseCreateArgs, TryFrom, TryInto,
-    Vec, Wei, ERC20_MINT_SELECTOR, H256, U256,
+    address_to_key, bytes_to_key, sdk, storage_to_key, u256_to_arr, AccountId, Address, BTreeMap,
+    BorshDeserialize, BorshSerialize, KeyPrefix, PromiseArgs, PromiseCreateArgs, RawAddress,
+    RawU256, ToString, TryFrom, TryInto, Vec, Wei, ERC20_MINT_SELECTOR, H256, U256,
 };
-use crate::transaction::NormalizedEthTransaction;
+use crate::transaction::eip_2930::AccessTuple;
+use crate::transaction::{EthTransactionKind, NormalizedEthTransaction, ParseTransactionError};
 
 /// Used as the first byte in the concatenation of data used to compute the blockhash.
 /// Could be useful in the future as a version byte, or to distinguish different types of blocks.
@@ -26,6 +43,11 @@ const BLOCK_HASH_PREFIX: u8 = 0;
 const BLOCK_HASH_PREFIX_SIZE: usize = 1;
 const BLOCK_HEIGHT_SIZE: usize = 8;
 const CHAIN_ID_SIZE: usize = 32;
+/// Number of recent block hashes available to the BLOCKHASH opcode.
+pub const BLOCK_HASH_HISTORY: u64 = 256;
+
+/// Maximum number of executions simulated by `estimate_gas` once the first one succeeded.
+pub const ESTIMATE_GAS_MAX_ITERATIONS: u32 = 24;
 
 pub fn current_address(current_account_id: &AccountId) -> Address {
     aurora_engine_sdk::types::near_account_to_evm_address(current_account_id.as_bytes())
@@ -72,6 +94,12 @@ impl AsRef<[u8]> for EngineError {
     }
 }
 
+impl From<EngineErrorKind> for EngineError {
+    fn from(kind: EngineErrorKind) -> Self {
+        kind.with_gas_used(0)
+    }
+}
+
 /// Errors with the EVM engine.
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum EngineErrorKind {
@@ -81,6 +109,24 @@ pub enum EngineErrorKind {
     EvmFatal(ExitFatal),
     /// Incorrect nonce.
     IncorrectNonce,
+    /// Failed to parse the signed transaction.
+    FailedTransactionParse(ParseTransactionError),
+    /// Transaction was signed for a different chain.
+    InvalidChainId,
+    /// Unable to recover the transaction signer.
+    InvalidSignature,
+    /// Transaction gas limit does not cover the intrinsic gas.
+    IntrinsicGasNotMet,
+    /// Overflow computing the transaction gas.
+    GasOverflow,
+    /// Max priority fee is greater than the max fee per gas.
+    MaxPriorityGasFeeTooLarge,
+    /// Failed to charge or refund the transaction gas.
+    GasPayment(GasPaymentError),
+    /// Failed to stake for, or refund, the storage used by the transaction.
+    StorageStaking(StorageStakingError),
+    /// Failed to queue a transaction with a future nonce.
+    TransactionQueue(TransactionQueueError),
 }
 
 impl EngineErrorKind {
@@ -112,6 +158,15 @@ impl EngineErrorKind {
             EvmFatal(ExitFatal::Other(m)) => m,
             EvmFatal(_) => unreachable!(), // unused misc
             IncorrectNonce => "ERR_INCORRECT_NONCE",
+            FailedTransactionParse(e) => e.as_str(),
+            InvalidChainId => "ERR_INVALID_CHAIN_ID",
+            InvalidSignature => "ERR_INVALID_ECDSA_SIGNATURE",
+            IntrinsicGasNotMet => "ERR_INTRINSIC_GAS",
+            GasOverflow => "ERR_GAS_OVERFLOW",
+            MaxPriorityGasFeeTooLarge => "ERR_MAX_PRIORITY_FEE_GREATER",
+            GasPayment(e) => e.to_str(),
+            StorageStaking(e) => e.to_str(),
+            TransactionQueue(e) => e.to_str(),
         }
     }
 }
@@ -163,6 +218,7 @@ impl ExitIntoResult for ExitReason {
     }
 }
 
+#[derive(Debug, Clone, Eq, PartialEq)]
 pub struct BalanceOverflow;
 
 impl AsRef<[u8]> for BalanceOverflow {
@@ -172,6 +228,7 @@ impl AsRef<[u8]> for BalanceOverflow {
 }
 
 /// Errors resulting from trying to pay for gas
+#[derive(Debug, Clone, Eq, PartialEq)]
 pub enum GasPaymentError {
     /// Overflow adding ETH to an account balance (should never happen)
     BalanceOverflow(BalanceOverflow),
@@ -179,18 +236,30 @@ pub enum GasPaymentError {
     EthAmountOverflow,
     /// Not enough balance for account to cover the gas cost
     OutOfFund,
+    /// The maximum fee per gas of the transaction is below the base fee of the block
+    MaxFeeTooLow,
+    /// The gas price of the transaction is below the minimum of the fee policy
+    GasPriceTooLow,
 }
 
-impl AsRef<[u8]> for GasPaymentError {
-    fn as_ref(&self) -> &[u8] {
+impl GasPaymentError {
+    pub fn to_str(&self) -> &str {
         match self {
-            Self::BalanceOverflow(overflow) => overflow.as_ref(),
-            Self::EthAmountOverflow => b"ERR_GAS_ETH_AMOUNT_OVERFLOW",
-            Self::OutOfFund => b"ERR_OUT_OF_FUND",
+            Self::BalanceOverflow(_) => "ERR_BALANCE_OVERFLOW",
+            Self::EthAmountOverflow => "ERR_GAS_ETH_AMOUNT_OVERFLOW",
+            Self::OutOfFund => "ERR_OUT_OF_FUND",
+            Self::MaxFeeTooLow => "ERR_MAX_FEE_LESS_THAN_BASE_FEE",
+            Self::GasPriceTooLow => "ERR_GAS_PRICE_TOO_LOW",
         }
     }
 }
 
+impl AsRef<[u8]> for GasPaymentError {
+    fn as_ref(&self) -> &[u8] {
+        self.to_str().as_bytes()
+    }
+}
+
 impl From<BalanceOverflow> for GasPaymentError {
     fn from(overflow: BalanceOverflow) -> Self {
         Self::BalanceOverflow(overflow)
@@ -307,6 +376,14 @@ impl StackExecutorParams {
         }
     }
 
+    /// Parameters of the executions of the view calls, without NEAR transaction.
+    fn new_view(gas_limit: u64, current_account_id: AccountId) -> Self {
+        Self {
+            precompiles: Precompiles::new_london(current_account_id).into_view(),
+            gas_limit,
+        }
+    }
+
     fn make_executor<'a, 'env, I: IO + Copy, E: Env>(
         &'a self,
         engine: &'a Engine<'env, I, E>,
@@ -327,11 +404,25 @@ pub struct GasPaymentResult {
     pub prepaid_amount: Wei,
     pub effective_gas_price: U256,
     pub priority_fee_per_gas: U256,
+    pub base_fee_per_gas: U256,
+}
+
+/// How the gas prepaid for a transaction was settled.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct GasRefundResult {
+    /// Unused gas, returned to the sender.
+    pub refunded: Wei,
+    /// Share of the priority fee of the used gas paid to the relayer.
+    pub relayer_reward: Wei,
+    /// Share of the priority fee of the used gas paid to the treasury of the fee policy.
+    pub treasury_reward: Wei,
+    /// Base fee of the used gas, and share of the priority fee, removed from the ETH supply.
+    pub burned: Wei,
 }
 
 /// Engine internal state, mostly configuration.
 /// Should not contain anything large or enumerable.
-#[derive(BorshSerialize, BorshDeserialize, Default)]
+#[derive(BorshSerialize, BorshDeserialize, Default, Clone)]
 pub struct EngineState {
     /// Chain id, according to the EIP-155 / ethereum-lists spec.
     pub chain_id: [u8; 32],
@@ -343,6 +434,10 @@ pub struct EngineState {
     pub bridge_prover_id: AccountId,
     /// How many blocks after staging upgrade can deploy it.
     pub upgrade_delay_blocks: u64,
+    /// How the fees paid for gas are distributed (see `fee_policy`).
+    pub fee_policy: FeePolicy,
+    /// EIP-1559 base fee, and how it evolves from one block to the next (see `base_fee`).
+    pub base_fee: BaseFeeState,
 }
 
 impl From<NewCallArgs> for EngineState {
@@ -352,6 +447,8 @@ impl From<NewCallArgs> for EngineState {
             owner_id: args.owner_id,
             bridge_prover_id: args.bridge_prover_id,
             upgrade_delay_blocks: args.upgrade_delay_blocks,
+            fee_policy: FeePolicy::default(),
+            base_fee: BaseFeeState::default(),
         }
     }
 }
@@ -360,16 +457,29 @@ pub struct Engine<'env, I: IO, E: Env> {
     state: EngineState,
     origin: Address,
     gas_price: U256,
+    base_fee_per_gas: U256,
     current_account_id: AccountId,
     io: I,
     env: &'env E,
+    /// Storage slots read since the last `apply`. The storage is only written to when a
+    /// transaction is applied, so these are also the values at the start of the transaction.
+    storage_cache: RefCell<BTreeMap<(Address, H256), H256>>,
+    /// Block context set by `begin_block` (only with the `evm_bully` feature).
+    block_context: Option<BlockContextOverride>,
+    /// Storage entries changed by the transaction being executed (see `storage_staking`).
+    storage_changes: StorageChanges,
+    /// Whether the storage used by the transactions is staked for by their origin.
+    storage_staking_exempt: bool,
 }
 
 // TODO: upgrade to Berlin HF
 pub(crate) const CONFIG: &Config = &Config::london();
 
 /// Key for storing the state of the engine.
-const STATE_KEY: &[u8; 5] = b"STATE";
+pub(crate) const STATE_KEY: &[u8; 5] = b"STATE";
+
+/// Key for storing the block context set by `begin_block`.
+const BLOCK_CONTEXT_KEY: &[u8; 13] = b"BLOCK_CONTEXT";
 
 impl<'env, I: IO + Copy, E: Env> Engine<'env, I, E> {
     pub fn new(
@@ -388,29 +498,50 @@ impl<'env, I: IO + Copy, E: Env> Engine<'env, I, E> {
         io: I,
         env: &'env E,
     ) -> Self {
+        let base_fee_per_gas = state.base_fee.base_fee_at(env.block_height());
+        #[cfg(feature = "evm_bully")]
+        let block_context = get_block_context_override(&io);
+        #[cfg(not(feature = "evm_bully"))]
+        let block_context = None;
         Self {
             state,
             origin,
             gas_price: U256::zero(),
+            base_fee_per_gas,
             current_account_id,
             io,
             env,
+            storage_cache: RefCell::new(BTreeMap::new()),
+            block_context,
+            storage_changes: StorageChanges::default(),
+            storage_staking_exempt: false,
         }
     }
 
+    /// Stops staking for the storage used by the transactions of this engine. For the calls the
+    /// engine makes itself (refunds, callbacks, token deployments and mints), whose origins are
+    /// system addresses which hold no ETH: the engine account pays for their storage.
+    pub fn exempt_from_storage_staking(&mut self) {
+        self.storage_staking_exempt = true;
+    }
+
     pub fn charge_gas(
         &mut self,
         sender: &Address,
         transaction: &NormalizedEthTransaction,
     ) -> Result<GasPaymentResult, GasPaymentError> {
-        if transaction.max_fee_per_gas.is_zero() {
+        let base_fee_per_gas = self.block_base_fee_per_gas();
+        if transaction.max_fee_per_gas.is_zero() && base_fee_per_gas.is_zero() {
             return Ok(GasPaymentResult::default());
         }
+        if transaction.max_fee_per_gas < base_fee_per_gas {
+            return Err(GasPaymentError::MaxFeeTooLow);
+        }
 
         let priority_fee_per_gas = transaction
             .max_priority_fee_per_gas
-            .min(transaction.max_fee_per_gas - self.block_base_fee_per_gas());
-        let effective_gas_price = priority_fee_per_gas + self.block_base_fee_per_gas();
+            .min(transaction.max_fee_per_gas - base_fee_per_gas);
+        let effective_gas_price = priority_fee_per_gas + base_fee_per_gas;
         let gas_limit = transaction.gas_limit;
         let prepaid_amount = gas_limit
             .checked_mul(effective_gas_price)
@@ -429,6 +560,7 @@ impl<'env, I: IO + Copy, E: Env> Engine<'env, I, E> {
             prepaid_amount,
             effective_gas_price,
             priority_fee_per_gas,
+            base_fee_per_gas,
         })
     }
 
@@ -451,6 +583,7 @@ impl<'env, I: IO + Copy, E: Env> Engine<'env, I, E> {
         access_list: Vec<(Address, Vec<H256>)>, // See EIP-2930
         handler: &mut P,
     ) -> EngineResult<SubmitResult> {
+        self.storage_changes = StorageChanges::default();
         let executor_params = StackExecutorParams::new(gas_limit, self.current_account_id.clone());
         let mut executor = executor_params.make_executor(self);
         let address = executor.create_address(CreateScheme::Legacy { caller: origin });
@@ -469,9 +602,16 @@ impl<'env, I: IO + Copy, E: Env> Engine<'env, I, E> {
         };
 
         let (values, logs) = executor.into_state().deconstruct();
-        let logs = filter_promises_from_logs(handler, logs);
 
         self.apply(values, Vec::<Log>::new(), true);
+        // The promises are only scheduled once nothing can fail anymore, but their storage
+        // changes must be staked for with the others.
+        let mut promises = PromiseBuffer::new(handler);
+        let logs =
+            filter_promises_from_logs(&mut self.io, &mut promises, logs, &mut self.storage_changes);
+        self.settle_storage_usage(&origin)
+            .map_err(|e| e.with_gas_used(used_gas))?;
+        promises.flush();
 
         Ok(SubmitResult::new(status, used_gas, logs))
     }
@@ -526,6 +666,7 @@ impl<'env, I: IO + Copy, E: Env> Engine<'env, I, E> {
         access_list: Vec<(Address, Vec<H256>)>, // See EIP-2930
         handler: &mut P,
     ) -> EngineResult<SubmitResult> {
+        self.storage_changes = StorageChanges::default();
         let executor_params = StackExecutorParams::new(gas_limit, self.current_account_id.clone());
         let mut executor = executor_params.make_executor(self);
         let (exit_reason, result) =
@@ -541,15 +682,57 @@ impl<'env, I: IO + Copy, E: Env> Engine<'env, I, E> {
         };
 
         let (values, logs) = executor.into_state().deconstruct();
-        let logs = filter_promises_from_logs(handler, logs);
 
         // There is no way to return the logs to the NEAR log method as it only
         // allows a return of UTF-8 strings.
         self.apply(values, Vec::<Log>::new(), true);
+        // The promises are only scheduled once nothing can fail anymore, but their storage
+        // changes must be staked for with the others.
+        let mut promises = PromiseBuffer::new(handler);
+        let logs =
+            filter_promises_from_logs(&mut self.io, &mut promises, logs, &mut self.storage_changes);
+        self.settle_storage_usage(&origin)
+            .map_err(|e| e.with_gas_used(used_gas))?;
+        promises.flush();
 
         Ok(SubmitResult::new(status, used_gas, logs))
     }
 
+    /// Stakes ETH from `payer` for the storage entries the transaction created or resized, and
+    /// refunds the previous stakes of the entries it changed. This must be the last write of
+    /// the transaction. On error the changes of the transaction are already applied: it is up
+    /// to the caller to revert them (the contract does so by panicking).
+    fn settle_storage_usage(&mut self, payer: &Address) -> Result<(), EngineErrorKind> {
+        let changes = mem::take(&mut self.storage_changes);
+        let payer = if self.storage_staking_exempt {
+            None
+        } else {
+            Some(payer)
+        };
+        storage_staking::settle(&mut self.io, payer, changes)
+            .map(|_| ())
+            .map_err(EngineErrorKind::StorageStaking)
+    }
+
+    /// Accounts for changing the entry at `key`, before it changes.
+    fn record_storage_change(&mut self, key: &[u8]) {
+        self.storage_changes.record(&self.io, key);
+    }
+
+    /// Accounts for removing the nonce, balance and code of an account and resetting its storage.
+    fn record_account_removal(&mut self, address: &Address) {
+        self.record_storage_change(&address_to_key(KeyPrefix::Nonce, address));
+        self.record_storage_change(&address_to_key(KeyPrefix::Balance, address));
+        self.record_storage_change(&address_to_key(KeyPrefix::Code, address));
+        self.record_generation_bump(address);
+    }
+
+    /// Accounts for bumping the storage generation of an account. The slots of the previous
+    /// generation cannot be removed, so they are not refunded.
+    fn record_generation_bump(&mut self, address: &Address) {
+        self.record_storage_change(&address_to_key(KeyPrefix::Generation, address));
+    }
+
     pub fn view_with_args(&self, args: ViewCallArgs) -> Result<TransactionStatus, EngineErrorKind> {
         let origin = Address::from_slice(&args.sender);
         let contract = Address::from_slice(&args.address);
@@ -565,13 +748,129 @@ impl<'env, I: IO + Copy, E: Env> Engine<'env, I, E> {
         input: Vec<u8>,
         gas_limit: u64,
     ) -> Result<TransactionStatus, EngineErrorKind> {
-        let executor_params = StackExecutorParams::new(gas_limit, self.current_account_id.clone());
+        let executor_params =
+            StackExecutorParams::new_view(gas_limit, self.current_account_id.clone());
         let mut executor = executor_params.make_executor(self);
         let (status, result) =
             executor.transact_call(origin, contract, value.raw(), input, gas_limit, Vec::new());
         status.into_result(result)
     }
 
+    /// Finds the minimal gas limit for which the call, or the deployment when `args.to` is
+    /// `None`, succeeds, by a binary search over non-applied executions. The search starts
+    /// from `args.gas_limit`, or the block gas limit, and stops after
+    /// `ESTIMATE_GAS_MAX_ITERATIONS` executions with the lowest limit found to succeed.
+    pub fn estimate_gas(
+        &self,
+        args: EstimateGasArgs,
+    ) -> Result<EstimateGasResult, EngineErrorKind> {
+        let origin = Address(args.sender);
+        let to = args.to.map(Address);
+        let value = Wei::new(U256::from_big_endian(&args.value));
+        let access_list: Vec<(Address, Vec<H256>)> = args
+            .access_list
+            .into_iter()
+            .map(|(address, keys)| (Address(address), keys.into_iter().map(H256).collect()))
+            .collect();
+        let intrinsic_gas = NormalizedEthTransaction {
+            address: Some(origin),
+            chain_id: None,
+            nonce: U256::zero(),
+            gas_limit: U256::zero(),
+            max_priority_fee_per_gas: U256::zero(),
+            max_fee_per_gas: U256::zero(),
+            to,
+            value,
+            data: args.data.clone(),
+            access_list: access_list
+                .iter()
+                .map(|(address, storage_keys)| AccessTuple {
+                    address: *address,
+                    storage_keys: storage_keys.clone(),
+                })
+                .collect(),
+        }
+        .intrinsic_gas(CONFIG)
+        .ok_or(EngineErrorKind::GasOverflow)?;
+        let simulate = |gas_limit| {
+            self.simulate(
+                origin,
+                to,
+                value,
+                args.data.clone(),
+                gas_limit,
+                access_list.clone(),
+            )
+        };
+
+        let upper_bound = match args.gas_limit {
+            Some(gas_limit) => gas_limit,
+            None => self.block_gas_limit().min(U256::from(u64::MAX)).low_u64(),
+        };
+        let (status, gas_used) = simulate(upper_bound)?;
+        if !status.is_ok() {
+            return Ok(EstimateGasResult {
+                gas: gas_used,
+                status,
+            });
+        }
+
+        // The gas used is net of the refund, so any lower limit fails. The limit which is
+        // needed can be higher: it has to cover the gas used before the refund (which is at
+        // most a fifth of it) and the 1/64 of the available gas kept by each call frame, which
+        // the optimistic guess accounts for at one level of calls.
+        let mut failing = gas_used.max(intrinsic_gas) - 1;
+        let mut succeeding = (upper_bound, status);
+        let optimistic = u128::from(gas_used) * 5 / 4 * 64 / 63;
+        let mut next = optimistic.min(u128::from(upper_bound)) as u64;
+        let mut iterations = 0;
+        while succeeding.0 - failing > 1 && iterations < ESTIMATE_GAS_MAX_ITERATIONS {
+            iterations += 1;
+            if next <= failing || next >= succeeding.0 {
+                next = failing + (succeeding.0 - failing) / 2;
+            }
+            match simulate(next) {
+                Ok((status, _)) if status.is_ok() => succeeding = (next, status),
+                _ => failing = next,
+            }
+            next = failing + (succeeding.0 - failing) / 2;
+        }
+
+        let (gas, status) = succeeding;
+        Ok(EstimateGasResult { gas, status })
+    }
+
+    /// Runs a call, or a deployment when `to` is `None`, without applying its changes.
+    /// Returns the status of the execution and the gas it used.
+    fn simulate(
+        &self,
+        origin: Address,
+        to: Option<Address>,
+        value: Wei,
+        input: Vec<u8>,
+        gas_limit: u64,
+        access_list: Vec<(Address, Vec<H256>)>,
+    ) -> Result<(TransactionStatus, u64), EngineErrorKind> {
+        let executor_params =
+            StackExecutorParams::new_view(gas_limit, self.current_account_id.clone());
+        let mut executor = executor_params.make_executor(self);
+        let (exit_reason, data) = match to {
+            Some(contract) => {
+                executor.transact_call(origin, contract, value.raw(), input, gas_limit, access_list)
+            }
+            None => {
+                let address = executor.create_address(CreateScheme::Legacy { caller: origin });
+                let exit_reason =
+                    executor.transact_create(origin, value.raw(), input, gas_limit, access_list);
+                (exit_reason, address.0.to_vec())
+            }
+        };
+        let used_gas = executor.used_gas();
+        exit_reason
+            .into_result(data)
+            .map(|status| (status, used_gas))
+    }
+
     fn relayer_key(account_id: &[u8]) -> Vec<u8> {
         bytes_to_key(KeyPrefix::RelayerEvmAddressMap, account_id)
     }
@@ -759,6 +1058,8 @@ impl<'env, I: IO + Copy, E: Env> Engine<'env, I, E> {
                         kind: EngineErrorKind::EvmError(ExitError::CallTooDeep),
                         gas_used: submit_result.gas_used,
                     }),
+                    // Only `submit` queues transactions.
+                    TransactionStatus::Queued => unreachable!(),
                 }
             }),
             output_on_fail,
@@ -806,6 +1107,508 @@ pub fn compute_block_hash(chain_id: [u8; 32], block_height: u64, account_id: &[u
     sdk::sha256(&data)
 }
 
+fn block_hash_key(block_height: u64) -> Vec<u8> {
+    let index = block_height % BLOCK_HASH_HISTORY;
+    bytes_to_key(KeyPrefix::BlockHash, &index.to_le_bytes())
+}
+
+/// Stores the hash of a block in the ring buffer of recent block hashes, in place of the
+/// block `BLOCK_HASH_HISTORY` heights before it. Only the hashes which cannot be derived with
+/// `compute_block_hash` are stored, i.e. the ones given to `begin_block`.
+pub fn set_block_hash<I: IO>(io: &mut I, block_height: u64, block_hash: &H256) {
+    let mut value = [0u8; BLOCK_HEIGHT_SIZE + 32];
+    value[..BLOCK_HEIGHT_SIZE].copy_from_slice(&block_height.to_le_bytes());
+    value[BLOCK_HEIGHT_SIZE..].copy_from_slice(block_hash.as_bytes());
+    io.write_storage(&block_hash_key(block_height), &value);
+}
+
+/// Returns the hash of the block at `block_height` if it is in the ring buffer of recent
+/// block hashes, i.e. it was recorded and not replaced since.
+pub fn get_block_hash<I: IO>(io: &I, block_height: u64) -> Option<H256> {
+    let value = io.read_storage(&block_hash_key(block_height))?.to_vec();
+    if value.len() != BLOCK_HEIGHT_SIZE + 32
+        || value[..BLOCK_HEIGHT_SIZE] != block_height.to_le_bytes()
+    {
+        return None;
+    }
+    Some(H256::from_slice(&value[BLOCK_HEIGHT_SIZE..]))
+}
+
+/// Block context of a benchmark chain, used instead of the NEAR one (see `begin_block`).
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct BlockContextOverride {
+    pub coinbase: RawAddress,
+    /// Timestamp in seconds since the Unix epoch.
+    pub timestamp: RawU256,
+    pub number: RawU256,
+    pub difficulty: RawU256,
+    pub gas_limit: RawU256,
+}
+
+#[cfg(feature = "evm_bully")]
+impl From<&BeginBlockArgs> for BlockContextOverride {
+    fn from(args: &BeginBlockArgs) -> Self {
+        Self {
+            coinbase: args.coinbase,
+            timestamp: args.timestamp,
+            number: args.number,
+            difficulty: args.difficulty,
+            gas_limit: args.gaslimit,
+        }
+    }
+}
+
+pub fn get_block_context_override<I: IO>(io: &I) -> Option<BlockContextOverride> {
+    io.read_storage(&bytes_to_key(KeyPrefix::Config, BLOCK_CONTEXT_KEY))
+        .and_then(|bytes| BlockContextOverride::try_from_slice(&bytes.to_vec()).ok())
+}
+
+pub fn set_block_context_override<I: IO>(io: &mut I, block_context: &BlockContextOverride) {
+    io.write_storage(
+        &bytes_to_key(KeyPrefix::Config, BLOCK_CONTEXT_KEY),
+        &block_context.try_to_vec().expect("ERR_SER"),
+    );
+}
+
+/// Starts a new block on a benchmark chain: the given block context is used by the
+/// following transactions and the hash of the block is added to the recent block hashes.
+#[cfg(feature = "evm_bully")]
+pub fn begin_block<I: IO>(io: &mut I, args: &BeginBlockArgs) {
+    let block_context = BlockContextOverride::from(args);
+    let block_height = U256::from(block_context.number).low_u64();
+    set_block_context_override(io, &block_context);
+    set_block_hash(io, block_height, &H256(args.hash));
+}
+
+/// Validates and executes a signed Ethereum transaction (the input of the `submit` method),
+/// charging the sender for gas and rewarding `relayer_address` with the priority fee.
+/// If the transaction queue is enabled, a transaction with a future nonce is queued instead,
+/// and the queued transactions of the sender which are next in line are executed, up to
+/// `transaction_queue::MAX_QUEUED_EXECUTIONS` of them.
+#[allow(clippy::too_many_arguments)]
+pub fn submit<I: IO + Copy, E: Env, P: PromiseHandler>(
+    io: I,
+    env: &E,
+    transaction_bytes: &[u8],
+    state: EngineState,
+    current_account_id: AccountId,
+    relayer_address: Address,
+    handler: &mut P,
+) -> EngineResult<SubmitResult> {
+    let (sender, result) = submit_transaction(
+        io,
+        env,
+        transaction_bytes,
+        state.clone(),
+        current_account_id.clone(),
+        relayer_address,
+        handler,
+    )?;
+    submit_queued_transactions(
+        io,
+        env,
+        &sender,
+        state,
+        current_account_id,
+        relayer_address,
+        handler,
+    );
+    Ok(result)
+}
+
+/// Executes the queued transactions of `sender` as long as the next one is queued, up to
+/// `transaction_queue::MAX_QUEUED_EXECUTIONS` of them. A queued transaction which fails is
+/// dropped without effect, on the storage or promises, leaving a nonce gap again.
+#[allow(clippy::too_many_arguments)]
+fn submit_queued_transactions<I: IO + Copy, E: Env, P: PromiseHandler>(
+    mut io: I,
+    env: &E,
+    sender: &Address,
+    state: EngineState,
+    current_account_id: AccountId,
+    relayer_address: Address,
+    handler: &mut P,
+) {
+    let buffer = OverlayBuffer::default();
+    for _ in 0..transaction_queue::MAX_QUEUED_EXECUTIONS {
+        let account_nonce = get_nonce(&io, sender);
+        let mut storage_changes = StorageChanges::default();
+        let queued = transaction_queue::take_next(
+            &mut io,
+            sender,
+            &account_nonce,
+            env.block_height(),
+            &mut storage_changes,
+        );
+        // Removing entries only refunds stakes, so it fails only if a balance overflows.
+        let _ = storage_staking::settle(&mut io, Some(sender), storage_changes);
+        let queued = match queued {
+            Some(queued) => queued,
+            None => break,
+        };
+        let overlay = OverlayIO::new(io, &buffer);
+        let mut promises = PromiseBuffer::new(&mut *handler);
+        let result = submit_transaction(
+            overlay,
+            env,
+            &queued.transaction,
+            state.clone(),
+            current_account_id.clone(),
+            relayer_address,
+            &mut promises,
+        );
+        match result {
+            Ok(_) => {
+                overlay.commit();
+                promises.flush();
+            }
+            Err(_) => overlay.discard(),
+        }
+    }
+}
+
+/// Executes a transaction for `submit`, returning its sender along with the result.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn submit_transaction<I: IO + Copy, E: Env, P: PromiseHandler>(
+    mut io: I,
+    env: &E,
+    transaction_bytes: &[u8],
+    state: EngineState,
+    current_account_id: AccountId,
+    relayer_address: Address,
+    handler: &mut P,
+) -> EngineResult<(Address, SubmitResult)> {
+    let transaction: NormalizedEthTransaction = EthTransactionKind::try_from(transaction_bytes)
+        .map_err(EngineErrorKind::FailedTransactionParse)?
+        .into();
+    let transaction_hash = sdk::keccak(transaction_bytes);
+
+    // Validate the chain ID, if provided inside the signature:
+    if let Some(chain_id) = transaction.chain_id {
+        if U256::from(chain_id) != U256::from(state.chain_id) {
+            return Err(EngineErrorKind::InvalidChainId.into());
+        }
+    }
+
+    // Retrieve the signer of the transaction:
+    let sender = transaction
+        .address
+        .ok_or(EngineErrorKind::InvalidSignature)?;
+
+    #[cfg(feature = "log")]
+    sdk::log(crate::prelude::format!("signer_address {:?}", sender).as_str());
+
+    // Check intrinsic gas is covered by transaction gas limit
+    match transaction.intrinsic_gas(CONFIG) {
+        None => return Err(EngineErrorKind::GasOverflow.into()),
+        Some(intrinsic_gas) => {
+            if transaction.gas_limit < intrinsic_gas.into() {
+                return Err(EngineErrorKind::IntrinsicGasNotMet.into());
+            }
+        }
+    }
+
+    if transaction.max_priority_fee_per_gas > transaction.max_fee_per_gas {
+        return Err(EngineErrorKind::MaxPriorityGasFeeTooLarge.into());
+    }
+
+    check_min_gas_price(&io, env, &state, &sender, &transaction)?;
+
+    if transaction.nonce > get_nonce(&io, &sender) && transaction_queue::get_limit(&io) > 0 {
+        queue_transaction(
+            &mut io,
+            env.block_height(),
+            &sender,
+            &transaction,
+            transaction_bytes,
+        )?;
+        let result = SubmitResult::new(TransactionStatus::Queued, 0, Vec::new());
+        return Ok((sender, result));
+    }
+
+    check_nonce(&io, &sender, &transaction.nonce)?;
+
+    // Figure out what kind of a transaction this is, and execute it:
+    let fee_policy = state.fee_policy.clone();
+    let engine_address = current_address(&current_account_id);
+    let mut engine = Engine::new_with_state(state, sender, current_account_id, io, env);
+    let prepaid_amount = match engine.charge_gas(&sender, &transaction) {
+        Ok(gas_result) => gas_result,
+        Err(GasPaymentError::OutOfFund) => {
+            increment_nonce(&mut io, &sender);
+            let result = SubmitResult::new(TransactionStatus::OutOfFund, 0, Vec::new());
+            let receipt = TransactionReceipt::new(
+                transaction_hash,
+                sender,
+                transaction.to,
+                &result,
+                U256::zero(),
+                env.block_height(),
+            );
+            record_receipt(&mut io, &receipt, &result.logs);
+            return Ok((sender, result));
+        }
+        Err(err) => return Err(EngineErrorKind::GasPayment(err).into()),
+    };
+    let effective_gas_price = prepaid_amount.effective_gas_price;
+    let receiver = transaction.to;
+    let gas_limit: u64 = transaction
+        .gas_limit
+        .try_into()
+        .map_err(|_| EngineErrorKind::GasOverflow)?;
+    let access_list = transaction
+        .access_list
+        .into_iter()
+        .map(|a| (a.address, a.storage_keys))
+        .collect();
+    let result = if let Some(receiver) = receiver {
+        engine.call(
+            sender,
+            receiver,
+            transaction.value,
+            transaction.data,
+            gas_limit,
+            access_list,
+            handler,
+        )
+    } else {
+        // Execute a contract deployment:
+        engine.deploy_code(
+            sender,
+            transaction.value,
+            transaction.data,
+            gas_limit,
+            access_list,
+            handler,
+        )
+    };
+
+    // Give refund
+    let gas_used = match &result {
+        Ok(submit_result) => submit_result.gas_used,
+        Err(engine_err) => engine_err.gas_used,
+    };
+    let refund_result = refund_unused_gas(
+        &mut io,
+        &sender,
+        gas_used,
+        prepaid_amount,
+        &relayer_address,
+        &fee_policy,
+    )
+    .map_err(|e| EngineErrorKind::GasPayment(e).with_gas_used(gas_used))?;
+    base_fee::record_gas_used(&mut io, env.block_height(), gas_used);
+
+    let result = result.map(|mut submit_result| {
+        if fee_policy.is_split() && !effective_gas_price.is_zero() {
+            submit_result.logs.push(fee_policy::fee_distributed_log(
+                &engine_address,
+                &relayer_address,
+                &fee_policy.treasury(),
+                refund_result.relayer_reward,
+                refund_result.treasury_reward,
+                refund_result.burned,
+            ));
+        }
+        submit_result
+    });
+
+    if let Ok(submit_result) = &result {
+        let receipt = TransactionReceipt::new(
+            transaction_hash,
+            sender,
+            receiver,
+            submit_result,
+            effective_gas_price,
+            env.block_height(),
+        );
+        record_receipt(&mut io, &receipt, &submit_result.logs);
+    }
+
+    result.map(|submit_result| (sender, submit_result))
+}
+
+/// Checks that the gas price the transaction would pay is not below the minimum of the fee
+/// policy, unless the transaction is exempt.
+fn check_min_gas_price<I: IO, E: Env>(
+    io: &I,
+    env: &E,
+    state: &EngineState,
+    sender: &Address,
+    transaction: &NormalizedEthTransaction,
+) -> Result<(), EngineErrorKind> {
+    let min_gas_price = U256::from(state.fee_policy.min_gas_price);
+    if min_gas_price.is_zero() {
+        return Ok(());
+    }
+    let base_fee_per_gas = state.base_fee.base_fee_at(env.block_height());
+    let gas_price = transaction.max_fee_per_gas.min(
+        transaction
+            .max_priority_fee_per_gas
+            .saturating_add(base_fee_per_gas),
+    );
+    if gas_price >= min_gas_price
+        || fee_policy::get_gas_price_exemptions(io).is_exempt(sender, &env.predecessor_account_id())
+    {
+        return Ok(());
+    }
+    Err(EngineErrorKind::GasPayment(GasPaymentError::GasPriceTooLow))
+}
+
+/// Adds a transaction with a future nonce to the queue of its sender. The sender stakes for
+/// the storage of the queue entry, and must be able to pay for all its queued transactions at
+/// their maximum fee, so the queue cannot be filled for free.
+fn queue_transaction<I: IO>(
+    io: &mut I,
+    block_height: u64,
+    sender: &Address,
+    transaction: &NormalizedEthTransaction,
+    transaction_bytes: &[u8],
+) -> EngineResult<()> {
+    let max_cost = transaction
+        .gas_limit
+        .checked_mul(transaction.max_fee_per_gas)
+        .and_then(|gas_cost| gas_cost.checked_add(transaction.value.raw()))
+        .ok_or(EngineErrorKind::GasPayment(
+            GasPaymentError::EthAmountOverflow,
+        ))?;
+    let mut storage_changes = StorageChanges::default();
+    transaction_queue::enqueue(
+        io,
+        sender,
+        block_height,
+        &transaction.nonce,
+        &transaction.max_fee_per_gas,
+        &transaction.max_priority_fee_per_gas,
+        &max_cost,
+        transaction_bytes,
+        &mut storage_changes,
+    )
+    .map_err(EngineErrorKind::TransactionQueue)?;
+    storage_staking::settle(io, Some(sender), storage_changes)
+        .map_err(EngineErrorKind::StorageStaking)?;
+
+    // What is left after staking must cover all the queued transactions of the sender,
+    // including the one just queued.
+    let queued_cost = transaction_queue::get_queued_cost(io, sender).ok_or(
+        EngineErrorKind::GasPayment(GasPaymentError::EthAmountOverflow),
+    )?;
+    if get_balance(io, sender).raw() < queued_cost {
+        return Err(EngineErrorKind::GasPayment(GasPaymentError::OutOfFund).into());
+    }
+    Ok(())
+}
+
+fn transaction_receipt_key(transaction_hash: &H256) -> Vec<u8> {
+    bytes_to_key(KeyPrefix::Receipt, transaction_hash.as_bytes())
+}
+
+/// Receipt of a transaction processed by `submit`, given the keccak hash of its bytes. The
+/// receipts are kept for `logs_index::RETENTION_BLOCKS` blocks.
+pub fn get_transaction_receipt<I: IO>(
+    io: &I,
+    transaction_hash: &H256,
+) -> Option<TransactionReceipt> {
+    io.read_storage(&transaction_receipt_key(transaction_hash))
+        .and_then(|bytes| TransactionReceipt::try_from_slice(&bytes.to_vec()).ok())
+}
+
+pub fn set_transaction_receipt<I: IO>(io: &mut I, receipt: &TransactionReceipt) {
+    io.write_storage(
+        &transaction_receipt_key(&H256(receipt.transaction_hash)),
+        &receipt.try_to_vec().expect("ERR_SER"),
+    );
+}
+
+pub fn remove_transaction_receipt<I: IO>(io: &mut I, transaction_hash: &H256) {
+    io.remove_storage(&transaction_receipt_key(transaction_hash));
+}
+
+/// Stores the receipt of a transaction and adds the transaction to the logs index, which
+/// removes the receipt once the block is no longer kept (see `logs_index`).
+fn record_receipt<I: IO>(io: &mut I, receipt: &TransactionReceipt, logs: &[ResultLog]) {
+    set_transaction_receipt(io, receipt);
+    logs_index::record_transaction(
+        io,
+        receipt.block_height,
+        &H256(receipt.transaction_hash),
+        logs,
+    );
+}
+
+impl TransactionReceipt {
+    fn new(
+        transaction_hash: H256,
+        from: Address,
+        to: Option<Address>,
+        result: &SubmitResult,
+        effective_gas_price: U256,
+        block_height: u64,
+    ) -> Self {
+        // The output of a successful deployment is the address of the created contract.
+        let contract_address = match (&to, &result.status) {
+            (None, TransactionStatus::Succeed(output)) if output.len() == 20 => {
+                Some(Address::from_slice(output).0)
+            }
+            _ => None,
+        };
+        Self {
+            transaction_hash: transaction_hash.0,
+            from: from.0,
+            to: to.map(|address| address.0),
+            status: result.status.is_ok(),
+            gas_used: result.gas_used,
+            effective_gas_price: u256_to_arr(&effective_gas_price),
+            contract_address,
+            logs_bloom: Bloom::from_logs(&result.logs),
+            block_height,
+        }
+    }
+}
+
+/// Executes the given transactions in order, each of them as `submit` does. The changes of a
+/// transaction which fails, to the storage as well as its promises, are discarded (as a failing
+/// `submit` is reverted), and the next transactions are executed regardless.
+#[allow(clippy::too_many_arguments)]
+pub fn submit_batch<I: IO + Copy, E: Env, P: PromiseHandler>(
+    io: I,
+    env: &E,
+    transactions: &[Vec<u8>],
+    state: EngineState,
+    current_account_id: AccountId,
+    relayer_address: Address,
+    handler: &mut P,
+) -> Vec<BatchTransactionResult> {
+    let buffer = OverlayBuffer::default();
+    transactions
+        .iter()
+        .map(|transaction_bytes| {
+            let overlay = OverlayIO::new(io, &buffer);
+            let mut promises = PromiseBuffer::new(&mut *handler);
+            let result = submit(
+                overlay,
+                env,
+                transaction_bytes,
+                state.clone(),
+                current_account_id.clone(),
+                relayer_address,
+                &mut promises,
+            );
+            match result {
+                Ok(submit_result) => {
+                    overlay.commit();
+                    promises.flush();
+                    BatchTransactionResult::Executed(submit_result)
+                }
+                Err(e) => {
+                    overlay.discard();
+                    BatchTransactionResult::Failed(e.kind.to_str().to_string())
+                }
+            }
+        })
+        .collect()
+}
+
 pub fn get_state<I: IO>(io: &I) -> Result<EngineState, EngineStateError> {
     match io.read_storage(&bytes_to_key(KeyPrefix::Config, STATE_KEY)) {
         None => Err(EngineStateError::NotFound),
@@ -822,15 +1625,18 @@ pub fn set_state<I: IO>(io: &mut I, state: EngineState) {
     );
 }
 
-pub fn refund_unused_gas<I: IO>(
+/// Settles the gas prepaid by `sender`: the unused gas is refunded, the priority fee goes to
+/// the relayer and the base fee is burned.
+pub fn refund_unused_gas<I: IO + Copy>(
     io: &mut I,
     sender: &Address,
     gas_used: u64,
     gas_result: GasPaymentResult,
     relayer: &Address,
-) -> Result<(), GasPaymentError> {
+    fee_policy: &FeePolicy,
+) -> Result<GasRefundResult, GasPaymentError> {
     if gas_result.effective_gas_price.is_zero() {
-        return Ok(());
+        return Ok(GasRefundResult::default());
     }
 
     let gas_to_wei = |price: U256| {
@@ -841,7 +1647,10 @@ pub fn refund_unused_gas<I: IO>(
     };
 
     let spent_amount = gas_to_wei(gas_result.effective_gas_price)?;
-    let reward_amount = gas_to_wei(gas_result.priority_fee_per_gas)?;
+    let reward = fee_policy.split(gas_to_wei(gas_result.priority_fee_per_gas)?);
+    let burned_amount = gas_to_wei(gas_result.base_fee_per_gas)?
+        .checked_add(reward.burned)
+        .ok_or(GasPaymentError::EthAmountOverflow)?;
 
     let refund = gas_result
         .prepaid_amount
@@ -849,9 +1658,21 @@ pub fn refund_unused_gas<I: IO>(
         .ok_or(GasPaymentError::EthAmountOverflow)?;
 
     add_balance(io, sender, refund)?;
-    add_balance(io, relayer, reward_amount)?;
+    add_balance(io, relayer, reward.relayer)?;
+    if !reward.treasury.is_zero() {
+        add_balance(io, &fee_policy.treasury(), reward.treasury)?;
+    }
+    if !burned_amount.is_zero() {
+        // The fee was already taken from the sender, only the supply is left to update.
+        EthConnectorContract::get_instance(*io).internal_burn_eth_supply(&burned_amount.raw());
+    }
 
-    Ok(())
+    Ok(GasRefundResult {
+        refunded: refund,
+        relayer_reward: reward.relayer,
+        treasury_reward: reward.treasury,
+        burned: burned_amount,
+    })
 }
 
 pub fn set_code<I: IO>(io: &mut I, address: &Address, code: &[u8]) {
@@ -1032,26 +1853,60 @@ fn remove_account<I: IO + Copy>(io: &mut I, address: &Address, generation: u32)
     remove_all_storage(io, address, generation);
 }
 
-fn filter_promises_from_logs<T, P>(handler: &mut P, logs: T) -> Vec<ResultLog>
+/// Schedules the promises logged by the precompiles, and returns the other logs. The exits to
+/// NEAR get an ID, whose `ExitScheduled` log replaces the promise log.
+fn filter_promises_from_logs<I, T, P>(
+    io: &mut I,
+    handler: &mut P,
+    logs: T,
+    storage_changes: &mut StorageChanges,
+) -> Vec<ResultLog>
 where
+    I: IO,
     T: IntoIterator<Item = Log>,
     P: PromiseHandler,
 {
     logs.into_iter()
         .filter_map(|log| {
-            if log.address == ExitToNear::ADDRESS || log.address == ExitToEthereum::ADDRESS {
+            if log.address == ExitToNear::ADDRESS
+                || log.address == ExitToEthereum::ADDRESS
+                || log.address == CrossContractCall::ADDRESS
+            {
                 if log.topics.is_empty() {
-                    if let Ok(promise) = PromiseArgs::try_from_slice(&log.data) {
+                    let mut exit_log = None;
+                    let promise = if log.address == CrossContractCall::ADDRESS {
+                        CrossContractCallLog::try_from_slice(&log.data)
+                            .ok()
+                            .map(|xcc_log| {
+                                let caller = Address(xcc_log.caller);
+                                cross_contract_call::consume_allowance(
+                                    io,
+                                    &caller,
+                                    &xcc_log.promise,
+                                );
+                                xcc_log.promise
+                            })
+                    } else {
+                        PromiseArgs::try_from_slice(&log.data).ok()
+                    };
+                    if let Some(promise) = promise {
                         match promise {
                             PromiseArgs::Create(promise) => schedule_promise(handler, &promise),
-                            PromiseArgs::Callback(promise) => {
+                            PromiseArgs::Callback(mut promise) => {
+                                if log.address == ExitToNear::ADDRESS {
+                                    exit_log = exit_status::schedule_exit(
+                                        io,
+                                        &mut promise,
+                                        storage_changes,
+                                    );
+                                }
                                 let base_id = schedule_promise(handler, &promise.base);
                                 schedule_promise_callback(handler, base_id, &promise.callback)
                             }
                         };
                     }
                     // do not pass on these "internal logs" to caller
-                    None
+                    exit_log
                 } else {
                     // The exit precompiles do produce externally consumable logs in
                     // addition to the promises. The external logs have a non-empty
@@ -1100,38 +1955,37 @@ impl<'env, I: IO + Copy, E: Env> evm::backend::Backend for Engine<'env, I, E> {
 
     /// Returns a block hash from a given index.
     ///
-    /// Currently, this returns
-    /// 0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff if
-    /// only for the 256 most recent blocks, excluding of the current one.
-    /// Otherwise, it returns 0x0.
-    ///
-    /// In other words, if the requested block index is less than the current
-    /// block index, return
-    /// 0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff.
-    /// Otherwise, return 0.
-    ///
-    /// This functionality may change in the future. Follow
-    /// [nearcore#3456](https://github.com/near/nearcore/issues/3456) for more
-    /// details.
+    /// Only the 256 most recent blocks, excluding the current one, have a hash;
+    /// otherwise 0x0 is returned. The hash is derived with `compute_block_hash`, unless
+    /// it was recorded in the ring buffer of recent block hashes (see `set_block_hash`).
     ///
     /// See: https://doc.aurora.dev/develop/compat/evm#blockhash
     fn block_hash(&self, number: U256) -> H256 {
-        let idx = U256::from(self.env.block_height());
-        if idx.saturating_sub(U256::from(256)) <= number && number < idx {
-            // since `idx` comes from `u64` it is always safe to downcast `number` from `U256`
-            compute_block_hash(
-                self.state.chain_id,
-                number.low_u64(),
-                self.current_account_id.as_bytes(),
-            )
+        let idx = self.block_number();
+        if idx.saturating_sub(U256::from(BLOCK_HASH_HISTORY)) <= number && number < idx {
+            // `number` is below the block number, which fits in a `u64` (see `block_number`)
+            let block_height = number.low_u64();
+            get_block_hash(&self.io, block_height).unwrap_or_else(|| {
+                compute_block_hash(
+                    self.state.chain_id,
+                    block_height,
+                    self.current_account_id.as_bytes(),
+                )
+            })
         } else {
             H256::zero()
         }
     }
 
     /// Returns the current block index number.
+    ///
+    /// The NEAR block height, unless overridden by `begin_block` (in which case it is
+    /// truncated to a `u64`).
     fn block_number(&self) -> U256 {
-        U256::from(self.env.block_height())
+        match &self.block_context {
+            Some(block_context) => U256::from(U256::from(block_context.number).low_u64()),
+            None => U256::from(self.env.block_height()),
+        }
     }
 
     /// Returns a mocked coinbase which is the EVM address for the Aurora
@@ -1139,6 +1993,9 @@ impl<'env, I: IO + Copy, E: Env> evm::backend::Backend for Engine<'env, I, E> {
     ///
     /// See: https://doc.aurora.dev/develop/compat/evm#coinbase
     fn block_coinbase(&self) -> Address {
+        if let Some(block_context) = &self.block_context {
+            return Address(block_context.coinbase);
+        }
         Address([
             0x44, 0x44, 0x58, 0x84, 0x43, 0xC3, 0xa9, 0x12, 0x88, 0xc5, 0x00, 0x24, 0x83, 0x44,
             0x9A, 0xba, 0x10, 0x54, 0x19, 0x2b,
@@ -1147,14 +2004,20 @@ impl<'env, I: IO + Copy, E: Env> evm::backend::Backend for Engine<'env, I, E> {
 
     /// Returns the current block timestamp.
     fn block_timestamp(&self) -> U256 {
-        U256::from(self.env.block_timestamp().secs())
+        match &self.block_context {
+            Some(block_context) => U256::from(block_context.timestamp),
+            None => U256::from(self.env.block_timestamp().secs()),
+        }
     }
 
     /// Returns the current block difficulty.
     ///
     /// See: https://doc.aurora.dev/develop/compat/evm#difficulty
     fn block_difficulty(&self) -> U256 {
-        U256::zero()
+        match &self.block_context {
+            Some(block_context) => U256::from(block_context.difficulty),
+            None => U256::zero(),
+        }
     }
 
     /// Returns the current block gas limit.
@@ -1165,17 +2028,18 @@ impl<'env, I: IO + Copy, E: Env> evm::backend::Backend for Engine<'env, I, E> {
     ///
     /// See: https://doc.aurora.dev/develop/compat/evm#gaslimit
     fn block_gas_limit(&self) -> U256 {
-        U256::max_value()
+        match &self.block_context {
+            Some(block_context) => U256::from(block_context.gas_limit),
+            None => U256::max_value(),
+        }
     }
 
-    /// Returns the current base fee for the current block.
+    /// Returns the base fee of the current block.
     ///
-    /// Currently, this returns 0 as there is no concept of a base fee at this
-    /// time but this may change in the future.
-    ///
-    /// TODO: doc.aurora.dev link
+    /// It is zero unless configured by the owner (see `set_base_fee`), either as a fixed
+    /// value or following the EIP-1559 update rule.
     fn block_base_fee_per_gas(&self) -> U256 {
-        U256::zero()
+        self.base_fee_per_gas
     }
 
     /// Returns the states chain ID.
@@ -1203,15 +2067,25 @@ impl<'env, I: IO + Copy, E: Env> evm::backend::Backend for Engine<'env, I, E> {
 
     /// Get storage value of address at index.
     fn storage(&self, address: Address, index: H256) -> H256 {
+        if let Some(value) = self.storage_cache.borrow().get(&(address, index)) {
+            return *value;
+        }
         let generation = get_generation(&self.io, &address);
-        get_storage(&self.io, &address, &index, generation)
+        let value = get_storage(&self.io, &address, &index, generation);
+        self.storage_cache
+            .borrow_mut()
+            .insert((address, index), value);
+        value
     }
 
-    /// Get original storage value of address at index, if available.
+    /// Get original storage value of address at index, that is its value at the start of
+    /// the current transaction.
     ///
-    /// Currently, this returns `None` for now.
-    fn original_storage(&self, _address: Address, _index: H256) -> Option<H256> {
-        None
+    /// Changes made by the transaction are only kept in the executor until the end of the
+    /// transaction, so this is the value in storage. It is needed for the SSTORE gas cost
+    /// and refunds of EIP-2200 / EIP-3529.
+    fn original_storage(&self, address: Address, index: H256) -> Option<H256> {
+        Some(self.storage(address, index))
     }
 }
 
@@ -1222,6 +2096,9 @@ impl<'env, J: IO + Copy, E: Env> ApplyBackend for Engine<'env, J, E> {
         I: IntoIterator<Item = (H256, H256)>,
         L: IntoIterator<Item = Log>,
     {
+        // The cached values are about to be outdated.
+        self.storage_cache.borrow_mut().clear();
+
         let mut writes_counter: usize = 0;
         let mut code_bytes_written: usize = 0;
         for apply in values {
@@ -1234,11 +2111,14 @@ impl<'env, J: IO + Copy, E: Env> ApplyBackend for Engine<'env, J, E> {
                     reset_storage,
                 } => {
                     let generation = get_generation(&self.io, &address);
+                    self.record_storage_change(&address_to_key(KeyPrefix::Nonce, &address));
+                    self.record_storage_change(&address_to_key(KeyPrefix::Balance, &address));
                     set_nonce(&mut self.io, &address, &basic.nonce);
                     set_balance(&mut self.io, &address, &Wei::new(basic.balance));
                     writes_counter += 2; // 1 for nonce, 1 for balance
 
                     if let Some(code) = code {
+                        self.record_storage_change(&address_to_key(KeyPrefix::Code, &address));
                         set_code(&mut self.io, &address, &code);
                         code_bytes_written = code.len();
                         sdk::log!(crate::prelude::format!(
@@ -1250,6 +2130,7 @@ impl<'env, J: IO + Copy, E: Env> ApplyBackend for Engine<'env, J, E> {
                     }
 
                     let next_generation = if reset_storage {
+                        self.record_generation_bump(&address);
                         remove_all_storage(&mut self.io, &address, generation);
                         generation + 1
                     } else {
@@ -1257,6 +2138,8 @@ impl<'env, J: IO + Copy, E: Env> ApplyBackend for Engine<'env, J, E> {
                     };
 
                     for (index, value) in storage {
+                        let key = storage_to_key(&address, &index, next_generation);
+                        self.record_storage_change(key.as_ref());
                         if value == H256::default() {
                             remove_storage(&mut self.io, &address, &index, next_generation)
                         } else {
@@ -1274,12 +2157,14 @@ impl<'env, J: IO + Copy, E: Env> ApplyBackend for Engine<'env, J, E> {
                         && is_account_empty(&self.io, &address)
                         && generation == next_generation
                     {
+                        self.record_account_removal(&address);
                         remove_account(&mut self.io, &address, generation);
                         writes_counter += 1;
                     }
                 }
                 Apply::Delete { address } => {
                     let generation = get_generation(&self.io, &address);
+                    self.record_account_removal(&address);
                     remove_account(&mut self.io, &address, generation);
                     writes_counter += 1;
                 }
@@ -0,0 +1,116 @@
+//! Status of the exits to NEAR, which transfer their tokens asynchronously.
+//!
+//! When it schedules the transfer of an exit, the engine gives it an ID, logged in an
+//! `ExitScheduled` event, and records it as pending. The `refund_on_error` callback of the
+//! transfer then records it as completed, or as refunded if any of the tokens came back to the
+//! engine, in which case it also logs a `Refunded` event.
+//!
+//! The last exit ID is stored under the counter key, and each status under its exit ID.
+
+use crate::parameters::ResultLog;
+use crate::prelude::parameters::{PromiseWithCallbackArgs, RefundCallArgs};
+use crate::prelude::precompiles::native::{events, ExitToNear, REFUND_ON_ERROR_METHOD};
+use crate::prelude::storage::{bytes_to_key, KeyPrefix};
+use crate::prelude::{Address, BorshDeserialize, BorshSerialize, Vec, U256};
+use crate::storage_staking::StorageChanges;
+use aurora_engine_sdk::io::{StorageIntermediate, IO};
+
+/// Sub-prefix of the key of the last exit ID.
+const EXIT_COUNTER_ID: u8 = 0x0;
+/// Sub-prefix of the keys of the exit statuses.
+const EXIT_STATUS_ID: u8 = 0x1;
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitStatus {
+    /// The transfer is scheduled and its callback did not run yet.
+    Pending,
+    /// The tokens were transferred.
+    Completed,
+    /// The transfer failed, at least in part, and the sender was refunded.
+    Refunded,
+}
+
+fn counter_key() -> Vec<u8> {
+    bytes_to_key(KeyPrefix::ExitStatus, &[EXIT_COUNTER_ID])
+}
+
+fn status_key(exit_id: u64) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(9);
+    bytes.push(EXIT_STATUS_ID);
+    bytes.extend_from_slice(&exit_id.to_be_bytes());
+    bytes_to_key(KeyPrefix::ExitStatus, &bytes)
+}
+
+/// Allocates the ID of a new exit. IDs start at 1.
+fn next_exit_id<I: IO>(io: &mut I, storage_changes: &mut StorageChanges) -> u64 {
+    let key = counter_key();
+    storage_changes.record(io, &key);
+    let exit_id = io
+        .read_u64(&key)
+        .unwrap_or(0)
+        .checked_add(1)
+        .expect("ERR_EXIT_ID_OVERFLOW");
+    io.write_storage(&key, &exit_id.to_le_bytes());
+    exit_id
+}
+
+pub fn get_status<I: IO>(io: &I, exit_id: u64) -> Option<ExitStatus> {
+    io.read_storage(&status_key(exit_id))
+        .and_then(|bytes| ExitStatus::try_from_slice(&bytes.to_vec()).ok())
+}
+
+/// Records the status of an exit. The exits scheduled before the exits had an ID, whose ID
+/// is 0, have no status.
+pub fn set_status<I: IO>(io: &mut I, exit_id: u64, status: ExitStatus) {
+    if exit_id == 0 {
+        return;
+    }
+    io.write_storage(&status_key(exit_id), &status.try_to_vec().expect("ERR_SER"));
+}
+
+/// Gives an ID to the exit to NEAR scheduled by `promise`, if its callback is the refund of
+/// the exit, and records the exit as pending. Returns the `ExitScheduled` log of the exit. The
+/// entries written are recorded in `storage_changes`, to be staked for by the transaction.
+pub fn schedule_exit<I: IO>(
+    io: &mut I,
+    promise: &mut PromiseWithCallbackArgs,
+    storage_changes: &mut StorageChanges,
+) -> Option<ResultLog> {
+    if promise.callback.method != REFUND_ON_ERROR_METHOD {
+        return None;
+    }
+    let mut args = RefundCallArgs::try_from_slice(&promise.callback.args).ok()?;
+    args.exit_id = next_exit_id(io, storage_changes);
+    promise.callback.args = args.try_to_vec().expect("ERR_SER");
+    storage_changes.record(io, &status_key(args.exit_id));
+    set_status(io, args.exit_id, ExitStatus::Pending);
+
+    let log = events::ExitScheduled {
+        exit_id: args.exit_id,
+    }
+    .encode();
+    Some(exit_log(log))
+}
+
+/// The `Refunded` log of the refund of `amount` to the sender of an exit.
+pub fn refunded_log(args: &RefundCallArgs, amount: U256) -> ResultLog {
+    let log = events::Refunded {
+        exit_id: args.exit_id,
+        recipient: Address(args.recipient_address),
+        erc20_address: args
+            .erc20_address
+            .map(Address)
+            .unwrap_or(events::ETH_ADDRESS),
+        amount,
+    }
+    .encode();
+    exit_log(log)
+}
+
+fn exit_log(log: ethabi::RawLog) -> ResultLog {
+    ResultLog {
+        address: ExitToNear::ADDRESS.0,
+        topics: log.topics.into_iter().map(|topic| topic.0).collect(),
+        data: log.data,
+    }
+}
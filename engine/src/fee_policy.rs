@@ -0,0 +1,151 @@
+//! Distribution of the priority fee paid for the gas used by a transaction.
+//!
+//! The fee policy is part of `EngineState`. Shares of the priority fee, in basis points, go to
+//! a treasury address and are burned, and the relayer gets the rest. By default the relayer
+//! gets everything. The base fee is always burned (see `base_fee`).
+//!
+//! When the policy splits the fee, each distribution is logged by the engine address as a
+//! `FeeDistributed` event.
+//!
+//! Transactions paying less than the minimum gas price of the policy are rejected by `submit`,
+//! unless they are exempt (see `GasPriceExemptions`).
+
+use crate::parameters::ResultLog;
+use crate::prelude::storage::{bytes_to_key, KeyPrefix};
+use crate::prelude::{
+    sdk, vec, AccountId, Address, BorshDeserialize, BorshSerialize, RawAddress, RawU256, Vec, Wei,
+    H256, U256,
+};
+use aurora_engine_sdk::io::{StorageIntermediate, IO};
+
+/// Key for storing the exemptions from the minimum gas price.
+const GAS_PRICE_EXEMPTIONS_KEY: &[u8; 20] = b"GAS_PRICE_EXEMPTIONS";
+
+/// Basis points making up the whole fee.
+pub const MAX_BPS: u16 = 10_000;
+
+/// Signature of the event logged for each distribution. The burned amount includes the base fee.
+pub const FEE_DISTRIBUTED_SIGNATURE: &[u8] =
+    b"FeeDistributed(address,address,uint256,uint256,uint256)";
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct FeePolicy {
+    /// Share of the priority fee paid to `treasury_address`, in basis points.
+    pub treasury_bps: u16,
+    /// Share of the priority fee burned, in basis points.
+    pub burn_bps: u16,
+    pub treasury_address: RawAddress,
+    /// Gas price below which transactions are rejected. Zero by default, i.e. no minimum.
+    pub min_gas_price: RawU256,
+}
+
+/// Transactions exempt from the minimum gas price: the ones signed by one of `addresses`, and
+/// the ones submitted by one of `accounts` (e.g. relayers sponsoring free transactions).
+#[derive(BorshSerialize, BorshDeserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct GasPriceExemptions {
+    pub addresses: Vec<RawAddress>,
+    pub accounts: Vec<AccountId>,
+}
+
+impl GasPriceExemptions {
+    pub fn is_exempt(&self, sender: &Address, relayer_account_id: &AccountId) -> bool {
+        self.addresses.contains(&sender.0) || self.accounts.contains(relayer_account_id)
+    }
+}
+
+pub fn get_gas_price_exemptions<I: IO>(io: &I) -> GasPriceExemptions {
+    io.read_storage(&bytes_to_key(KeyPrefix::Config, GAS_PRICE_EXEMPTIONS_KEY))
+        .and_then(|bytes| GasPriceExemptions::try_from_slice(&bytes.to_vec()).ok())
+        .unwrap_or_default()
+}
+
+pub fn set_gas_price_exemptions<I: IO>(io: &mut I, exemptions: &GasPriceExemptions) {
+    io.write_storage(
+        &bytes_to_key(KeyPrefix::Config, GAS_PRICE_EXEMPTIONS_KEY),
+        &exemptions.try_to_vec().expect("ERR_SER"),
+    );
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeePolicyError {
+    /// The treasury and burn shares add up to more than the whole fee.
+    InvalidShares,
+    /// A share of the fee goes to the treasury, but its address is not set.
+    MissingTreasuryAddress,
+}
+
+impl AsRef<[u8]> for FeePolicyError {
+    fn as_ref(&self) -> &[u8] {
+        match self {
+            Self::InvalidShares => b"ERR_INVALID_FEE_SHARES",
+            Self::MissingTreasuryAddress => b"ERR_MISSING_TREASURY_ADDRESS",
+        }
+    }
+}
+
+/// Parts of a priority fee, as distributed by a `FeePolicy`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct FeeSplit {
+    pub relayer: Wei,
+    pub treasury: Wei,
+    pub burned: Wei,
+}
+
+impl FeePolicy {
+    pub fn validate(&self) -> Result<(), FeePolicyError> {
+        if u32::from(self.treasury_bps) + u32::from(self.burn_bps) > u32::from(MAX_BPS) {
+            return Err(FeePolicyError::InvalidShares);
+        }
+        if self.treasury_bps != 0 && self.treasury_address == [0u8; 20] {
+            return Err(FeePolicyError::MissingTreasuryAddress);
+        }
+        Ok(())
+    }
+
+    /// Whether some of the priority fee goes elsewhere than to the relayer.
+    pub fn is_split(&self) -> bool {
+        self.treasury_bps != 0 || self.burn_bps != 0
+    }
+
+    pub fn treasury(&self) -> Address {
+        Address(self.treasury_address)
+    }
+
+    /// Splits `fee` according to the policy. Rounding is in favor of the relayer.
+    pub fn split(&self, fee: Wei) -> FeeSplit {
+        let share = |bps: u16| Wei::new(fee.raw() * U256::from(bps) / U256::from(MAX_BPS));
+        let treasury = share(self.treasury_bps);
+        let burned = share(self.burn_bps);
+        let relayer = Wei::new(fee.raw() - treasury.raw() - burned.raw());
+        FeeSplit {
+            relayer,
+            treasury,
+            burned,
+        }
+    }
+}
+
+/// The `FeeDistributed` event, logged by `engine_address`.
+pub fn fee_distributed_log(
+    engine_address: &Address,
+    relayer: &Address,
+    treasury: &Address,
+    relayer_amount: Wei,
+    treasury_amount: Wei,
+    burned_amount: Wei,
+) -> ResultLog {
+    let address_topic = |address: &Address| H256::from(*address).0;
+    let mut data = Vec::with_capacity(96);
+    for amount in [relayer_amount, treasury_amount, burned_amount].iter() {
+        data.extend_from_slice(&amount.to_bytes());
+    }
+    ResultLog {
+        address: engine_address.0,
+        topics: vec![
+            sdk::keccak(FEE_DISTRIBUTED_SIGNATURE).0,
+            address_topic(relayer),
+            address_topic(treasury),
+        ],
+        data,
+    }
+}
@@ -1,4 +1,4 @@
-use crate::prelude::{BTreeMap, String, TryFrom, TryInto, Vec};
+use crate::prelude::{json_string, BTreeMap, String, TryFrom, TryInto, Vec};
 
 use core::convert::From;
 use rjson::{Array, Null, Object, Value};
@@ -237,7 +237,7 @@ impl core::fmt::Debug for JsonValue {
     fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
         match self {
             JsonValue::Null => f.write_str("null"),
-            JsonValue::String(v) => f.write_fmt(format_args!("\"{}\"", v)),
+            JsonValue::String(v) => f.write_str(&json_string(v)),
             JsonValue::F64(v) => f.write_fmt(format_args!("{}", v)),
             JsonValue::I64(v) => f.write_fmt(format_args!("{}", v)),
             JsonValue::U64(v) => f.write_fmt(format_args!("{}", v)),
@@ -257,10 +257,10 @@ impl core::fmt::Debug for JsonValue {
                 f.write_str("{")?;
                 let mut pairs = kvs.iter();
                 if let Some((key, value)) = pairs.next() {
-                    f.write_fmt(format_args!("\"{}\": {:?}", key, value))?;
+                    f.write_fmt(format_args!("{}: {:?}", json_string(key), value))?;
                 }
                 for (key, value) in pairs {
-                    f.write_fmt(format_args!(", \"{}\": {:?}", key, value))?;
+                    f.write_fmt(format_args!(", {}: {:?}", json_string(key), value))?;
                 }
                 f.write_str("}")
             }
@@ -554,5 +554,19 @@ mod tests {
             &format!("{}", object),
             "{\"words\": [\"Hello\", \"World\"]}"
         );
+
+        // strings and keys are escaped
+        let object = JsonValue::Object(
+            vec![(
+                "error".to_string(),
+                JsonValue::String("Other(\"ERR_INVALID_INPUT\")".to_string()),
+            )]
+            .into_iter()
+            .collect(),
+        );
+        assert_eq!(
+            &format!("{}", object),
+            r#"{"error": "Other(\"ERR_INVALID_INPUT\")"}"#
+        );
     }
 }
@@ -18,14 +18,27 @@ pub mod proof;
 pub mod transaction;
 
 pub mod admin_controlled;
+pub mod base_fee;
+pub mod bloom;
 #[cfg_attr(feature = "contract", allow(dead_code))]
 pub mod connector;
+pub mod cross_contract_call;
 pub mod deposit_event;
 pub mod engine;
+pub mod exit_status;
+pub mod fee_policy;
 pub mod fungible_token;
 pub mod json;
 pub mod log_entry;
+pub mod logs_index;
+pub mod migration;
+pub mod overrides;
 mod prelude;
+pub mod storage_staking;
+#[cfg(feature = "tracing")]
+pub mod tracing;
+pub mod transaction_queue;
+pub mod upgrade;
 
 #[cfg(target_arch = "wasm32")]
 #[global_allocator]
@@ -66,18 +79,29 @@ pub unsafe fn on_alloc_error(_: core::alloc::Layout) -> ! {
 mod contract {
     use borsh::{BorshDeserialize, BorshSerialize};
 
+    use crate::base_fee::{BaseFeeMode, BaseFeeState};
     use crate::connector::EthConnectorContract;
-    use crate::engine::{self, current_address, Engine, EngineState, GasPaymentError};
+    use crate::cross_contract_call;
+    use crate::engine::{self, current_address, Engine, EngineState};
+    use crate::exit_status::{self, ExitStatus};
+    use crate::fee_policy::{self, FeePolicy, GasPriceExemptions};
     use crate::fungible_token::FungibleTokenMetadata;
+    use crate::logs_index;
+    use crate::migration;
+    use crate::overrides;
     #[cfg(feature = "evm_bully")]
     use crate::parameters::{BeginBlockArgs, BeginChainArgs};
     use crate::parameters::{
-        CallArgs, DeployErc20TokenArgs, GetErc20FromNep141CallArgs, GetStorageAtArgs, InitCallArgs,
+        CallArgs, DeployErc20TokenArgs, DeployUpgradeArgs, EstimateGasArgs,
+        GetErc20FromNep141CallArgs, GetLogsArgs, GetStorageAtArgs, InitCallArgs,
         IsUsedProofCallArgs, NEP141FtOnTransferArgs, NewCallArgs, PauseEthConnectorCallArgs,
-        ResolveTransferCallArgs, SetContractDataCallArgs, StorageDepositCallArgs,
-        StorageWithdrawCallArgs, SubmitResult, TransactionStatus, TransferCallCallArgs,
-        ViewCallArgs,
+        ResolveTransferCallArgs, SetBaseFeeArgs, SetContractDataCallArgs,
+        SetCrossContractCallPermissionArgs, StageUpgradeArgs, StorageDepositCallArgs,
+        StorageWithdrawCallArgs, TransactionStatus, TransferCallCallArgs, ViewArgs,
     };
+    use crate::storage_staking;
+    use crate::transaction_queue;
+    use crate::upgrade;
     use aurora_engine_sdk::env::Env;
     use aurora_engine_sdk::io::{StorageIntermediate, IO};
     use aurora_engine_sdk::near_runtime::Runtime;
@@ -85,21 +109,19 @@ mod contract {
     use aurora_engine_types::account_id::AccountId;
 
     use crate::json::parse_json;
-    use crate::prelude::parameters::RefundCallArgs;
+    use crate::prelude::parameters::{CrossContractCallbackArgs, RefundCallArgs};
     use crate::prelude::sdk::types::{
         near_account_to_evm_address, SdkExpect, SdkProcess, SdkUnwrap,
     };
     use crate::prelude::storage::{bytes_to_key, KeyPrefix};
-    use crate::prelude::types::{u256_to_arr, ERR_FAILED_PARSE};
+    use crate::prelude::types::{u256_to_arr, RawH256, RawU256, ERR_FAILED_PARSE};
     use crate::prelude::{
-        sdk, vec, Address, PromiseResult, ToString, TryFrom, TryInto, Vec, Wei,
-        ERC20_MINT_SELECTOR, H160, H256, U256,
+        sdk, vec, Address, PromiseResult, ToString, TryInto, Vec, Wei, ERC20_MINT_SELECTOR, H160,
+        H256, U256,
     };
-    use crate::transaction::{EthTransactionKind, NormalizedEthTransaction};
 
     const CODE_KEY: &[u8; 4] = b"CODE";
     const CODE_STAGE_KEY: &[u8; 10] = b"CODE_STAGE";
-    const GAS_OVERFLOW: &str = "ERR_GAS_OVERFLOW";
     const PROMISE_COUNT_ERR: &str = "ERR_PROMISE_COUNT";
 
     ///
@@ -113,6 +135,10 @@ mod contract {
         let mut io = Runtime;
         if let Ok(state) = engine::get_state(&io) {
             require_owner_only(&state, &io.predecessor_account_id());
+        } else {
+            // A new deployment starts at the latest version of the state.
+            let migrations = migration::registry::<Runtime>();
+            migration::set_version(&mut io, migration::latest_version(&migrations));
         }
 
         let args: NewCallArgs = io.read_input_borsh().sdk_unwrap();
@@ -161,38 +187,218 @@ mod contract {
         io.return_output(&(index + state.upgrade_delay_blocks).to_le_bytes())
     }
 
-    /// Stage new code for deployment.
+    /// Get the base fee per gas of the current block.
+    #[no_mangle]
+    pub extern "C" fn get_base_fee() {
+        let mut io = Runtime;
+        let base_fee = engine::get_state(&io)
+            .sdk_unwrap()
+            .base_fee
+            .base_fee_at(io.block_height());
+        io.return_output(&u256_to_arr(&base_fee))
+    }
+
+    /// Set the base fee per gas, and how it evolves from one block to the next.
+    #[no_mangle]
+    pub extern "C" fn set_base_fee() {
+        let mut io = Runtime;
+        let mut state = engine::get_state(&io).sdk_unwrap();
+        require_owner_only(&state, &io.predecessor_account_id());
+        let args: SetBaseFeeArgs = io.read_input_borsh().sdk_unwrap();
+        let mode = match args.gas_target {
+            None => BaseFeeMode::Fixed,
+            Some(0) => sdk::panic_utf8(b"ERR_INVALID_GAS_TARGET"),
+            Some(gas_target) => BaseFeeMode::Dynamic { gas_target },
+        };
+        state.base_fee = BaseFeeState {
+            mode,
+            min_base_fee_per_gas: args.min_base_fee_per_gas,
+            base_fee_per_gas: args.base_fee_per_gas,
+            block_height: io.block_height(),
+            block_gas_used: 0,
+        };
+        engine::set_state(&mut io, state);
+    }
+
+    /// Get the fee policy: how the priority fee paid for gas is split between the relayer, the
+    /// treasury and a burn, and the minimum gas price.
+    #[no_mangle]
+    pub extern "C" fn get_fee_policy() {
+        let mut io = Runtime;
+        let state = engine::get_state(&io).sdk_unwrap();
+        io.return_output(&state.fee_policy.try_to_vec().sdk_expect("ERR_SERIALIZE"))
+    }
+
+    /// Set the fee policy. The treasury and burn shares, in basis points, cannot exceed the
+    /// whole fee.
+    #[no_mangle]
+    pub extern "C" fn set_fee_policy() {
+        let mut io = Runtime;
+        let mut state = engine::get_state(&io).sdk_unwrap();
+        require_owner_only(&state, &io.predecessor_account_id());
+        let fee_policy: FeePolicy = io.read_input_borsh().sdk_unwrap();
+        fee_policy.validate().sdk_unwrap();
+        state.fee_policy = fee_policy;
+        engine::set_state(&mut io, state);
+    }
+
+    /// Get the addresses and NEAR accounts exempt from the minimum gas price of the fee policy.
+    #[no_mangle]
+    pub extern "C" fn get_gas_price_exemptions() {
+        let mut io = Runtime;
+        let exemptions = fee_policy::get_gas_price_exemptions(&io);
+        io.return_output(&exemptions.try_to_vec().sdk_expect("ERR_SERIALIZE"))
+    }
+
+    /// Set the addresses signing, and the NEAR accounts submitting, transactions exempt from
+    /// the minimum gas price of the fee policy.
+    #[no_mangle]
+    pub extern "C" fn set_gas_price_exemptions() {
+        let mut io = Runtime;
+        let state = engine::get_state(&io).sdk_unwrap();
+        require_owner_only(&state, &io.predecessor_account_id());
+        let exemptions: GasPriceExemptions = io.read_input_borsh().sdk_unwrap();
+        fee_policy::set_gas_price_exemptions(&mut io, &exemptions);
+    }
+
+    /// Get the price, in wei, of a byte of storage added to the engine by a transaction.
+    #[no_mangle]
+    pub extern "C" fn get_storage_price() {
+        let mut io = Runtime;
+        let price = storage_staking::get_price_per_byte(&io);
+        io.return_output(&u256_to_arr(&price))
+    }
+
+    /// Set the price, in wei, of a byte of storage added to the engine by a transaction.
+    #[no_mangle]
+    pub extern "C" fn set_storage_price() {
+        let mut io = Runtime;
+        let state = engine::get_state(&io).sdk_unwrap();
+        require_owner_only(&state, &io.predecessor_account_id());
+        let price: RawU256 = io.read_input_borsh().sdk_unwrap();
+        storage_staking::set_price_per_byte(&mut io, &U256::from(price));
+    }
+
+    /// Get the maximum number of transactions with a future nonce queued per sender by
+    /// `submit`. The queue is disabled when it is zero.
+    #[no_mangle]
+    pub extern "C" fn get_transaction_queue_limit() {
+        let mut io = Runtime;
+        let limit = transaction_queue::get_limit(&io);
+        io.return_output(&limit.try_to_vec().sdk_expect("ERR_SERIALIZE"))
+    }
+
+    /// Set the maximum number of transactions with a future nonce queued per sender by
+    /// `submit`. Setting it to zero disables the queue.
+    #[no_mangle]
+    pub extern "C" fn set_transaction_queue_limit() {
+        let mut io = Runtime;
+        let state = engine::get_state(&io).sdk_unwrap();
+        require_owner_only(&state, &io.predecessor_account_id());
+        let limit: u32 = io.read_input_borsh().sdk_unwrap();
+        transaction_queue::set_limit(&mut io, limit);
+    }
+
+    /// Allow an EVM contract to schedule NEAR calls through the cross-contract call precompile,
+    /// within the given bounds, or revoke its permission.
+    #[no_mangle]
+    pub extern "C" fn set_cross_contract_call_permission() {
+        let mut io = Runtime;
+        let state = engine::get_state(&io).sdk_unwrap();
+        require_owner_only(&state, &io.predecessor_account_id());
+        let args: SetCrossContractCallPermissionArgs = io.read_input_borsh().sdk_unwrap();
+        cross_contract_call::set_permission(
+            &mut io,
+            &Address(args.address),
+            args.permission.as_ref(),
+        );
+    }
+
+    /// Stage new code for deployment. With the `validate` flag, the code must be a wasm module
+    /// exporting the methods required by the upgrade process.
     #[no_mangle]
     pub extern "C" fn stage_upgrade() {
         let mut io = Runtime;
         let state = engine::get_state(&io).sdk_unwrap();
         let block_height = io.block_height();
         require_owner_only(&state, &io.predecessor_account_id());
-        io.read_input_and_store(&bytes_to_key(KeyPrefix::Config, CODE_KEY));
+        let args: StageUpgradeArgs = io.read_input_borsh().sdk_unwrap();
+        if args.validate {
+            upgrade::validate_code(&args.code).sdk_unwrap();
+        }
+        io.write_storage(&bytes_to_key(KeyPrefix::Config, CODE_KEY), &args.code);
         io.write_storage(
             &bytes_to_key(KeyPrefix::Config, CODE_STAGE_KEY),
             &block_height.to_le_bytes(),
         );
     }
 
-    /// Deploy staged upgrade.
+    /// Remove the code staged for deployment.
+    #[no_mangle]
+    pub extern "C" fn cancel_upgrade() {
+        let mut io = Runtime;
+        let state = engine::get_state(&io).sdk_unwrap();
+        require_owner_only(&state, &io.predecessor_account_id());
+        if io
+            .remove_storage(&bytes_to_key(KeyPrefix::Config, CODE_KEY))
+            .is_none()
+        {
+            sdk::panic_utf8(b"ERR_NO_UPGRADE");
+        }
+        io.remove_storage(&bytes_to_key(KeyPrefix::Config, CODE_STAGE_KEY));
+    }
+
+    /// Get the sha256 hash of the code staged for deployment.
+    #[no_mangle]
+    pub extern "C" fn get_staged_upgrade_hash() {
+        let mut io = Runtime;
+        let code = internal_get_staged_code(&io);
+        io.return_output(sdk::sha256(&code.to_vec()).as_bytes())
+    }
+
+    /// Get the size, in bytes, of the code staged for deployment.
+    #[no_mangle]
+    pub extern "C" fn get_staged_upgrade_size() {
+        let mut io = Runtime;
+        let code = internal_get_staged_code(&io);
+        io.return_output(&(code.len() as u64).to_le_bytes())
+    }
+
+    /// Deploy staged upgrade. With the `validate` flag, the staged code is checked first, as
+    /// `stage_upgrade` does.
     #[no_mangle]
     pub extern "C" fn deploy_upgrade() {
         let io = Runtime;
+        let args: DeployUpgradeArgs = io.read_input_borsh().sdk_unwrap();
         let state = engine::get_state(&io).sdk_unwrap();
         let index = internal_get_upgrade_index();
         if io.block_height() <= index + state.upgrade_delay_blocks {
             sdk::panic_utf8(b"ERR_NOT_ALLOWED:TOO_EARLY");
         }
+        if args.validate {
+            let code = internal_get_staged_code(&io).to_vec();
+            upgrade::validate_code(&code).sdk_unwrap();
+        }
         Runtime::self_deploy(&bytes_to_key(KeyPrefix::Config, CODE_KEY));
     }
 
     /// Called as part of the upgrade process (see `engine-sdk::self_deploy`). This function is meant
     /// to make any necessary changes to the state such that it aligns with the newly deployed
-    /// code.
+    /// code. If a migration step is not done within one call, the owner calls it again to resume it.
     #[no_mangle]
     pub extern "C" fn state_migration() {
-        // TODO: currently we don't have migrations
+        let mut io = Runtime;
+        let outcome =
+            migration::state_migration(&mut io, &Runtime, &migration::registry()).sdk_unwrap();
+        io.return_output(&outcome.try_to_vec().sdk_expect("ERR_SERIALIZE"));
+    }
+
+    /// Get the version of the engine state (see `migration`).
+    #[no_mangle]
+    pub extern "C" fn get_state_version() {
+        let mut io = Runtime;
+        let version = migration::get_version(&io);
+        io.return_output(&version.try_to_vec().sdk_expect("ERR_SERIALIZE"));
     }
 
     ///
@@ -215,7 +421,6 @@ mod contract {
         Engine::deploy_code_with_input(&mut engine, input, &mut Runtime)
             .map(|res| res.try_to_vec().sdk_expect("ERR_SERIALIZE"))
             .sdk_process();
-        // TODO: charge for storage
     }
 
     /// Call method on the EVM contract.
@@ -235,113 +440,55 @@ mod contract {
         Engine::call_with_args(&mut engine, args, &mut Runtime)
             .map(|res| res.try_to_vec().sdk_expect("ERR_SERIALIZE"))
             .sdk_process();
-        // TODO: charge for storage
     }
 
     /// Process signed Ethereum transaction.
     /// Must match CHAIN_ID to make sure it's signed for given chain vs replayed from another chain.
     #[no_mangle]
     pub extern "C" fn submit() {
-        let mut io = Runtime;
+        let io = Runtime;
         let input = io.read_input().to_vec();
-
-        let transaction: NormalizedEthTransaction = EthTransactionKind::try_from(input.as_slice())
-            .sdk_unwrap()
-            .into();
-
-        let state = engine::get_state(&io).sdk_unwrap();
-
-        // Validate the chain ID, if provided inside the signature:
-        if let Some(chain_id) = transaction.chain_id {
-            if U256::from(chain_id) != U256::from(state.chain_id) {
-                sdk::panic_utf8(b"ERR_INVALID_CHAIN_ID");
-            }
-        }
-
-        // Retrieve the signer of the transaction:
-        let sender = transaction
-            .address
-            .sdk_expect("ERR_INVALID_ECDSA_SIGNATURE");
-
-        #[cfg(feature = "log")]
-        sdk::log(crate::prelude::format!("signer_address {:?}", sender).as_str());
-
-        engine::check_nonce(&io, &sender, &transaction.nonce).sdk_unwrap();
-
-        // Check intrinsic gas is covered by transaction gas limit
-        match transaction.intrinsic_gas(crate::engine::CONFIG) {
-            None => sdk::panic_utf8(GAS_OVERFLOW.as_bytes()),
-            Some(intrinsic_gas) => {
-                if transaction.gas_limit < intrinsic_gas.into() {
-                    sdk::panic_utf8(b"ERR_INTRINSIC_GAS")
-                }
-            }
-        }
-
-        if transaction.max_priority_fee_per_gas > transaction.max_fee_per_gas {
-            sdk::panic_utf8(b"ERR_MAX_PRIORITY_FEE_GREATER")
-        }
-
-        // Figure out what kind of a transaction this is, and execute it:
         let current_account_id = io.current_account_id();
-        let mut engine = Engine::new_with_state(state, sender, current_account_id, io, &io);
-        let prepaid_amount = match engine.charge_gas(&sender, &transaction) {
-            Ok(gas_result) => gas_result,
-            Err(GasPaymentError::OutOfFund) => {
-                engine::increment_nonce(&mut io, &sender);
-                let result = SubmitResult::new(TransactionStatus::OutOfFund, 0, vec![]);
-                io.return_output(&result.try_to_vec().unwrap());
-                return;
-            }
-            Err(err) => sdk::panic_utf8(err.as_ref()),
-        };
-        let gas_limit: u64 = transaction.gas_limit.try_into().sdk_expect(GAS_OVERFLOW);
-        let access_list = transaction
-            .access_list
-            .into_iter()
-            .map(|a| (a.address, a.storage_keys))
-            .collect();
-        let result = if let Some(receiver) = transaction.to {
-            Engine::call(
-                &mut engine,
-                sender,
-                receiver,
-                transaction.value,
-                transaction.data,
-                gas_limit,
-                access_list,
-                &mut Runtime,
-            )
-            // TODO: charge for storage
-        } else {
-            // Execute a contract deployment:
-            Engine::deploy_code(
-                &mut engine,
-                sender,
-                transaction.value,
-                transaction.data,
-                gas_limit,
-                access_list,
-                &mut Runtime,
-            )
-            // TODO: charge for storage
-        };
-
-        // Give refund
-        let relayer = predecessor_address(&io.predecessor_account_id());
-        let gas_used = match &result {
-            Ok(submit_result) => submit_result.gas_used,
-            Err(engine_err) => engine_err.gas_used,
-        };
-        engine::refund_unused_gas(&mut io, &sender, gas_used, prepaid_amount, &relayer)
-            .sdk_unwrap();
+        let state = engine::get_state(&io).sdk_unwrap();
+        let relayer_address = predecessor_address(&io.predecessor_account_id());
+        let result = engine::submit(
+            io,
+            &io,
+            &input,
+            state,
+            current_account_id,
+            relayer_address,
+            &mut Runtime,
+        );
 
-        // return result to user
         result
             .map(|res| res.try_to_vec().sdk_expect("ERR_SERIALIZE"))
             .sdk_process();
     }
 
+    /// Process signed Ethereum transactions in order, each of them as `submit` does. A
+    /// transaction which fails has no effect and does not prevent the next ones from being
+    /// processed. The input is the borsh-encoded list of the transactions and the output the
+    /// borsh-encoded list of their outcomes.
+    #[no_mangle]
+    pub extern "C" fn submit_batch() {
+        let mut io = Runtime;
+        let transactions: Vec<Vec<u8>> = io.read_input_borsh().sdk_unwrap();
+        let current_account_id = io.current_account_id();
+        let state = engine::get_state(&io).sdk_unwrap();
+        let relayer_address = predecessor_address(&io.predecessor_account_id());
+        let results = engine::submit_batch(
+            io,
+            &io,
+            &transactions,
+            state,
+            current_account_id,
+            relayer_address,
+            &mut Runtime,
+        );
+        io.return_output(&results.try_to_vec().sdk_expect("ERR_SERIALIZE"));
+    }
+
     #[cfg(feature = "meta-call")]
     #[no_mangle]
     pub extern "C" fn meta_call() {
@@ -419,6 +566,7 @@ mod contract {
             EthConnectorContract::get_instance(io).ft_on_transfer(&engine, &args);
         } else {
             let signer_account_id = io.signer_account_id();
+            engine.exempt_from_storage_staking();
             engine.receive_erc20_tokens(
                 &predecessor_account_id,
                 &signer_account_id,
@@ -430,6 +578,10 @@ mod contract {
     }
 
     /// Deploy ERC20 token mapped to a NEP141
+    ///
+    /// The token is always the `EvmErc20V2` contract, whose exits are refunded to the burner
+    /// on failure. Tokens deployed by older versions use the legacy exit format, refunded to
+    /// the token contract itself.
     #[no_mangle]
     pub extern "C" fn deploy_erc20_token() {
         let mut io = Runtime;
@@ -445,11 +597,9 @@ mod contract {
             &io,
         )
         .sdk_unwrap();
+        engine.exempt_from_storage_staking();
 
-        #[cfg(feature = "error_refund")]
         let erc20_contract = include_bytes!("../../etc/eth-contracts/res/EvmErc20V2.bin");
-        #[cfg(not(feature = "error_refund"))]
-        let erc20_contract = include_bytes!("../../etc/eth-contracts/res/EvmErc20.bin");
 
         let deploy_args = ethabi::encode(&[
             ethabi::Token::String("Empty".to_string()),
@@ -473,15 +623,14 @@ mod contract {
         sdk::log!(crate::prelude::format!("Deployed ERC-20 in Aurora at: {:#?}", address).as_str());
         engine.register_token(address, args.nep141).sdk_unwrap();
         io.return_output(&address.as_bytes().try_to_vec().sdk_expect("ERR_SERIALIZE"));
-
-        // TODO: charge for storage
     }
 
     /// Callback invoked by exit to NEAR precompile to handle potential
-    /// errors in the exit call.
+    /// errors in the exit call, and the tokens not used by the receiver of
+    /// an exit with `ft_transfer_call`.
     #[no_mangle]
     pub extern "C" fn refund_on_error() {
-        let io = Runtime;
+        let mut io = Runtime;
         io.assert_private_call().sdk_unwrap();
 
         // This function should only be called as the callback of
@@ -490,27 +639,36 @@ mod contract {
             sdk::panic_utf8(PROMISE_COUNT_ERR.as_bytes());
         }
 
-        if let Some(PromiseResult::Successful(_)) = io.promise_result(0) {
-            // Promise succeeded -- nothing to do
-        } else {
+        let args: RefundCallArgs = io.read_input_borsh().sdk_unwrap();
+        let exit_amount = U256::from_big_endian(&args.amount);
+        let refund_amount = match io.promise_result(0) {
+            // `ft_transfer` returns nothing, and `ft_transfer_call` the amount used by the
+            // receiver, the rest having been transferred back to this account.
+            Some(PromiseResult::Successful(value)) => {
+                exit_amount.saturating_sub(parse_used_amount(&value).unwrap_or(exit_amount))
+            }
             // Exit call failed; need to refund tokens
+            _ => exit_amount,
+        };
 
+        if refund_amount.is_zero() {
+            exit_status::set_status(&mut io, args.exit_id, ExitStatus::Completed);
+        } else {
             let current_account_id = io.current_account_id();
-            let args: RefundCallArgs = io.read_input_borsh().sdk_unwrap();
-            let refund_result = match args.erc20_address {
+            let mut refund_result = match args.erc20_address {
                 // ERC-20 exit; re-mint burned tokens
                 Some(erc20_address) => {
                     let erc20_admin_address = current_address(&current_account_id);
                     let mut engine =
                         Engine::new(erc20_admin_address, current_account_id, io, &io).sdk_unwrap();
+                    engine.exempt_from_storage_staking();
                     let erc20_address = Address(erc20_address);
                     let refund_address = Address(args.recipient_address);
-                    let amount = U256::from_big_endian(&args.amount);
 
                     let selector = ERC20_MINT_SELECTOR;
                     let mint_args = ethabi::encode(&[
                         ethabi::Token::Address(refund_address),
-                        ethabi::Token::Uint(amount),
+                        ethabi::Token::Uint(refund_amount),
                     ]);
 
                     engine
@@ -530,8 +688,9 @@ mod contract {
                     let exit_address = aurora_engine_precompiles::native::ExitToNear::ADDRESS;
                     let mut engine =
                         Engine::new(exit_address, current_account_id, io, &io).sdk_unwrap();
+                    engine.exempt_from_storage_staking();
                     let refund_address = Address(args.recipient_address);
-                    let amount = Wei::new(U256::from_big_endian(&args.amount));
+                    let amount = Wei::new(refund_amount);
                     engine
                         .call(
                             exit_address,
@@ -549,15 +708,85 @@ mod contract {
             if !refund_result.status.is_ok() {
                 sdk::panic_utf8(b"ERR_REFUND_FAILURE");
             }
+
+            exit_status::set_status(&mut io, args.exit_id, ExitStatus::Refunded);
+            refund_result
+                .logs
+                .push(exit_status::refunded_log(&args, refund_amount));
+            io.return_output(&refund_result.try_to_vec().sdk_expect("ERR_SERIALIZE"));
         }
     }
+
+    /// Callback of the NEAR calls scheduled by the cross-contract call precompile. Calls back
+    /// the EVM contract which scheduled the call with its result (see
+    /// `cross_contract_call::callback_input`), from the precompile address.
+    #[no_mangle]
+    pub extern "C" fn cross_contract_call_callback() {
+        let mut io = Runtime;
+        io.assert_private_call().sdk_unwrap();
+
+        // This function should only be called as the callback of
+        // exactly one promise.
+        if io.promise_results_count() != 1 {
+            sdk::panic_utf8(PROMISE_COUNT_ERR.as_bytes());
+        }
+
+        let args: CrossContractCallbackArgs = io.read_input_borsh().sdk_unwrap();
+        let promise_result = io.promise_result(0).sdk_unwrap();
+        let input = cross_contract_call::callback_input(&args, &promise_result);
+
+        let current_account_id = io.current_account_id();
+        let origin = aurora_engine_precompiles::cross_contract_call::CrossContractCall::ADDRESS;
+        let mut engine = Engine::new(origin, current_account_id, io, &io).sdk_unwrap();
+        engine.exempt_from_storage_staking();
+        let result = engine
+            .call(
+                origin,
+                Address(args.caller),
+                Wei::zero(),
+                input,
+                u64::MAX,
+                Vec::new(),
+                &mut Runtime,
+            )
+            .sdk_unwrap();
+        io.return_output(&result.try_to_vec().sdk_expect("ERR_SERIALIZE"));
+    }
+
     ///
     /// NONMUTATIVE METHODS
     ///
     #[no_mangle]
     pub extern "C" fn view() {
         let mut io = Runtime;
-        let args: ViewCallArgs = io.read_input_borsh().sdk_unwrap();
+        let bytes = io.read_input().to_vec();
+        let args = ViewArgs::deserialize(&bytes).sdk_expect("ERR_BORSH_DESERIALIZE");
+        let current_account_id = io.current_account_id();
+        let result = match args {
+            ViewArgs::V2(args) => {
+                let state = engine::get_state(&io).sdk_unwrap();
+                overrides::view_with_overrides(io, &io, state, current_account_id, args)
+            }
+            ViewArgs::V1(args) => {
+                let engine = Engine::new(
+                    Address::from_slice(&args.sender),
+                    current_account_id,
+                    io,
+                    &io,
+                )
+                .sdk_unwrap();
+                Engine::view_with_args(&engine, args)
+            }
+        }
+        .sdk_unwrap();
+        io.return_output(&result.try_to_vec().sdk_expect("ERR_SERIALIZE"));
+    }
+
+    /// Estimate the minimal gas limit for which a call or a deployment succeeds.
+    #[no_mangle]
+    pub extern "C" fn estimate_gas() {
+        let mut io = Runtime;
+        let args: EstimateGasArgs = io.read_input_borsh().sdk_unwrap();
         let current_account_id = io.current_account_id();
         let engine = Engine::new(
             Address::from_slice(&args.sender),
@@ -566,10 +795,62 @@ mod contract {
             &io,
         )
         .sdk_unwrap();
-        let result = Engine::view_with_args(&engine, args).sdk_unwrap();
+        let result = engine.estimate_gas(args).sdk_unwrap();
         io.return_output(&result.try_to_vec().sdk_expect("ERR_SERIALIZE"));
     }
 
+    /// Executes a view call and returns the trace of its execution, in the format of geth's
+    /// `debug_traceCall` (JSON encoded).
+    #[cfg(feature = "tracing")]
+    #[no_mangle]
+    pub extern "C" fn debug_trace_call() {
+        use crate::parameters::ViewCallArgs;
+        use crate::tracing::{traced_call, StepTracer};
+
+        let mut io = Runtime;
+        let args: ViewCallArgs = io.read_input_borsh().sdk_unwrap();
+        let current_account_id = io.current_account_id();
+        let engine = Engine::new(
+            Address::from_slice(&args.sender),
+            current_account_id,
+            io,
+            &io,
+        )
+        .sdk_unwrap();
+        let (tracer, result) = traced_call(StepTracer::default(), || {
+            Engine::view_with_args(&engine, args)
+        });
+        let trace = tracer.finish(&result.sdk_unwrap());
+        io.return_output(trace.to_json().to_string().as_bytes());
+    }
+
+    /// Executes a signed Ethereum transaction as `submit` does, without keeping any of its
+    /// effects, and returns the trace of its execution in the format of geth's
+    /// `debug_traceTransaction` (JSON encoded).
+    #[cfg(feature = "tracing")]
+    #[no_mangle]
+    pub extern "C" fn debug_trace_transaction() {
+        use crate::tracing::{trace_transaction, StepTracer};
+
+        let mut io = Runtime;
+        let input = io.read_input().to_vec();
+        let current_account_id = io.current_account_id();
+        let state = engine::get_state(&io).sdk_unwrap();
+        let relayer_address = predecessor_address(&io.predecessor_account_id());
+        let (tracer, result) = trace_transaction(
+            io,
+            &io,
+            &input,
+            state,
+            current_account_id,
+            relayer_address,
+            &mut Runtime,
+            StepTracer::default(),
+        );
+        let trace = tracer.finish(&result.sdk_unwrap().status);
+        io.return_output(trace.to_json().to_string().as_bytes());
+    }
+
     #[no_mangle]
     pub extern "C" fn get_block_hash() {
         let mut io = Runtime;
@@ -578,8 +859,9 @@ mod contract {
         let chain_id = engine::get_state(&io)
             .map(|state| state.chain_id)
             .sdk_unwrap();
-        let block_hash =
-            crate::engine::compute_block_hash(chain_id, block_height, account_id.as_bytes());
+        let block_hash = engine::get_block_hash(&io, block_height).unwrap_or_else(|| {
+            engine::compute_block_hash(chain_id, block_height, account_id.as_bytes())
+        });
         io.return_output(block_hash.as_bytes())
     }
 
@@ -617,6 +899,67 @@ mod contract {
         io.return_output(&value.0)
     }
 
+    /// Get the receipt of a transaction processed by `submit`, given the keccak hash of the
+    /// signed transaction bytes. The receipts are only kept for about a day
+    /// (`logs_index::RETENTION_BLOCKS` blocks), like the logs: older ones are not found.
+    #[no_mangle]
+    pub extern "C" fn get_transaction_receipt() {
+        let mut io = Runtime;
+        let transaction_hash: RawH256 = io.read_input_borsh().sdk_unwrap();
+        let receipt = engine::get_transaction_receipt(&io, &H256(transaction_hash))
+            .unwrap_or_else(|| sdk::panic_utf8(b"ERR_RECEIPT_NOT_FOUND"));
+        io.return_output(&receipt.try_to_vec().sdk_expect("ERR_SERIALIZE"))
+    }
+
+    /// Get the transactions of a sender queued by `submit` until their nonce is reached, in
+    /// increasing nonce order.
+    #[no_mangle]
+    pub extern "C" fn get_pending_transactions() {
+        let mut io = Runtime;
+        let sender = io.read_input_arr20().sdk_unwrap();
+        let pending = transaction_queue::get_pending_transactions(&io, &Address(sender));
+        io.return_output(&pending.try_to_vec().sdk_expect("ERR_SERIALIZE"))
+    }
+
+    /// Get the bounds of the NEAR calls an EVM contract may schedule through the cross-contract
+    /// call precompile, if it is allowed to.
+    #[no_mangle]
+    pub extern "C" fn get_cross_contract_call_permission() {
+        let mut io = Runtime;
+        let address = io.read_input_arr20().sdk_unwrap();
+        let permission = cross_contract_call::get_permission(&io, &Address(address));
+        io.return_output(&permission.try_to_vec().sdk_expect("ERR_SERIALIZE"))
+    }
+
+    /// Get the status of an exit to NEAR from its ID, logged in its `ExitScheduled` event.
+    #[no_mangle]
+    pub extern "C" fn get_exit_status() {
+        let mut io = Runtime;
+        let exit_id: u64 = io.read_input_borsh().sdk_unwrap();
+        let status = exit_status::get_status(&io, exit_id);
+        io.return_output(&status.try_to_vec().sdk_expect("ERR_SERIALIZE"))
+    }
+
+    /// Get the logs emitted in a range of NEAR blocks by the transactions processed by
+    /// `submit`, filtered by address and topics.
+    #[no_mangle]
+    pub extern "C" fn get_logs() {
+        let mut io = Runtime;
+        let args: GetLogsArgs = io.read_input_borsh().sdk_unwrap();
+        let logs = logs_index::get_logs(&io, &args).sdk_unwrap();
+        io.return_output(&logs.try_to_vec().sdk_expect("ERR_SERIALIZE"))
+    }
+
+    /// Get the bloom of the logs emitted in a NEAR block by the transactions processed by
+    /// `submit`.
+    #[no_mangle]
+    pub extern "C" fn get_block_logs_bloom() {
+        let mut io = Runtime;
+        let block_height: u64 = io.read_input_borsh().sdk_unwrap();
+        let block_logs = logs_index::get_block_logs(&io, block_height);
+        io.return_output(&block_logs.bloom.0)
+    }
+
     ///
     /// BENCHMARKING METHODS
     ///
@@ -644,11 +987,11 @@ mod contract {
     #[cfg(feature = "evm_bully")]
     #[no_mangle]
     pub extern "C" fn begin_block() {
-        let io = Runtime;
+        let mut io = Runtime;
         let state = engine::get_state(&io).sdk_unwrap();
         require_owner_only(&state, &io.predecessor_account_id());
-        let _args: BeginBlockArgs = io.read_input_borsh().sdk_unwrap();
-        // TODO: https://github.com/aurora-is-near/aurora-engine/issues/2
+        let args: BeginBlockArgs = io.read_input_borsh().sdk_unwrap();
+        engine::begin_block(&mut io, &args);
     }
 
     #[no_mangle]
@@ -1001,6 +1344,11 @@ mod contract {
         }
     }
 
+    fn internal_get_staged_code(io: &Runtime) -> <Runtime as IO>::StorageValue {
+        io.read_storage(&bytes_to_key(KeyPrefix::Config, CODE_KEY))
+            .unwrap_or_else(|| sdk::panic_utf8(b"ERR_NO_UPGRADE"))
+    }
+
     fn require_owner_only(state: &EngineState, predecessor_account_id: &AccountId) {
         if &state.owner_id != predecessor_account_id {
             sdk::panic_utf8(b"ERR_NOT_ALLOWED");
@@ -1010,6 +1358,13 @@ mod contract {
     fn predecessor_address(predecessor_account_id: &AccountId) -> Address {
         near_account_to_evm_address(predecessor_account_id.as_bytes())
     }
+
+    /// Parses the amount returned by `ft_transfer_call`, a JSON string of a decimal number.
+    fn parse_used_amount(value: &[u8]) -> Option<U256> {
+        let value = core::str::from_utf8(value).ok()?;
+        let amount = value.strip_prefix('"')?.strip_suffix('"')?;
+        amount.parse::<u128>().ok().map(U256::from)
+    }
 }
 
 pub trait AuroraState {
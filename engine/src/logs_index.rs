@@ -0,0 +1,246 @@
+//! Index of the logs emitted by the transactions processed by `submit`, backing the
+//! `get_logs` queries.
+//!
+//! Each transaction of a NEAR block is stored under the block height and its position in the
+//! block, with its hash and logs. Each block also has a fixed-size entry with the number of its
+//! transactions and the bloom of all their logs, so the blocks which cannot match a query are
+//! skipped without reading the logs.
+//!
+//! Only the last `RETENTION_BLOCKS` blocks are kept: the blocks are stored in a ring buffer, and
+//! when a block reuses the slot of an older one, the transactions of the latter are removed
+//! along with their receipts (see `engine::get_transaction_receipt`). To bound the work of each
+//! transaction, the old transactions are removed as the new block records its own: each of them
+//! overwrites the old transaction at its position and removes up to `PRUNE_BATCH` more from the
+//! end. What is left when the block ends is removed when its slot is reused in turn.
+
+use crate::bloom::Bloom;
+use crate::engine;
+use crate::parameters::{FilteredLog, GetLogsArgs, ResultLog};
+use crate::prelude::storage::{bytes_to_key, KeyPrefix};
+use crate::prelude::{BorshDeserialize, BorshSerialize, RawH256, Vec, H256};
+use aurora_engine_sdk::io::{StorageIntermediate, IO};
+
+/// Maximum number of blocks a `get_logs` query can span.
+pub const MAX_BLOCK_RANGE: u64 = 1_000;
+
+/// Number of blocks for which the logs and receipts are kept, about a day of NEAR blocks.
+pub const RETENTION_BLOCKS: u64 = 86_400;
+
+/// Maximum number of old transactions removed by the recording of a transaction, on top of
+/// the one it overwrites.
+pub const PRUNE_BATCH: u32 = 4;
+
+/// Sub-prefix of the keys of the logs index of a block.
+const BLOCK_LOGS_ID: u8 = 0x0;
+/// Sub-prefix of the keys of the logs of a transaction.
+const TRANSACTION_LOGS_ID: u8 = 0x1;
+
+/// Transactions of a block, with the bloom of their logs.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct BlockLogs {
+    pub block_height: u64,
+    pub bloom: Bloom,
+    pub transactions_count: u32,
+    /// End of the positions in the slot of the block still holding the transactions of an
+    /// older block, from `transactions_count`.
+    pub stale_count: u32,
+}
+
+/// Logs of the transaction at some position in its block.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TransactionLogs {
+    pub transaction_hash: RawH256,
+    pub logs: Vec<ResultLog>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GetLogsError {
+    /// The first block of the range is after the last one.
+    InvalidBlockRange,
+    /// The range spans more than `MAX_BLOCK_RANGE` blocks.
+    BlockRangeTooLarge,
+}
+
+impl AsRef<[u8]> for GetLogsError {
+    fn as_ref(&self) -> &[u8] {
+        match self {
+            Self::InvalidBlockRange => b"ERR_INVALID_BLOCK_RANGE",
+            Self::BlockRangeTooLarge => b"ERR_BLOCK_RANGE_TOO_LARGE",
+        }
+    }
+}
+
+fn block_logs_key(block_height: u64) -> Vec<u8> {
+    let mut key = [0u8; 9];
+    key[0] = BLOCK_LOGS_ID;
+    key[1..].copy_from_slice(&(block_height % RETENTION_BLOCKS).to_be_bytes());
+    bytes_to_key(KeyPrefix::Logs, &key)
+}
+
+fn transaction_logs_key(block_height: u64, index: u32) -> Vec<u8> {
+    let mut key = [0u8; 13];
+    key[0] = TRANSACTION_LOGS_ID;
+    key[1..9].copy_from_slice(&(block_height % RETENTION_BLOCKS).to_be_bytes());
+    key[9..].copy_from_slice(&index.to_be_bytes());
+    bytes_to_key(KeyPrefix::Logs, &key)
+}
+
+/// Entry of the slot of `block_height`, which may belong to an older block.
+fn read_block_logs<I: IO>(io: &I, block_height: u64) -> Option<BlockLogs> {
+    io.read_storage(&block_logs_key(block_height))
+        .and_then(|bytes| BlockLogs::try_from_slice(&bytes.to_vec()).ok())
+}
+
+/// Logs index of the block at `block_height`, empty if none of its transactions were
+/// recorded or if it is no longer kept.
+pub fn get_block_logs<I: IO>(io: &I, block_height: u64) -> BlockLogs {
+    read_block_logs(io, block_height)
+        .filter(|block_logs| block_logs.block_height == block_height)
+        .unwrap_or(BlockLogs {
+            block_height,
+            ..Default::default()
+        })
+}
+
+/// Logs of the transaction at `index` in the block of `block_logs`.
+pub fn get_transaction_logs<I: IO>(
+    io: &I,
+    block_logs: &BlockLogs,
+    index: u32,
+) -> Option<TransactionLogs> {
+    if index >= block_logs.transactions_count {
+        return None;
+    }
+    io.read_storage(&transaction_logs_key(block_logs.block_height, index))
+        .and_then(|bytes| TransactionLogs::try_from_slice(&bytes.to_vec()).ok())
+}
+
+/// Adds a transaction and its logs to the index of the block at `block_height`, removing some
+/// of the transactions of the older block kept in its slot, if any.
+pub fn record_transaction<I: IO>(
+    io: &mut I,
+    block_height: u64,
+    transaction_hash: &H256,
+    logs: &[ResultLog],
+) {
+    let mut block_logs = match read_block_logs(io, block_height) {
+        Some(block_logs) if block_logs.block_height == block_height => block_logs,
+        // All the positions of the older block are stale, including those it did not clear
+        Some(expired) => BlockLogs {
+            block_height,
+            stale_count: expired.transactions_count.max(expired.stale_count),
+            ..Default::default()
+        },
+        None => BlockLogs {
+            block_height,
+            ..Default::default()
+        },
+    };
+    let index = block_logs.transactions_count;
+    if index < block_logs.stale_count {
+        remove_stale_receipt(io, block_height, index);
+    }
+    let transaction_logs = TransactionLogs {
+        transaction_hash: transaction_hash.0,
+        logs: logs.to_vec(),
+    };
+    io.write_storage(
+        &transaction_logs_key(block_height, index),
+        &transaction_logs.try_to_vec().expect("ERR_SER"),
+    );
+    for log in logs {
+        block_logs.bloom.accrue_log(log);
+    }
+    block_logs.transactions_count += 1;
+
+    for _ in 0..PRUNE_BATCH {
+        if block_logs.stale_count <= block_logs.transactions_count {
+            break;
+        }
+        block_logs.stale_count -= 1;
+        remove_stale_receipt(io, block_height, block_logs.stale_count);
+        io.remove_storage(&transaction_logs_key(block_height, block_logs.stale_count));
+    }
+    if block_logs.stale_count <= block_logs.transactions_count {
+        block_logs.stale_count = 0;
+    }
+
+    io.write_storage(
+        &block_logs_key(block_height),
+        &block_logs.try_to_vec().expect("ERR_SER"),
+    );
+}
+
+/// Removes the receipt of the transaction of an older block stored at `index` in the slot of
+/// `block_height`.
+fn remove_stale_receipt<I: IO>(io: &mut I, block_height: u64, index: u32) {
+    let transaction_logs = io
+        .read_storage(&transaction_logs_key(block_height, index))
+        .and_then(|bytes| TransactionLogs::try_from_slice(&bytes.to_vec()).ok());
+    if let Some(transaction_logs) = transaction_logs {
+        engine::remove_transaction_receipt(io, &H256(transaction_logs.transaction_hash));
+    }
+}
+
+/// Logs of the blocks in the range of `args` matching its address and topics filters, in
+/// the order they were emitted.
+pub fn get_logs<I: IO>(io: &I, args: &GetLogsArgs) -> Result<Vec<FilteredLog>, GetLogsError> {
+    if args.from_block > args.to_block {
+        return Err(GetLogsError::InvalidBlockRange);
+    }
+    if args.to_block - args.from_block >= MAX_BLOCK_RANGE {
+        return Err(GetLogsError::BlockRangeTooLarge);
+    }
+
+    let mut result = Vec::new();
+    for block_height in args.from_block..=args.to_block {
+        let block_logs = get_block_logs(io, block_height);
+        if !bloom_matches(&block_logs.bloom, args) {
+            continue;
+        }
+        for index in 0..block_logs.transactions_count {
+            let transaction_logs = match get_transaction_logs(io, &block_logs, index) {
+                Some(transaction_logs) => transaction_logs,
+                None => continue,
+            };
+            for (log_index, log) in transaction_logs.logs.into_iter().enumerate() {
+                if log_matches(&log, args) {
+                    result.push(FilteredLog {
+                        block_height,
+                        transaction_hash: transaction_logs.transaction_hash,
+                        log_index: log_index as u32,
+                        log,
+                    });
+                }
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// Whether a bloom may contain logs matching the filters of `args`.
+fn bloom_matches(bloom: &Bloom, args: &GetLogsArgs) -> bool {
+    let any_matches = |inputs: &[&[u8]]| {
+        inputs.is_empty() || inputs.iter().any(|input| bloom.contains_input(input))
+    };
+    let addresses: Vec<&[u8]> = args.addresses.iter().map(|a| &a[..]).collect();
+    any_matches(&addresses)
+        && args.topics.iter().all(|values| {
+            let values: Vec<&[u8]> = values.iter().map(|v| &v[..]).collect();
+            any_matches(&values)
+        })
+}
+
+fn log_matches(log: &ResultLog, args: &GetLogsArgs) -> bool {
+    if !args.addresses.is_empty() && !args.addresses.contains(&log.address) {
+        return false;
+    }
+    args.topics
+        .iter()
+        .enumerate()
+        .all(|(position, values)| match log.topics.get(position) {
+            _ if values.is_empty() => true,
+            Some(topic) => values.contains(topic),
+            None => false,
+        })
+}
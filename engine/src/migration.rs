@@ -0,0 +1,241 @@
+//! Versioned migrations of the engine state.
+//!
+//! The version of the state is stored under its own config key. A deployment which predates
+//! this marker is at version 0. After an upgrade, `state_migration` runs the steps of the
+//! registry whose version is above the stored one, in order, storing the new version after
+//! each of them. Running it again once up to date does nothing.
+//!
+//! A step which moves a lot of data can do it in chunks: it returns a cursor when it is not
+//! done, and is called again with that cursor by the next `state_migration` call.
+
+use crate::base_fee::BaseFeeState;
+use crate::engine::{self, EngineState};
+use crate::fee_policy::FeePolicy;
+use crate::prelude::storage::{bytes_to_key, KeyPrefix};
+use crate::prelude::{sdk, AccountId, BorshDeserialize, BorshSerialize, Vec};
+use aurora_engine_sdk::env::Env;
+use aurora_engine_sdk::io::{StorageIntermediate, IO};
+
+/// Key for storing the version of the state.
+const STATE_VERSION_KEY: &[u8; 13] = b"STATE_VERSION";
+
+/// Key for storing the progress of a migration step which is not done.
+const MIGRATION_CURSOR_KEY: &[u8; 16] = b"MIGRATION_CURSOR";
+
+pub type StateVersion = u32;
+
+/// Version from which `EngineState` holds the fee policy and the base fee.
+const FEE_POLICY_AND_BASE_FEE_VERSION: StateVersion = 2;
+
+/// Outcome of running (a chunk of) a migration step.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StepStatus {
+    Done,
+    /// The step needs to be called again, with the given cursor.
+    Pending(Vec<u8>),
+}
+
+/// A migration step, bringing the state to `version`.
+pub struct Migration<I: IO> {
+    pub version: StateVersion,
+    pub name: &'static str,
+    /// Runs the step from the given cursor (`None` the first time it is called).
+    pub run: fn(&mut I, Option<&[u8]>) -> StepStatus,
+}
+
+/// Progress of a pending migration step.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct MigrationCursor {
+    pub version: StateVersion,
+    pub cursor: Vec<u8>,
+}
+
+/// Outcome of a `state_migration` call.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub enum MigrationOutcome {
+    /// The state is at the given version, which is the latest one.
+    Done(StateVersion),
+    /// The state is at the given version and the next step still needs to be resumed.
+    Pending(StateVersion),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrationError {
+    /// Only the owner or the engine itself can run the migrations.
+    NotAllowed,
+    /// The versions of the registry are not strictly increasing.
+    InvalidRegistry,
+    /// The stored version is newer than the code, which can happen after a downgrade.
+    UnknownVersion,
+}
+
+impl AsRef<[u8]> for MigrationError {
+    fn as_ref(&self) -> &[u8] {
+        match self {
+            Self::NotAllowed => b"ERR_NOT_ALLOWED",
+            Self::InvalidRegistry => b"ERR_INVALID_MIGRATION_REGISTRY",
+            Self::UnknownVersion => b"ERR_UNKNOWN_STATE_VERSION",
+        }
+    }
+}
+
+/// Migrations of the engine, in order. New steps are appended with the next version.
+pub fn registry<I: IO>() -> Vec<Migration<I>> {
+    crate::prelude::vec![
+        Migration {
+            version: 1,
+            name: "state_version",
+            // Introduces the version marker itself, there is nothing to move.
+            run: |_, _| StepStatus::Done,
+        },
+        Migration {
+            version: FEE_POLICY_AND_BASE_FEE_VERSION,
+            name: "fee_policy_and_base_fee",
+            run: add_fee_policy_and_base_fee,
+        },
+    ]
+}
+
+/// Layout of `EngineState` before the fee policy and the base fee were added to it.
+#[derive(BorshDeserialize)]
+struct EngineStateV1 {
+    chain_id: [u8; 32],
+    owner_id: AccountId,
+    bridge_prover_id: AccountId,
+    upgrade_delay_blocks: u64,
+}
+
+fn get_state_v1<I: IO>(io: &I) -> Option<EngineStateV1> {
+    io.read_storage(&bytes_to_key(KeyPrefix::Config, engine::STATE_KEY))
+        .and_then(|bytes| EngineStateV1::try_from_slice(&bytes.to_vec()).ok())
+}
+
+/// Owner of the engine, read with the layout of `EngineState` at the stored version, since
+/// the state is only rewritten by the `fee_policy_and_base_fee` step.
+fn get_owner_id<I: IO>(io: &I) -> Option<AccountId> {
+    if get_version(io) < FEE_POLICY_AND_BASE_FEE_VERSION {
+        get_state_v1(io).map(|state| state.owner_id)
+    } else {
+        engine::get_state(io).ok().map(|state| state.owner_id)
+    }
+}
+
+/// Rewrites the engine state with the default fee policy, which keeps paying the whole
+/// priority fee to the relayer, and the default base fee, fixed at zero.
+fn add_fee_policy_and_base_fee<I: IO>(io: &mut I, _cursor: Option<&[u8]>) -> StepStatus {
+    if let Some(legacy_state) = get_state_v1(io) {
+        let state = EngineState {
+            chain_id: legacy_state.chain_id,
+            owner_id: legacy_state.owner_id,
+            bridge_prover_id: legacy_state.bridge_prover_id,
+            upgrade_delay_blocks: legacy_state.upgrade_delay_blocks,
+            fee_policy: FeePolicy::default(),
+            base_fee: BaseFeeState::default(),
+        };
+        engine::set_state(io, state);
+    }
+    StepStatus::Done
+}
+
+/// Version of the state the code of the engine works with.
+pub fn latest_version<I: IO>(migrations: &[Migration<I>]) -> StateVersion {
+    migrations.last().map(|m| m.version).unwrap_or(0)
+}
+
+pub fn get_version<I: IO>(io: &I) -> StateVersion {
+    io.read_storage(&bytes_to_key(KeyPrefix::Config, STATE_VERSION_KEY))
+        .map(|value| {
+            let mut bytes = [0u8; 4];
+            value.copy_to_slice(&mut bytes);
+            StateVersion::from_le_bytes(bytes)
+        })
+        .unwrap_or(0)
+}
+
+pub fn set_version<I: IO>(io: &mut I, version: StateVersion) {
+    io.write_storage(
+        &bytes_to_key(KeyPrefix::Config, STATE_VERSION_KEY),
+        &version.to_le_bytes(),
+    );
+}
+
+pub fn get_cursor<I: IO>(io: &I) -> Option<MigrationCursor> {
+    io.read_storage(&bytes_to_key(KeyPrefix::Config, MIGRATION_CURSOR_KEY))
+        .and_then(|bytes| MigrationCursor::try_from_slice(&bytes.to_vec()).ok())
+}
+
+fn set_cursor<I: IO>(io: &mut I, cursor: &MigrationCursor) {
+    io.write_storage(
+        &bytes_to_key(KeyPrefix::Config, MIGRATION_CURSOR_KEY),
+        &cursor.try_to_vec().expect("ERR_SER"),
+    );
+}
+
+fn remove_cursor<I: IO>(io: &mut I) {
+    io.remove_storage(&bytes_to_key(KeyPrefix::Config, MIGRATION_CURSOR_KEY));
+}
+
+/// Runs the steps of `migrations` above the stored version, until one of them is pending.
+pub fn migrate<I: IO>(
+    io: &mut I,
+    migrations: &[Migration<I>],
+) -> Result<MigrationOutcome, MigrationError> {
+    if migrations
+        .windows(2)
+        .any(|pair| pair[0].version >= pair[1].version)
+    {
+        return Err(MigrationError::InvalidRegistry);
+    }
+    let mut version = get_version(io);
+    if version > latest_version(migrations) {
+        return Err(MigrationError::UnknownVersion);
+    }
+
+    for migration in migrations.iter().filter(|m| m.version > version) {
+        // A cursor left by another version (e.g. a step removed by a later upgrade) is stale.
+        let cursor = get_cursor(io)
+            .filter(|cursor| cursor.version == migration.version)
+            .map(|cursor| cursor.cursor);
+        match (migration.run)(io, cursor.as_deref()) {
+            StepStatus::Done => {
+                sdk::log!(crate::prelude::format!(
+                    "Migrated the state to version {} ({})",
+                    migration.version,
+                    migration.name
+                )
+                .as_str());
+                remove_cursor(io);
+                version = migration.version;
+                set_version(io, version);
+            }
+            StepStatus::Pending(cursor) => {
+                set_cursor(
+                    io,
+                    &MigrationCursor {
+                        version: migration.version,
+                        cursor,
+                    },
+                );
+                return Ok(MigrationOutcome::Pending(version));
+            }
+        }
+    }
+    Ok(MigrationOutcome::Done(version))
+}
+
+/// Entry point of the `state_migration` method: it is called by the engine itself right after
+/// an upgrade is deployed, and can then be called by the owner to resume a pending step.
+pub fn state_migration<I: IO, E: Env>(
+    io: &mut I,
+    env: &E,
+    migrations: &[Migration<I>],
+) -> Result<MigrationOutcome, MigrationError> {
+    let predecessor_account_id = env.predecessor_account_id();
+    let is_owner = get_owner_id(io)
+        .map(|owner_id| owner_id == predecessor_account_id)
+        .unwrap_or(false);
+    if !is_owner && env.assert_private_call().is_err() {
+        return Err(MigrationError::NotAllowed);
+    }
+    migrate(io, migrations)
+}
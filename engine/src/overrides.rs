@@ -0,0 +1,111 @@
+//! View calls with state and block overrides, as geth's `eth_call` with a state override set.
+//!
+//! The overrides are written to an `OverlayIO`, whose changes are discarded after the call,
+//! so the view call sees them while the storage itself is never modified.
+
+use crate::engine::{self, Engine, EngineErrorKind, EngineState};
+use crate::parameters::{
+    AccountOverride, BlockOverrides, StorageOverride, TransactionStatus, ViewCallWithOverridesArgs,
+};
+use crate::prelude::{AccountId, Address, Wei, H256, U256};
+use aurora_engine_sdk::env::{Env, Timestamp};
+use aurora_engine_sdk::io::{OverlayBuffer, OverlayIO, IO};
+
+/// Runs a view call on top of the given overrides.
+pub fn view_with_overrides<I: IO + Copy, E: Env>(
+    io: I,
+    env: &E,
+    state: EngineState,
+    current_account_id: AccountId,
+    args: ViewCallWithOverridesArgs,
+) -> Result<TransactionStatus, EngineErrorKind> {
+    let buffer = OverlayBuffer::default();
+    let mut overlay = OverlayIO::new(io, &buffer);
+    for (address, account_override) in args.state_overrides {
+        apply_account_override(&mut overlay, &Address(address), account_override);
+    }
+    let env = OverrideEnv {
+        env,
+        overrides: args.block_overrides,
+    };
+
+    let call = args.call;
+    let origin = Address(call.sender);
+    let engine = Engine::new_with_state(state, origin, current_account_id, overlay, &env);
+    let result = engine.view(
+        origin,
+        Address(call.address),
+        Wei::new(U256::from_big_endian(&call.amount)),
+        call.input,
+        u64::MAX,
+    );
+    overlay.discard();
+    result
+}
+
+fn apply_account_override<I: IO>(io: &mut I, address: &Address, account: AccountOverride) {
+    if let Some(balance) = account.balance {
+        engine::set_balance(io, address, &Wei::new(U256::from(balance)));
+    }
+    if let Some(nonce) = account.nonce {
+        engine::set_nonce(io, address, &U256::from(nonce));
+    }
+    if let Some(code) = account.code {
+        engine::set_code(io, address, &code);
+    }
+    let mut generation = engine::get_generation(io, address);
+    let slots = match account.storage {
+        None => return,
+        Some(StorageOverride::Diff(slots)) => slots,
+        Some(StorageOverride::Replace(slots)) => {
+            // The slots of the previous generation are no longer visible.
+            generation += 1;
+            engine::set_generation(io, address, generation);
+            slots
+        }
+    };
+    for (key, value) in slots {
+        engine::set_storage(io, address, &H256(key), &H256(value), generation);
+    }
+}
+
+/// Environment of a view call, with the block overrides applied.
+struct OverrideEnv<'a, E> {
+    env: &'a E,
+    overrides: BlockOverrides,
+}
+
+impl<'a, E: Env> Env for OverrideEnv<'a, E> {
+    fn signer_account_id(&self) -> AccountId {
+        self.env.signer_account_id()
+    }
+
+    fn current_account_id(&self) -> AccountId {
+        self.env.current_account_id()
+    }
+
+    fn predecessor_account_id(&self) -> AccountId {
+        self.env.predecessor_account_id()
+    }
+
+    fn block_height(&self) -> u64 {
+        self.overrides
+            .number
+            .unwrap_or_else(|| self.env.block_height())
+    }
+
+    fn block_timestamp(&self) -> Timestamp {
+        match self.overrides.timestamp {
+            Some(secs) => Timestamp::new(secs.saturating_mul(1_000_000_000)),
+            None => self.env.block_timestamp(),
+        }
+    }
+
+    fn attached_deposit(&self) -> u128 {
+        self.env.attached_deposit()
+    }
+
+    fn random_seed(&self) -> H256 {
+        self.env.random_seed()
+    }
+}
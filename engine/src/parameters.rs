@@ -1,7 +1,9 @@
 use crate::admin_controlled::PausedMask;
+use crate::bloom::Bloom;
 use crate::fungible_token::FungibleTokenMetadata;
 use crate::json::{JsonError, JsonValue};
 use crate::prelude::account_id::AccountId;
+use crate::prelude::parameters::CrossContractCallPermission;
 use crate::prelude::{
     format, Balance, BorshDeserialize, BorshSerialize, EthAddress, RawAddress, RawH256, RawU256,
     SdkUnwrap, String, ToString, TryFrom, Vec, Wei, U256,
@@ -29,6 +31,43 @@ pub struct NewCallArgs {
     pub upgrade_delay_blocks: u64,
 }
 
+/// Borsh-encoded parameters for the `set_cross_contract_call_permission` function.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SetCrossContractCallPermissionArgs {
+    pub address: RawAddress,
+    /// Use `None` to revoke the permission.
+    pub permission: Option<CrossContractCallPermission>,
+}
+
+/// Borsh-encoded parameters for the `stage_upgrade` function.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct StageUpgradeArgs {
+    /// Wasm code of the upgrade.
+    pub code: Vec<u8>,
+    /// Whether to check that the code is a wasm module exporting the methods required by the
+    /// upgrade process.
+    pub validate: bool,
+}
+
+/// Borsh-encoded parameters for the `deploy_upgrade` function.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct DeployUpgradeArgs {
+    /// Whether to check the staged code before deploying it, as `stage_upgrade` does.
+    pub validate: bool,
+}
+
+/// Borsh-encoded parameters for the `set_base_fee` function.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SetBaseFeeArgs {
+    /// Base fee per gas from the current block on.
+    pub base_fee_per_gas: RawU256,
+    /// Gas per block targeted by the EIP-1559 update rule.
+    /// Use `None` to keep the base fee fixed.
+    pub gas_target: Option<u64>,
+    /// Lowest value the base fee can be lowered to by the update rule.
+    pub min_base_fee_per_gas: RawU256,
+}
+
 /// Borsh-encoded parameters for the `meta_call` function.
 #[derive(Debug, BorshSerialize, BorshDeserialize)]
 pub struct MetaCallArgs {
@@ -44,7 +83,7 @@ pub struct MetaCallArgs {
 }
 
 /// Borsh-encoded log for use in a `SubmitResult`.
-#[derive(Debug, BorshSerialize, BorshDeserialize)]
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize, PartialEq, Eq)]
 pub struct ResultLog {
     pub address: RawAddress,
     pub topics: Vec<RawU256>,
@@ -66,6 +105,49 @@ impl From<Log> for ResultLog {
     }
 }
 
+/// Compact receipt of a transaction processed by `submit`, borsh-encoded output of the
+/// `get_transaction_receipt` function.
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize, PartialEq, Eq)]
+pub struct TransactionReceipt {
+    /// Keccak hash of the signed transaction bytes.
+    pub transaction_hash: RawH256,
+    pub from: RawAddress,
+    /// `None` for a contract deployment.
+    pub to: Option<RawAddress>,
+    /// Whether the transaction succeeded (status 1 of EIP-658).
+    pub status: bool,
+    pub gas_used: u64,
+    pub effective_gas_price: RawU256,
+    /// Address of the contract created by a successful deployment.
+    pub contract_address: Option<RawAddress>,
+    pub logs_bloom: Bloom,
+    /// Height of the NEAR block in which the transaction was processed.
+    pub block_height: u64,
+}
+
+/// Borsh-encoded parameters for the `get_logs` function.
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize, PartialEq, Eq)]
+pub struct GetLogsArgs {
+    /// First NEAR block height of the range, inclusive.
+    pub from_block: u64,
+    /// Last NEAR block height of the range, inclusive.
+    pub to_block: u64,
+    /// Addresses of the contracts emitting the logs, any address when empty.
+    pub addresses: Vec<RawAddress>,
+    /// Accepted values of each topic, by position. An empty list accepts any value.
+    pub topics: Vec<Vec<RawU256>>,
+}
+
+/// A log matching a `get_logs` query, borsh-encoded in the output of the `get_logs` function.
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize, PartialEq, Eq)]
+pub struct FilteredLog {
+    pub block_height: u64,
+    pub transaction_hash: RawH256,
+    /// Position of the log among the ones emitted by the transaction.
+    pub log_index: u32,
+    pub log: ResultLog,
+}
+
 /// The status of a transaction.
 #[derive(Debug, BorshSerialize, BorshDeserialize, PartialEq, Eq)]
 pub enum TransactionStatus {
@@ -75,6 +157,9 @@ pub enum TransactionStatus {
     OutOfFund,
     OutOfOffset,
     CallTooDeep,
+    /// The transaction nonce is ahead of the sender nonce: the transaction was queued and
+    /// will be executed once the transactions with the missing nonces are submitted.
+    Queued,
 }
 
 impl TransactionStatus {
@@ -103,10 +188,36 @@ impl AsRef<[u8]> for TransactionStatus {
             Self::OutOfGas => b"ERR_OUT_OF_GAS",
             Self::OutOfOffset => b"ERR_OUT_OF_OFFSET",
             Self::CallTooDeep => b"ERR_CALL_TOO_DEEP",
+            Self::Queued => b"QUEUED",
         }
     }
 }
 
+/// A transaction waiting in the queue of its sender, borsh-encoded in the output of the
+/// `get_pending_transactions` function.
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize, PartialEq, Eq)]
+pub struct PendingTransaction {
+    pub nonce: u64,
+    /// Block height at which the transaction was queued, for its expiry.
+    pub queued_at: u64,
+    pub max_fee_per_gas: RawU256,
+    pub max_priority_fee_per_gas: RawU256,
+    /// Most ETH the transaction can spend: its gas limit times its max fee per gas, plus its
+    /// value.
+    pub max_cost: RawU256,
+    /// The signed transaction bytes, as given to `submit`.
+    pub transaction: Vec<u8>,
+}
+
+/// Borsh-encoded outcome of one of the transactions of a `submit_batch` call.
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+pub enum BatchTransactionResult {
+    /// The transaction was executed, successfully or not (e.g. reverted).
+    Executed(SubmitResult),
+    /// The transaction was rejected (e.g. because of its nonce) and has no effect.
+    Failed(String),
+}
+
 /// Borsh-encoded parameters for the `call`, `call_with_args`, `deploy_code`,
 /// and `deploy_with_input` methods.
 #[derive(Debug, BorshSerialize, BorshDeserialize)]
@@ -233,6 +344,95 @@ pub struct ViewCallArgs {
     pub input: Vec<u8>,
 }
 
+/// Deserialized values from bytes to current or legacy Borsh-encoded parameters
+/// for passing to the engine `view` function, and to provide backward type compatibility
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq, Eq)]
+pub enum ViewArgs {
+    V2(ViewCallWithOverridesArgs),
+    V1(ViewCallArgs),
+}
+
+impl ViewArgs {
+    pub fn deserialize(bytes: &[u8]) -> Option<Self> {
+        if let Ok(value) = Self::try_from_slice(bytes) {
+            Some(value)
+        } else if let Ok(value) = ViewCallArgs::try_from_slice(bytes) {
+            Some(Self::V1(value))
+        } else {
+            None
+        }
+    }
+}
+
+/// A view call with state and block overrides, as geth's `eth_call` with a state override set.
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq, Eq)]
+pub struct ViewCallWithOverridesArgs {
+    pub call: ViewCallArgs,
+    pub state_overrides: Vec<(RawAddress, AccountOverride)>,
+    pub block_overrides: BlockOverrides,
+}
+
+/// Replacement of the state of an account during a view call.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct AccountOverride {
+    pub balance: Option<RawU256>,
+    pub nonce: Option<u64>,
+    pub code: Option<Vec<u8>>,
+    pub storage: Option<StorageOverride>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub enum StorageOverride {
+    /// Replaces the whole storage of the account by the given slots (geth's `state`).
+    Replace(Vec<(RawH256, RawH256)>),
+    /// Replaces the given slots only (geth's `stateDiff`).
+    Diff(Vec<(RawH256, RawH256)>),
+}
+
+/// Replacement of the block context during a view call.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct BlockOverrides {
+    pub number: Option<u64>,
+    /// Timestamp in seconds since the Unix epoch.
+    pub timestamp: Option<u64>,
+}
+
+/// Borsh-encoded parameters for the `estimate_gas` function.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Eq, PartialEq)]
+pub struct EstimateGasArgs {
+    pub sender: RawAddress,
+    /// `None` to estimate the gas of a contract deployment.
+    pub to: Option<RawAddress>,
+    pub value: RawU256,
+    pub data: Vec<u8>,
+    /// See EIP-2930.
+    pub access_list: Vec<(RawAddress, Vec<RawH256>)>,
+    /// Highest gas limit to try, `None` to use the block gas limit.
+    pub gas_limit: Option<u64>,
+}
+
+impl From<ViewCallArgs> for EstimateGasArgs {
+    fn from(args: ViewCallArgs) -> Self {
+        Self {
+            sender: args.sender,
+            to: Some(args.address),
+            value: args.amount,
+            data: args.input,
+            access_list: Vec::new(),
+            gas_limit: None,
+        }
+    }
+}
+
+/// Borsh-encoded result of the `estimate_gas` function.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Eq, PartialEq)]
+pub struct EstimateGasResult {
+    /// The minimal gas limit for which the execution succeeds, or the gas used with the
+    /// maximum gas limit if it does not succeed.
+    pub gas: u64,
+    pub status: TransactionStatus,
+}
+
 /// Borsh-encoded parameters for `deploy_erc20_token` function.
 #[derive(BorshSerialize, BorshDeserialize, Debug, Eq, PartialEq)]
 pub struct DeployErc20TokenArgs {
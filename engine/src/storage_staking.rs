@@ -0,0 +1,193 @@
+//! Storage staking: the EVM account paying for a transaction (the signer for `submit`, the
+//! caller for `call` and `deploy_code`) stakes ETH for each storage entry the transaction
+//! creates or resizes. The calls the engine makes itself, from system addresses, are exempt
+//! (see `Engine::exempt_from_storage_staking`).
+//!
+//! The stake of an entry is recorded under its key along with the account which paid it, at the
+//! price of the time. When the entry is resized or removed, that exact amount goes back to that
+//! account, whoever changes the entry, and the new size is staked for by the payer of the
+//! change. The staked ETH is moved out of the balances into a pool tracked under a config key:
+//! it is still part of the ETH supply on Aurora.
+
+use crate::engine::{get_balance, set_balance};
+use crate::prelude::storage::{bytes_to_key, KeyPrefix};
+use crate::prelude::{
+    u256_to_arr, Address, BTreeMap, BorshDeserialize, BorshSerialize, RawAddress, RawU256, Vec,
+    Wei, U256,
+};
+use aurora_engine_sdk::io::{StorageIntermediate, IO};
+
+/// Key for storing the price of a byte of storage.
+const STORAGE_PRICE_KEY: &[u8; 13] = b"STORAGE_PRICE";
+
+/// Key for storing the amount of ETH staked for storage.
+const STORAGE_STAKE_KEY: &[u8; 13] = b"STORAGE_STAKE";
+
+/// Bytes accounted for each storage entry on top of its key and value, as NEAR does for the
+/// storage staking of a contract.
+pub const STORAGE_ENTRY_OVERHEAD: u64 = 40;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageStakingError {
+    /// The payer does not have enough ETH to stake for the storage used.
+    InsufficientFunds,
+    /// Overflow computing the ETH to stake.
+    Overflow,
+}
+
+impl StorageStakingError {
+    pub fn to_str(&self) -> &str {
+        match self {
+            Self::InsufficientFunds => "ERR_STORAGE_STAKE_NOT_COVERED",
+            Self::Overflow => "ERR_STORAGE_STAKE_OVERFLOW",
+        }
+    }
+}
+
+impl AsRef<[u8]> for StorageStakingError {
+    fn as_ref(&self) -> &[u8] {
+        self.to_str().as_bytes()
+    }
+}
+
+/// Stake of a storage entry.
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+struct StorageStake {
+    /// Account refunded when the entry is resized or removed.
+    payer: RawAddress,
+    /// ETH staked, at the price of the time.
+    amount: RawU256,
+}
+
+/// Storage entries changed by a transaction, with their size before the change. An entry must
+/// be recorded before it is first changed.
+#[derive(Debug, Default)]
+pub struct StorageChanges(BTreeMap<Vec<u8>, u64>);
+
+impl StorageChanges {
+    pub fn record<I: IO>(&mut self, io: &I, key: &[u8]) {
+        if !self.0.contains_key(key) {
+            self.0.insert(key.to_vec(), entry_size(io, key));
+        }
+    }
+}
+
+/// ETH moved between the accounts and the storage stake by a transaction.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct StorageStakingResult {
+    pub staked: Wei,
+    pub refunded: Wei,
+}
+
+/// Price of a byte of storage, in wei. Storage is free by default.
+pub fn get_price_per_byte<I: IO>(io: &I) -> U256 {
+    read_u256(io, STORAGE_PRICE_KEY)
+}
+
+pub fn set_price_per_byte<I: IO>(io: &mut I, price: &U256) {
+    io.write_storage(
+        &bytes_to_key(KeyPrefix::Config, STORAGE_PRICE_KEY),
+        &u256_to_arr(price),
+    );
+}
+
+/// Total ETH staked for storage.
+pub fn get_total_stake<I: IO>(io: &I) -> Wei {
+    Wei::new(read_u256(io, STORAGE_STAKE_KEY))
+}
+
+fn set_total_stake<I: IO>(io: &mut I, stake: &Wei) {
+    io.write_storage(
+        &bytes_to_key(KeyPrefix::Config, STORAGE_STAKE_KEY),
+        &stake.to_bytes(),
+    );
+}
+
+fn read_u256<I: IO>(io: &I, key: &[u8]) -> U256 {
+    io.read_storage(&bytes_to_key(KeyPrefix::Config, key))
+        .map(|value| {
+            let mut bytes: RawU256 = [0u8; 32];
+            value.copy_to_slice(&mut bytes);
+            U256::from_big_endian(&bytes)
+        })
+        .unwrap_or_default()
+}
+
+fn stake_key(key: &[u8]) -> Vec<u8> {
+    bytes_to_key(KeyPrefix::StorageStake, key)
+}
+
+fn take_stake<I: IO>(io: &mut I, key: &[u8]) -> Option<StorageStake> {
+    io.remove_storage(&stake_key(key))
+        .and_then(|bytes| StorageStake::try_from_slice(&bytes.to_vec()).ok())
+}
+
+/// Bytes accounted for the entry stored at `key`, 0 if there is none.
+fn entry_size<I: IO>(io: &I, key: &[u8]) -> u64 {
+    io.read_storage(key)
+        .map(|value| (key.len() + value.len()) as u64 + STORAGE_ENTRY_OVERHEAD)
+        .unwrap_or(0)
+}
+
+/// Settles the stakes of the storage entries in `changes`: the stake of each entry whose size
+/// changed is refunded to the account which paid it, and `payer` stakes for the new size at
+/// the current price. Nothing is staked without a payer, for the exempt calls.
+pub fn settle<I: IO>(
+    io: &mut I,
+    payer: Option<&Address>,
+    changes: StorageChanges,
+) -> Result<StorageStakingResult, StorageStakingError> {
+    let price = get_price_per_byte(io);
+    let mut refunded = Wei::zero();
+    let mut resized = Vec::new();
+    for (key, old_size) in changes.0 {
+        let new_size = entry_size(io, &key);
+        if new_size == old_size {
+            continue;
+        }
+        if let Some(stake) = take_stake(io, &key) {
+            let amount = Wei::new(U256::from(stake.amount));
+            let staker = Address(stake.payer);
+            let new_balance = get_balance(io, &staker)
+                .checked_add(amount)
+                .ok_or(StorageStakingError::Overflow)?;
+            set_balance(io, &staker, &new_balance);
+            refunded = refunded
+                .checked_add(amount)
+                .ok_or(StorageStakingError::Overflow)?;
+        }
+        if new_size > 0 {
+            resized.push((key, new_size));
+        }
+    }
+
+    let mut staked = Wei::zero();
+    if let Some(payer) = payer.filter(|_| !price.is_zero()) {
+        for (key, size) in resized {
+            let amount = price
+                .checked_mul(U256::from(size))
+                .map(Wei::new)
+                .ok_or(StorageStakingError::Overflow)?;
+            let new_balance = get_balance(io, payer)
+                .checked_sub(amount)
+                .ok_or(StorageStakingError::InsufficientFunds)?;
+            set_balance(io, payer, &new_balance);
+            let stake = StorageStake {
+                payer: payer.0,
+                amount: u256_to_arr(&amount.raw()),
+            };
+            io.write_storage(&stake_key(&key), &stake.try_to_vec().expect("ERR_SER"));
+            staked = staked
+                .checked_add(amount)
+                .ok_or(StorageStakingError::Overflow)?;
+        }
+    }
+
+    // The pool holds the sum of the recorded stakes, so it covers every refund.
+    let total_stake = get_total_stake(io)
+        .checked_add(staked)
+        .and_then(|total| total.checked_sub(refunded))
+        .ok_or(StorageStakingError::Overflow)?;
+    set_total_stake(io, &total_stake);
+    Ok(StorageStakingResult { staked, refunded })
+}
@@ -0,0 +1,837 @@
+//! Execution tracing, enabled with the `tracing` feature.
+//!
+//! While executing a transaction SputnikVM emits events from the executor (calls and
+//! creates), the runtime (one event per opcode) and the gasometer. The listeners in this
+//! module turn those events into per-opcode step traces (geth `structLogs` / EIP-3155)
+//! or into a call tree (geth `callTracer`).
+
+use crate::engine::{self, EngineResult, EngineState};
+use crate::json::JsonValue;
+use crate::parameters::{SubmitResult, TransactionStatus};
+use crate::prelude::{format, AccountId, Address, BTreeMap, String, ToString, Vec, H256, U256};
+use aurora_engine_sdk::env::Env;
+use aurora_engine_sdk::io::{OverlayBuffer, OverlayIO, IO};
+use aurora_engine_sdk::promise::{PromiseBuffer, PromiseHandler};
+use core::cell::RefCell;
+use evm::tracing as evm_tracing;
+use evm::{CreateScheme, ExitReason};
+use evm_gasometer::tracing as gas_tracing;
+use evm_runtime::tracing as runtime_tracing;
+
+#[cfg(not(feature = "std"))]
+use alloc::rc::Rc;
+#[cfg(feature = "std")]
+use std::rc::Rc;
+
+/// Listener for all the events emitted by SputnikVM during a traced execution.
+pub trait TraceListener:
+    evm_tracing::EventListener + runtime_tracing::EventListener + gas_tracing::EventListener
+{
+}
+
+impl<T> TraceListener for T where
+    T: evm_tracing::EventListener + runtime_tracing::EventListener + gas_tracing::EventListener
+{
+}
+
+/// Runs `f`, forwarding all the events emitted by the EVM to `listener`.
+/// The listener is given back together with the result of `f`.
+pub fn traced_call<T, R, F>(listener: T, f: F) -> (T, R)
+where
+    T: TraceListener + 'static,
+    F: FnOnce() -> R,
+{
+    let shared = SharedListener(Rc::new(RefCell::new(listener)));
+    let mut gas_listener = shared.clone();
+    let mut runtime_listener = shared.clone();
+    let mut evm_listener = shared.clone();
+
+    let result = gas_tracing::using(&mut gas_listener, || {
+        runtime_tracing::using(&mut runtime_listener, || {
+            evm_tracing::using(&mut evm_listener, f)
+        })
+    });
+
+    drop((gas_listener, runtime_listener, evm_listener));
+    let listener = Rc::try_unwrap(shared.0)
+        .ok()
+        .expect("ERR_TRACE_LISTENER_IN_USE")
+        .into_inner();
+    (listener, result)
+}
+
+/// Executes a signed Ethereum transaction as `submit` does, forwarding the events of the EVM
+/// to `listener`. The execution runs on top of an overlay which is discarded afterwards, so the
+/// storage is left unchanged and the promises are never scheduled. Unlike `submit`, the queued
+/// transactions of the sender are not executed.
+#[allow(clippy::too_many_arguments)]
+pub fn trace_transaction<I, E, P, T>(
+    io: I,
+    env: &E,
+    transaction_bytes: &[u8],
+    state: EngineState,
+    current_account_id: AccountId,
+    relayer_address: Address,
+    handler: &mut P,
+    listener: T,
+) -> (T, EngineResult<SubmitResult>)
+where
+    I: IO + Copy,
+    E: Env,
+    P: PromiseHandler,
+    T: TraceListener + 'static,
+{
+    let buffer = OverlayBuffer::default();
+    let overlay = OverlayIO::new(io, &buffer);
+    let mut promises = PromiseBuffer::new(handler);
+    let (listener, result) = traced_call(listener, || {
+        engine::submit_transaction(
+            overlay,
+            env,
+            transaction_bytes,
+            state,
+            current_account_id,
+            relayer_address,
+            &mut promises,
+        )
+    });
+    overlay.discard();
+    (listener, result.map(|(_, result)| result))
+}
+
+/// The EVM needs a separate `&mut` listener for each of its event sources,
+/// so a single listener is shared between them.
+struct SharedListener<T>(Rc<RefCell<T>>);
+
+impl<T> Clone for SharedListener<T> {
+    fn clone(&self) -> Self {
+        Self(Rc::clone(&self.0))
+    }
+}
+
+impl<T: evm_tracing::EventListener> evm_tracing::EventListener for SharedListener<T> {
+    fn event(&mut self, event: evm_tracing::Event) {
+        self.0.borrow_mut().event(event)
+    }
+}
+
+impl<T: runtime_tracing::EventListener> runtime_tracing::EventListener for SharedListener<T> {
+    fn event(&mut self, event: runtime_tracing::Event) {
+        self.0.borrow_mut().event(event)
+    }
+}
+
+impl<T: gas_tracing::EventListener> gas_tracing::EventListener for SharedListener<T> {
+    fn event(&mut self, event: gas_tracing::Event) {
+        self.0.borrow_mut().event(event)
+    }
+}
+
+/// Options of the `StepTracer`, with the same defaults as geth.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct StepTracerConfig {
+    pub enable_memory: bool,
+    pub disable_stack: bool,
+    pub disable_storage: bool,
+}
+
+/// Execution of a single opcode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StepTrace {
+    pub pc: usize,
+    pub opcode: u8,
+    /// Gas available before executing the opcode.
+    pub gas: u64,
+    pub gas_cost: u64,
+    /// Call depth, starting at 1 for the transaction itself.
+    pub depth: usize,
+    pub memory_size: usize,
+    pub stack: Option<Vec<H256>>,
+    pub memory: Option<Vec<u8>>,
+    /// Storage slots of the executing contract accessed so far.
+    pub storage: Option<BTreeMap<H256, H256>>,
+}
+
+impl StepTrace {
+    /// Entry of the geth `structLogs` array.
+    pub fn to_struct_log(&self) -> JsonValue {
+        let mut log = BTreeMap::new();
+        log.insert("pc".to_string(), JsonValue::U64(self.pc as u64));
+        log.insert(
+            "op".to_string(),
+            JsonValue::String(opcode_name(self.opcode).to_string()),
+        );
+        log.insert("gas".to_string(), JsonValue::U64(self.gas));
+        log.insert("gasCost".to_string(), JsonValue::U64(self.gas_cost));
+        log.insert("depth".to_string(), JsonValue::U64(self.depth as u64));
+        if let Some(stack) = &self.stack {
+            let stack = stack
+                .iter()
+                .map(|word| JsonValue::String(hex::encode(word.as_bytes())))
+                .collect();
+            log.insert("stack".to_string(), JsonValue::Array(stack));
+        }
+        if let Some(memory) = &self.memory {
+            let memory = memory
+                .chunks(32)
+                .map(|word| JsonValue::String(hex::encode(word)))
+                .collect();
+            log.insert("memory".to_string(), JsonValue::Array(memory));
+        }
+        if let Some(storage) = &self.storage {
+            let storage = storage
+                .iter()
+                .map(|(key, value)| {
+                    (
+                        hex::encode(key.as_bytes()),
+                        JsonValue::String(hex::encode(value.as_bytes())),
+                    )
+                })
+                .collect();
+            log.insert("storage".to_string(), JsonValue::Object(storage));
+        }
+        JsonValue::Object(log)
+    }
+
+    /// Line of an EIP-3155 trace.
+    pub fn to_eip3155(&self) -> JsonValue {
+        let mut line = BTreeMap::new();
+        line.insert("pc".to_string(), JsonValue::U64(self.pc as u64));
+        line.insert("op".to_string(), JsonValue::U64(self.opcode as u64));
+        line.insert("gas".to_string(), JsonValue::String(hex_u64(self.gas)));
+        line.insert(
+            "gasCost".to_string(),
+            JsonValue::String(hex_u64(self.gas_cost)),
+        );
+        line.insert(
+            "memSize".to_string(),
+            JsonValue::U64(self.memory_size as u64),
+        );
+        line.insert("depth".to_string(), JsonValue::U64(self.depth as u64));
+        line.insert(
+            "opName".to_string(),
+            JsonValue::String(opcode_name(self.opcode).to_string()),
+        );
+        if let Some(stack) = &self.stack {
+            let stack = stack
+                .iter()
+                .map(|word| {
+                    JsonValue::String(format!("{:#x}", U256::from_big_endian(word.as_bytes())))
+                })
+                .collect();
+            line.insert("stack".to_string(), JsonValue::Array(stack));
+        }
+        if let Some(memory) = &self.memory {
+            line.insert(
+                "memory".to_string(),
+                JsonValue::String(format!("0x{}", hex::encode(memory))),
+            );
+        }
+        JsonValue::Object(line)
+    }
+}
+
+/// Per-opcode trace of a transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransactionTrace {
+    pub gas: u64,
+    pub failed: bool,
+    pub return_value: Vec<u8>,
+    pub struct_logs: Vec<StepTrace>,
+}
+
+impl TransactionTrace {
+    /// The trace in the format returned by geth's `debug_traceTransaction`.
+    pub fn to_json(&self) -> JsonValue {
+        let mut trace = BTreeMap::new();
+        trace.insert("gas".to_string(), JsonValue::U64(self.gas));
+        trace.insert("failed".to_string(), JsonValue::Bool(self.failed));
+        trace.insert(
+            "returnValue".to_string(),
+            JsonValue::String(hex::encode(&self.return_value)),
+        );
+        let struct_logs = self
+            .struct_logs
+            .iter()
+            .map(StepTrace::to_struct_log)
+            .collect();
+        trace.insert("structLogs".to_string(), JsonValue::Array(struct_logs));
+        JsonValue::Object(trace)
+    }
+
+    /// The trace as EIP-3155 lines: one line per step followed by a summary line.
+    pub fn to_eip3155(&self) -> Vec<JsonValue> {
+        let mut lines: Vec<JsonValue> =
+            self.struct_logs.iter().map(StepTrace::to_eip3155).collect();
+        let mut summary = BTreeMap::new();
+        summary.insert(
+            "output".to_string(),
+            JsonValue::String(format!("0x{}", hex::encode(&self.return_value))),
+        );
+        summary.insert("gasUsed".to_string(), JsonValue::String(hex_u64(self.gas)));
+        summary.insert("pass".to_string(), JsonValue::Bool(!self.failed));
+        lines.push(JsonValue::Object(summary));
+        lines
+    }
+}
+
+/// Records a `StepTrace` for every executed opcode.
+#[derive(Debug, Default)]
+pub struct StepTracer {
+    config: StepTracerConfig,
+    steps: Vec<StepTrace>,
+    depth: usize,
+    /// Gas remaining in each active call frame, as of the last gasometer event.
+    frames_gas: Vec<Option<u64>>,
+    /// Gas remaining after the intrinsic cost, until the first frame is entered.
+    transaction_gas: Option<u64>,
+    /// Whether the last step still waits for its gas cost (and whether some was recorded).
+    pending_step: Option<bool>,
+    storage: BTreeMap<Address, BTreeMap<H256, H256>>,
+    gas_used: u64,
+}
+
+impl StepTracer {
+    pub fn new(config: StepTracerConfig) -> Self {
+        Self {
+            config,
+            ..Self::default()
+        }
+    }
+
+    pub fn steps(&self) -> &[StepTrace] {
+        &self.steps
+    }
+
+    /// Builds the trace of the execution which ended with `status`.
+    /// The gas is the one used by the EVM, before refunds.
+    pub fn finish(self, status: &TransactionStatus) -> TransactionTrace {
+        let (failed, return_value) = match status {
+            TransactionStatus::Succeed(output) => (false, output.clone()),
+            TransactionStatus::Revert(output) => (true, output.clone()),
+            _ => (true, Vec::new()),
+        };
+        TransactionTrace {
+            gas: self.gas_used,
+            failed,
+            return_value,
+            struct_logs: self.steps,
+        }
+    }
+
+    fn record_gas(&mut self, snapshot: Option<gas_tracing::Snapshot>) {
+        let snapshot = match snapshot {
+            Some(snapshot) => snapshot,
+            // Out of gas: the gasometer has no state anymore.
+            None => return,
+        };
+        let remaining = remaining_gas(&snapshot);
+        if self.depth <= 1 {
+            self.gas_used = snapshot.used_gas + snapshot.memory_gas;
+        }
+
+        let frame_gas = match self.frames_gas.last_mut() {
+            Some(frame_gas) => frame_gas,
+            None => {
+                self.transaction_gas = Some(remaining);
+                return;
+            }
+        };
+        let gas_before = frame_gas.unwrap_or(snapshot.gas_limit);
+        *frame_gas = Some(remaining);
+
+        if let (Some(recorded), Some(step)) = (self.pending_step, self.steps.last_mut()) {
+            if step.depth == self.depth {
+                if !recorded {
+                    step.gas = gas_before;
+                }
+                step.gas_cost = step.gas.saturating_sub(remaining);
+                self.pending_step = Some(true);
+            }
+        }
+    }
+}
+
+impl evm_tracing::EventListener for StepTracer {
+    fn event(&mut self, event: evm_tracing::Event) {
+        match event {
+            evm_tracing::Event::Call { .. } | evm_tracing::Event::Create { .. } => {
+                self.depth += 1;
+                self.frames_gas.push(self.transaction_gas.take());
+            }
+            evm_tracing::Event::Exit { .. } => {
+                self.depth = self.depth.saturating_sub(1);
+                self.frames_gas.pop();
+            }
+            _ => (),
+        }
+    }
+}
+
+impl runtime_tracing::EventListener for StepTracer {
+    fn event(&mut self, event: runtime_tracing::Event) {
+        match event {
+            runtime_tracing::Event::Step {
+                context,
+                opcode,
+                position,
+                stack,
+                memory,
+            } => {
+                let storage = if self.config.disable_storage {
+                    None
+                } else {
+                    Some(
+                        self.storage
+                            .get(&context.address)
+                            .cloned()
+                            .unwrap_or_default(),
+                    )
+                };
+                self.steps.push(StepTrace {
+                    pc: position.as_ref().map(|pc| *pc).unwrap_or(0),
+                    opcode: opcode.0,
+                    gas: self.frames_gas.last().copied().flatten().unwrap_or(0),
+                    gas_cost: 0,
+                    depth: self.depth.max(1),
+                    memory_size: memory.data().len(),
+                    stack: if self.config.disable_stack {
+                        None
+                    } else {
+                        Some(stack.data().clone())
+                    },
+                    memory: if self.config.enable_memory {
+                        Some(memory.data().clone())
+                    } else {
+                        None
+                    },
+                    storage,
+                });
+                self.pending_step = Some(false);
+            }
+            runtime_tracing::Event::StepResult { .. } => self.pending_step = None,
+            runtime_tracing::Event::SLoad {
+                address,
+                index,
+                value,
+            }
+            | runtime_tracing::Event::SStore {
+                address,
+                index,
+                value,
+            } => {
+                self.storage
+                    .entry(address)
+                    .or_insert_with(BTreeMap::new)
+                    .insert(index, value);
+            }
+        }
+    }
+}
+
+impl gas_tracing::EventListener for StepTracer {
+    fn event(&mut self, event: gas_tracing::Event) {
+        match event {
+            gas_tracing::Event::RecordCost { snapshot, .. }
+            | gas_tracing::Event::RecordDynamicCost { snapshot, .. }
+            | gas_tracing::Event::RecordStipend { snapshot, .. }
+            | gas_tracing::Event::RecordTransaction { snapshot, .. } => self.record_gas(snapshot),
+            _ => (),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallType {
+    Call,
+    StaticCall,
+    DelegateCall,
+    CallCode,
+    Create,
+    Create2,
+    SelfDestruct,
+}
+
+impl CallType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Call => "CALL",
+            Self::StaticCall => "STATICCALL",
+            Self::DelegateCall => "DELEGATECALL",
+            Self::CallCode => "CALLCODE",
+            Self::Create => "CREATE",
+            Self::Create2 => "CREATE2",
+            Self::SelfDestruct => "SELFDESTRUCT",
+        }
+    }
+}
+
+/// A node of the call tree built by the `CallTracer`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallFrame {
+    pub call_type: CallType,
+    pub from: Address,
+    pub to: Address,
+    pub value: U256,
+    pub gas: u64,
+    pub gas_used: u64,
+    pub input: Vec<u8>,
+    pub output: Vec<u8>,
+    pub error: Option<String>,
+    pub calls: Vec<CallFrame>,
+}
+
+impl CallFrame {
+    fn new(call_type: CallType, from: Address, to: Address, value: U256, input: Vec<u8>) -> Self {
+        Self {
+            call_type,
+            from,
+            to,
+            value,
+            gas: 0,
+            gas_used: 0,
+            input,
+            output: Vec::new(),
+            error: None,
+            calls: Vec::new(),
+        }
+    }
+
+    /// The call tree in the format of geth's `callTracer`.
+    pub fn to_json(&self) -> JsonValue {
+        let mut frame = BTreeMap::new();
+        frame.insert(
+            "type".to_string(),
+            JsonValue::String(self.call_type.as_str().to_string()),
+        );
+        frame.insert(
+            "from".to_string(),
+            JsonValue::String(format!("0x{}", hex::encode(self.from.as_bytes()))),
+        );
+        frame.insert(
+            "to".to_string(),
+            JsonValue::String(format!("0x{}", hex::encode(self.to.as_bytes()))),
+        );
+        frame.insert(
+            "value".to_string(),
+            JsonValue::String(format!("{:#x}", self.value)),
+        );
+        frame.insert("gas".to_string(), JsonValue::String(hex_u64(self.gas)));
+        frame.insert(
+            "gasUsed".to_string(),
+            JsonValue::String(hex_u64(self.gas_used)),
+        );
+        frame.insert(
+            "input".to_string(),
+            JsonValue::String(format!("0x{}", hex::encode(&self.input))),
+        );
+        frame.insert(
+            "output".to_string(),
+            JsonValue::String(format!("0x{}", hex::encode(&self.output))),
+        );
+        if let Some(error) = &self.error {
+            frame.insert("error".to_string(), JsonValue::String(error.clone()));
+        }
+        if !self.calls.is_empty() {
+            let calls = self.calls.iter().map(CallFrame::to_json).collect();
+            frame.insert("calls".to_string(), JsonValue::Array(calls));
+        }
+        JsonValue::Object(frame)
+    }
+}
+
+/// Builds the tree of the calls (and creates) made during an execution.
+#[derive(Debug, Default)]
+pub struct CallTracer {
+    stack: Vec<CallFrame>,
+    root: Option<CallFrame>,
+}
+
+impl CallTracer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The top level call, once the execution is over.
+    pub fn finish(self) -> Option<CallFrame> {
+        self.root
+    }
+
+    fn enter(&mut self, frame: CallFrame) {
+        self.stack.push(frame);
+    }
+
+    fn exit(&mut self, reason: &ExitReason, return_value: &[u8]) {
+        let mut frame = match self.stack.pop() {
+            Some(frame) => frame,
+            None => return,
+        };
+        frame.output = return_value.to_vec();
+        frame.error = exit_error(reason);
+        match self.stack.last_mut() {
+            Some(parent) => parent.calls.push(frame),
+            None => self.root = Some(frame),
+        }
+    }
+}
+
+impl evm_tracing::EventListener for CallTracer {
+    fn event(&mut self, event: evm_tracing::Event) {
+        match event {
+            evm_tracing::Event::Call {
+                code_address,
+                transfer,
+                input,
+                is_static,
+                context,
+                ..
+            } => {
+                let call_type = if is_static {
+                    CallType::StaticCall
+                } else if context.address != code_address {
+                    if transfer.is_some() {
+                        CallType::CallCode
+                    } else {
+                        CallType::DelegateCall
+                    }
+                } else {
+                    CallType::Call
+                };
+                let value = transfer
+                    .as_ref()
+                    .map(|transfer| transfer.value)
+                    .unwrap_or(context.apparent_value);
+                let to = if call_type == CallType::DelegateCall || call_type == CallType::CallCode {
+                    code_address
+                } else {
+                    context.address
+                };
+                self.enter(CallFrame::new(
+                    call_type,
+                    context.caller,
+                    to,
+                    value,
+                    input.to_vec(),
+                ));
+            }
+            evm_tracing::Event::Create {
+                caller,
+                address,
+                scheme,
+                value,
+                init_code,
+                ..
+            } => {
+                let call_type = match scheme {
+                    CreateScheme::Create2 { .. } => CallType::Create2,
+                    _ => CallType::Create,
+                };
+                self.enter(CallFrame::new(
+                    call_type,
+                    caller,
+                    address,
+                    value,
+                    init_code.to_vec(),
+                ));
+            }
+            evm_tracing::Event::Suicide {
+                address,
+                target,
+                balance,
+            } => {
+                if let Some(parent) = self.stack.last_mut() {
+                    parent.calls.push(CallFrame::new(
+                        CallType::SelfDestruct,
+                        address,
+                        target,
+                        balance,
+                        Vec::new(),
+                    ));
+                }
+            }
+            evm_tracing::Event::Exit {
+                reason,
+                return_value,
+            } => self.exit(reason, return_value),
+            _ => (),
+        }
+    }
+}
+
+impl runtime_tracing::EventListener for CallTracer {
+    fn event(&mut self, _event: runtime_tracing::Event) {}
+}
+
+impl gas_tracing::EventListener for CallTracer {
+    fn event(&mut self, event: gas_tracing::Event) {
+        let snapshot = match event {
+            gas_tracing::Event::RecordCost { snapshot, .. }
+            | gas_tracing::Event::RecordDynamicCost { snapshot, .. }
+            | gas_tracing::Event::RecordStipend { snapshot, .. } => snapshot,
+            _ => None,
+        };
+        if let (Some(snapshot), Some(frame)) = (snapshot, self.stack.last_mut()) {
+            frame.gas = snapshot.gas_limit;
+            frame.gas_used = snapshot.used_gas + snapshot.memory_gas;
+        }
+    }
+}
+
+fn remaining_gas(snapshot: &gas_tracing::Snapshot) -> u64 {
+    snapshot
+        .gas_limit
+        .saturating_sub(snapshot.used_gas)
+        .saturating_sub(snapshot.memory_gas)
+}
+
+fn hex_u64(value: u64) -> String {
+    format!("{:#x}", value)
+}
+
+fn exit_error(reason: &ExitReason) -> Option<String> {
+    let error = match reason {
+        ExitReason::Succeed(_) => return None,
+        ExitReason::Revert(_) => "execution reverted".to_string(),
+        ExitReason::Error(e) => format!("{:?}", e),
+        ExitReason::Fatal(e) => format!("{:?}", e),
+    };
+    Some(error)
+}
+
+/// Mnemonic of an opcode, as used by geth.
+pub fn opcode_name(opcode: u8) -> &'static str {
+    const PUSH: [&str; 32] = [
+        "PUSH1", "PUSH2", "PUSH3", "PUSH4", "PUSH5", "PUSH6", "PUSH7", "PUSH8", "PUSH9", "PUSH10",
+        "PUSH11", "PUSH12", "PUSH13", "PUSH14", "PUSH15", "PUSH16", "PUSH17", "PUSH18", "PUSH19",
+        "PUSH20", "PUSH21", "PUSH22", "PUSH23", "PUSH24", "PUSH25", "PUSH26", "PUSH27", "PUSH28",
+        "PUSH29", "PUSH30", "PUSH31", "PUSH32",
+    ];
+    const DUP: [&str; 16] = [
+        "DUP1", "DUP2", "DUP3", "DUP4", "DUP5", "DUP6", "DUP7", "DUP8", "DUP9", "DUP10", "DUP11",
+        "DUP12", "DUP13", "DUP14", "DUP15", "DUP16",
+    ];
+    const SWAP: [&str; 16] = [
+        "SWAP1", "SWAP2", "SWAP3", "SWAP4", "SWAP5", "SWAP6", "SWAP7", "SWAP8", "SWAP9", "SWAP10",
+        "SWAP11", "SWAP12", "SWAP13", "SWAP14", "SWAP15", "SWAP16",
+    ];
+    const LOG: [&str; 5] = ["LOG0", "LOG1", "LOG2", "LOG3", "LOG4"];
+
+    match opcode {
+        0x00 => "STOP",
+        0x01 => "ADD",
+        0x02 => "MUL",
+        0x03 => "SUB",
+        0x04 => "DIV",
+        0x05 => "SDIV",
+        0x06 => "MOD",
+        0x07 => "SMOD",
+        0x08 => "ADDMOD",
+        0x09 => "MULMOD",
+        0x0a => "EXP",
+        0x0b => "SIGNEXTEND",
+        0x10 => "LT",
+        0x11 => "GT",
+        0x12 => "SLT",
+        0x13 => "SGT",
+        0x14 => "EQ",
+        0x15 => "ISZERO",
+        0x16 => "AND",
+        0x17 => "OR",
+        0x18 => "XOR",
+        0x19 => "NOT",
+        0x1a => "BYTE",
+        0x1b => "SHL",
+        0x1c => "SHR",
+        0x1d => "SAR",
+        0x20 => "SHA3",
+        0x30 => "ADDRESS",
+        0x31 => "BALANCE",
+        0x32 => "ORIGIN",
+        0x33 => "CALLER",
+        0x34 => "CALLVALUE",
+        0x35 => "CALLDATALOAD",
+        0x36 => "CALLDATASIZE",
+        0x37 => "CALLDATACOPY",
+        0x38 => "CODESIZE",
+        0x39 => "CODECOPY",
+        0x3a => "GASPRICE",
+        0x3b => "EXTCODESIZE",
+        0x3c => "EXTCODECOPY",
+        0x3d => "RETURNDATASIZE",
+        0x3e => "RETURNDATACOPY",
+        0x3f => "EXTCODEHASH",
+        0x40 => "BLOCKHASH",
+        0x41 => "COINBASE",
+        0x42 => "TIMESTAMP",
+        0x43 => "NUMBER",
+        0x44 => "DIFFICULTY",
+        0x45 => "GASLIMIT",
+        0x46 => "CHAINID",
+        0x47 => "SELFBALANCE",
+        0x48 => "BASEFEE",
+        0x50 => "POP",
+        0x51 => "MLOAD",
+        0x52 => "MSTORE",
+        0x53 => "MSTORE8",
+        0x54 => "SLOAD",
+        0x55 => "SSTORE",
+        0x56 => "JUMP",
+        0x57 => "JUMPI",
+        0x58 => "PC",
+        0x59 => "MSIZE",
+        0x5a => "GAS",
+        0x5b => "JUMPDEST",
+        0x60..=0x7f => PUSH[(opcode - 0x60) as usize],
+        0x80..=0x8f => DUP[(opcode - 0x80) as usize],
+        0x90..=0x9f => SWAP[(opcode - 0x90) as usize],
+        0xa0..=0xa4 => LOG[(opcode - 0xa0) as usize],
+        0xf0 => "CREATE",
+        0xf1 => "CALL",
+        0xf2 => "CALLCODE",
+        0xf3 => "RETURN",
+        0xf4 => "DELEGATECALL",
+        0xf5 => "CREATE2",
+        0xfa => "STATICCALL",
+        0xfd => "REVERT",
+        0xfe => "INVALID",
+        0xff => "SELFDESTRUCT",
+        _ => "UNKNOWN",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::vec;
+
+    #[test]
+    fn test_opcode_name() {
+        assert_eq!(opcode_name(0x00), "STOP");
+        assert_eq!(opcode_name(0x60), "PUSH1");
+        assert_eq!(opcode_name(0x7f), "PUSH32");
+        assert_eq!(opcode_name(0x8f), "DUP16");
+        assert_eq!(opcode_name(0x90), "SWAP1");
+        assert_eq!(opcode_name(0xa4), "LOG4");
+        assert_eq!(opcode_name(0xfa), "STATICCALL");
+        assert_eq!(opcode_name(0x0c), "UNKNOWN");
+    }
+
+    #[test]
+    fn test_step_trace_json() {
+        let step = StepTrace {
+            pc: 2,
+            opcode: 0x52,
+            gas: 100,
+            gas_cost: 6,
+            depth: 1,
+            memory_size: 0,
+            stack: Some(vec![H256::from_low_u64_be(42), H256::zero()]),
+            memory: None,
+            storage: None,
+        };
+        assert_eq!(
+            step.to_eip3155().to_string(),
+            r#"{"depth": 1, "gas": "0x64", "gasCost": "0x6", "memSize": 0, "op": 82, "opName": "MSTORE", "pc": 2, "stack": ["0x2a", "0x0"]}"#
+        );
+    }
+}
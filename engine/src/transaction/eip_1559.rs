@@ -36,6 +36,21 @@ impl Transaction1559 {
         self.rlp_append(s, 12);
     }
 
+    /// Decodes the fields of the transaction, which are the first items of `rlp`.
+    pub(crate) fn decode_fields(rlp: &Rlp<'_>) -> Result<Self, DecoderError> {
+        Ok(Self {
+            chain_id: rlp.val_at(0)?,
+            nonce: rlp.val_at(1)?,
+            max_priority_fee_per_gas: rlp.val_at(2)?,
+            max_fee_per_gas: rlp.val_at(3)?,
+            gas_limit: rlp.val_at(4)?,
+            to: super::rlp_extract_to(rlp, 5)?,
+            value: Wei::new(rlp.val_at(6)?),
+            data: rlp.val_at(7)?,
+            access_list: rlp.list_at(8)?,
+        })
+    }
+
     fn rlp_append(&self, s: &mut RlpStream, list_len: usize) {
         s.begin_list(list_len);
         s.append(&self.chain_id);
@@ -98,30 +113,12 @@ impl Decodable for SignedTransaction1559 {
         if rlp.item_count() != Ok(12) {
             return Err(rlp::DecoderError::RlpIncorrectListLen);
         }
-        let chain_id = rlp.val_at(0)?;
-        let nonce = rlp.val_at(1)?;
-        let max_priority_fee_per_gas = rlp.val_at(2)?;
-        let max_fee_per_gas = rlp.val_at(3)?;
-        let gas_limit = rlp.val_at(4)?;
-        let to = super::rlp_extract_to(rlp, 5)?;
-        let value = Wei::new(rlp.val_at(6)?);
-        let data = rlp.val_at(7)?;
-        let access_list = rlp.list_at(8)?;
+        let transaction = Transaction1559::decode_fields(rlp)?;
         let parity = rlp.val_at(9)?;
         let r = rlp.val_at(10)?;
         let s = rlp.val_at(11)?;
         Ok(Self {
-            transaction: Transaction1559 {
-                chain_id,
-                nonce,
-                max_priority_fee_per_gas,
-                max_fee_per_gas,
-                gas_limit,
-                to,
-                value,
-                data,
-                access_list,
-            },
+            transaction,
             parity,
             r,
             s,
@@ -0,0 +1,47 @@
+use crate::prelude::Address;
+use crate::transaction::eip_1559::Transaction1559;
+use rlp::{Decodable, DecoderError, Encodable, Rlp, RlpStream};
+
+/// Type indicator (per EIP-2718) for impersonated transactions. It is the last value
+/// available to typed transactions, so it is unlikely to be taken by a future EIP.
+pub const TYPE_BYTE: u8 = 0x7f;
+
+/// An unsigned EIP-1559 transaction carrying its sender, which is trusted without any
+/// signature. It lets test harnesses submit transactions on behalf of any address, and is
+/// only available with the `integration-test` feature.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct ImpersonatedTransaction {
+    pub sender: Address,
+    pub transaction: Transaction1559,
+}
+
+impl ImpersonatedTransaction {
+    pub fn sender(&self) -> Option<Address> {
+        Some(self.sender)
+    }
+}
+
+impl Encodable for ImpersonatedTransaction {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        s.begin_list(2);
+        s.append(&self.sender);
+        self.transaction.rlp_append_unsigned(s);
+    }
+}
+
+impl Decodable for ImpersonatedTransaction {
+    fn decode(rlp: &Rlp<'_>) -> Result<Self, DecoderError> {
+        if rlp.item_count() != Ok(2) {
+            return Err(rlp::DecoderError::RlpIncorrectListLen);
+        }
+        let sender = rlp.val_at(0)?;
+        let transaction = rlp.at(1)?;
+        if transaction.item_count() != Ok(9) {
+            return Err(rlp::DecoderError::RlpIncorrectListLen);
+        }
+        Ok(Self {
+            sender,
+            transaction: Transaction1559::decode_fields(&transaction)?,
+        })
+    }
+}
@@ -3,6 +3,8 @@ use rlp::{Decodable, DecoderError, Rlp};
 
 pub mod eip_1559;
 pub mod eip_2930;
+#[cfg(feature = "integration-test")]
+pub mod impersonated;
 pub mod legacy;
 
 use aurora_engine_types::types::Wei;
@@ -14,23 +16,52 @@ pub enum EthTransactionKind {
     Legacy(legacy::LegacyEthSignedTransaction),
     Eip2930(eip_2930::SignedTransaction2930),
     Eip1559(eip_1559::SignedTransaction1559),
+    #[cfg(feature = "integration-test")]
+    Impersonated(impersonated::ImpersonatedTransaction),
+}
+
+/// Decodes the payload of a typed transaction, i.e. the bytes following its type byte.
+type TypedTransactionDecoder = fn(&[u8]) -> Result<EthTransactionKind, ParseTransactionError>;
+
+/// Decoders of the supported typed transactions, by type byte. Supporting a new type takes a
+/// variant of `EthTransactionKind`, its normalization and an entry here.
+const TYPED_TRANSACTIONS: &[(u8, TypedTransactionDecoder)] = &[
+    (eip_2930::TYPE_BYTE, decode_eip_2930),
+    (eip_1559::TYPE_BYTE, decode_eip_1559),
+    #[cfg(feature = "integration-test")]
+    (impersonated::TYPE_BYTE, decode_impersonated),
+];
+
+fn decode_eip_2930(payload: &[u8]) -> Result<EthTransactionKind, ParseTransactionError> {
+    let tx = eip_2930::SignedTransaction2930::decode(&Rlp::new(payload))?;
+    Ok(EthTransactionKind::Eip2930(tx))
+}
+
+fn decode_eip_1559(payload: &[u8]) -> Result<EthTransactionKind, ParseTransactionError> {
+    let tx = eip_1559::SignedTransaction1559::decode(&Rlp::new(payload))?;
+    Ok(EthTransactionKind::Eip1559(tx))
+}
+
+#[cfg(feature = "integration-test")]
+fn decode_impersonated(payload: &[u8]) -> Result<EthTransactionKind, ParseTransactionError> {
+    let tx = impersonated::ImpersonatedTransaction::decode(&Rlp::new(payload))?;
+    Ok(EthTransactionKind::Impersonated(tx))
 }
 
 impl TryFrom<&[u8]> for EthTransactionKind {
     type Error = ParseTransactionError;
 
     fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
-        if bytes[0] == eip_2930::TYPE_BYTE {
-            Ok(Self::Eip2930(eip_2930::SignedTransaction2930::decode(
-                &Rlp::new(&bytes[1..]),
-            )?))
-        } else if bytes[0] == eip_1559::TYPE_BYTE {
-            Ok(Self::Eip1559(eip_1559::SignedTransaction1559::decode(
-                &Rlp::new(&bytes[1..]),
-            )?))
-        } else if bytes[0] <= 0x7f {
-            Err(ParseTransactionError::UnknownTransactionType)
-        } else if bytes[0] == 0xff {
+        let (&first_byte, payload) = bytes
+            .split_first()
+            .ok_or(ParseTransactionError::EmptyInput)?;
+        if first_byte <= 0x7f {
+            let (_, decode) = TYPED_TRANSACTIONS
+                .iter()
+                .find(|(type_byte, _)| *type_byte == first_byte)
+                .ok_or(ParseTransactionError::UnknownTransactionType)?;
+            decode(payload)
+        } else if first_byte == 0xff {
             Err(ParseTransactionError::ReservedSentinel)
         } else {
             let legacy = legacy::LegacyEthSignedTransaction::decode(&Rlp::new(bytes))?;
@@ -94,6 +125,19 @@ impl From<EthTransactionKind> for NormalizedEthTransaction {
                 data: tx.transaction.data,
                 access_list: tx.transaction.access_list,
             },
+            #[cfg(feature = "integration-test")]
+            Impersonated(tx) => Self {
+                address: tx.sender(),
+                chain_id: Some(tx.transaction.chain_id),
+                nonce: tx.transaction.nonce,
+                gas_limit: tx.transaction.gas_limit,
+                max_priority_fee_per_gas: tx.transaction.max_priority_fee_per_gas,
+                max_fee_per_gas: tx.transaction.max_fee_per_gas,
+                to: tx.transaction.to,
+                value: tx.transaction.value,
+                data: tx.transaction.data,
+                access_list: tx.transaction.access_list,
+            },
         }
     }
 }
@@ -136,13 +180,27 @@ impl NormalizedEthTransaction {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ParseTransactionError {
+    /// The transaction bytes are empty.
+    EmptyInput,
     UnknownTransactionType,
     // Per the EIP-2718 spec 0xff is a reserved value
     ReservedSentinel,
     RlpDecodeError(DecoderError),
 }
 
+impl ParseTransactionError {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::EmptyInput => "ERR_EMPTY_TX",
+            Self::UnknownTransactionType => "ERR_UNKNOWN_TX_TYPE",
+            Self::ReservedSentinel => "ERR_RESERVED_LEADING_TX_BYTE",
+            Self::RlpDecodeError(_) => "ERR_TX_RLP_DECODE",
+        }
+    }
+}
+
 impl From<DecoderError> for ParseTransactionError {
     fn from(e: DecoderError) -> Self {
         Self::RlpDecodeError(e)
@@ -151,11 +209,7 @@ impl From<DecoderError> for ParseTransactionError {
 
 impl AsRef<[u8]> for ParseTransactionError {
     fn as_ref(&self) -> &[u8] {
-        match self {
-            Self::UnknownTransactionType => b"ERR_UNKNOWN_TX_TYPE",
-            Self::ReservedSentinel => b"ERR_RESERVED_LEADING_TX_BYTE",
-            Self::RlpDecodeError(_) => b"ERR_TX_RLP_DECODE",
-        }
+        self.as_str().as_bytes()
     }
 }
 
@@ -184,3 +238,55 @@ fn vrs_to_arr(v: u8, r: U256, s: U256) -> [u8; 65] {
     result[64] = v;
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_transaction_errors() {
+        let parse = |bytes: &[u8]| EthTransactionKind::try_from(bytes).err();
+        assert_eq!(parse(&[]), Some(ParseTransactionError::EmptyInput));
+        assert_eq!(
+            parse(&[0x03, 0xc0]),
+            Some(ParseTransactionError::UnknownTransactionType)
+        );
+        assert_eq!(
+            parse(&[0xff, 0xc0]),
+            Some(ParseTransactionError::ReservedSentinel)
+        );
+        assert!(matches!(
+            parse(&[eip_1559::TYPE_BYTE]),
+            Some(ParseTransactionError::RlpDecodeError(_))
+        ));
+    }
+
+    #[cfg(feature = "integration-test")]
+    #[test]
+    fn test_parse_impersonated_transaction() {
+        let tx = impersonated::ImpersonatedTransaction {
+            sender: Address::repeat_byte(0x11),
+            transaction: eip_1559::Transaction1559 {
+                chain_id: 1,
+                nonce: U256::from(2),
+                max_priority_fee_per_gas: U256::zero(),
+                max_fee_per_gas: U256::zero(),
+                gas_limit: U256::from(21_000),
+                to: Some(Address::repeat_byte(0x22)),
+                value: Wei::new_u64(3),
+                data: Vec::new(),
+                access_list: Vec::new(),
+            },
+        };
+        let mut bytes = vec![impersonated::TYPE_BYTE];
+        bytes.extend_from_slice(&rlp::encode(&tx));
+
+        let normalized: NormalizedEthTransaction = EthTransactionKind::try_from(bytes.as_slice())
+            .unwrap()
+            .into();
+        assert_eq!(normalized.address, Some(tx.sender));
+        assert_eq!(normalized.nonce, tx.transaction.nonce);
+        assert_eq!(normalized.to, tx.transaction.to);
+        assert_eq!(normalized.value, tx.transaction.value);
+    }
+}
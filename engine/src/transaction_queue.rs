@@ -0,0 +1,265 @@
+//! Queue of the transactions submitted with a nonce ahead of the nonce of their sender.
+//!
+//! When the owner enables it by setting a non-zero limit, `submit` stores such a transaction
+//! instead of rejecting it, and executes it once the transactions filling the nonce gap have
+//! been processed. As in geth's transaction pool, a queued transaction can be replaced by
+//! another one with the same sender and nonce, provided it pays at least `PRICE_BUMP_PERCENT`
+//! percent more per gas.
+//!
+//! A `submit` executes at most `MAX_QUEUED_EXECUTIONS` queued transactions, the next ones
+//! waiting for the following `submit` of their sender. A transaction still queued
+//! `QUEUE_TTL_BLOCKS` blocks after it was queued is expired: it is dropped without being
+//! executed the next time the queue of its sender changes.
+//!
+//! The nonces of the queued transactions of a sender are stored under the sender address,
+//! and each transaction under the address followed by its nonce. The functions changing the
+//! queue record the entries they change in a `StorageChanges`, for the sender to stake for them.
+
+use crate::parameters::PendingTransaction;
+use crate::prelude::storage::{bytes_to_key, KeyPrefix};
+use crate::prelude::{u256_to_arr, Address, BorshDeserialize, BorshSerialize, Vec, U256};
+use crate::storage_staking::StorageChanges;
+use aurora_engine_sdk::io::{StorageIntermediate, IO};
+
+/// Key for storing the maximum number of queued transactions per sender.
+const QUEUE_LIMIT_KEY: &[u8; 14] = b"TX_QUEUE_LIMIT";
+
+/// Minimum increase, in percent, of the fees of a transaction replacing a queued one.
+pub const PRICE_BUMP_PERCENT: u64 = 10;
+
+/// Maximum number of queued transactions executed by one `submit`.
+pub const MAX_QUEUED_EXECUTIONS: usize = 4;
+
+/// Number of blocks after which a queued transaction expires.
+pub const QUEUE_TTL_BLOCKS: u64 = 600;
+
+/// Sub-prefix of the keys of the queued nonces of a sender.
+const QUEUE_NONCES_ID: u8 = 0x0;
+/// Sub-prefix of the keys of the queued transactions.
+const QUEUED_TRANSACTION_ID: u8 = 0x1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransactionQueueError {
+    /// The sender already has the maximum number of queued transactions.
+    QueueFull,
+    /// The transaction does not pay enough more than the queued one it would replace.
+    ReplacementUnderpriced,
+    /// The transaction nonce does not fit in 64 bits.
+    NonceOverflow,
+}
+
+impl TransactionQueueError {
+    pub fn to_str(&self) -> &str {
+        match self {
+            Self::QueueFull => "ERR_TX_QUEUE_FULL",
+            Self::ReplacementUnderpriced => "ERR_REPLACEMENT_UNDERPRICED",
+            Self::NonceOverflow => "ERR_NONCE_OVERFLOW",
+        }
+    }
+}
+
+impl AsRef<[u8]> for TransactionQueueError {
+    fn as_ref(&self) -> &[u8] {
+        self.to_str().as_bytes()
+    }
+}
+
+/// Maximum number of queued transactions per sender. The queue is disabled by default.
+pub fn get_limit<I: IO>(io: &I) -> u32 {
+    io.read_storage(&bytes_to_key(KeyPrefix::Config, QUEUE_LIMIT_KEY))
+        .and_then(|bytes| u32::try_from_slice(&bytes.to_vec()).ok())
+        .unwrap_or(0)
+}
+
+pub fn set_limit<I: IO>(io: &mut I, limit: u32) {
+    io.write_storage(
+        &bytes_to_key(KeyPrefix::Config, QUEUE_LIMIT_KEY),
+        &limit.to_le_bytes(),
+    );
+}
+
+fn queue_nonces_key(sender: &Address) -> Vec<u8> {
+    let mut key = [0u8; 21];
+    key[0] = QUEUE_NONCES_ID;
+    key[1..].copy_from_slice(sender.as_bytes());
+    bytes_to_key(KeyPrefix::TransactionQueue, &key)
+}
+
+fn queued_transaction_key(sender: &Address, nonce: u64) -> Vec<u8> {
+    let mut key = [0u8; 29];
+    key[0] = QUEUED_TRANSACTION_ID;
+    key[1..21].copy_from_slice(sender.as_bytes());
+    key[21..].copy_from_slice(&nonce.to_be_bytes());
+    bytes_to_key(KeyPrefix::TransactionQueue, &key)
+}
+
+/// Nonces of the queued transactions of `sender`, in increasing order.
+fn get_queue_nonces<I: IO>(io: &I, sender: &Address) -> Vec<u64> {
+    io.read_storage(&queue_nonces_key(sender))
+        .and_then(|bytes| Vec::<u64>::try_from_slice(&bytes.to_vec()).ok())
+        .unwrap_or_default()
+}
+
+fn set_queue_nonces<I: IO>(
+    io: &mut I,
+    sender: &Address,
+    nonces: &[u64],
+    storage_changes: &mut StorageChanges,
+) {
+    let key = queue_nonces_key(sender);
+    storage_changes.record(io, &key);
+    if nonces.is_empty() {
+        io.remove_storage(&key);
+    } else {
+        io.write_storage(&key, &nonces.try_to_vec().expect("ERR_SER"));
+    }
+}
+
+fn remove_queued_transaction<I: IO>(
+    io: &mut I,
+    sender: &Address,
+    nonce: u64,
+    storage_changes: &mut StorageChanges,
+) {
+    let key = queued_transaction_key(sender, nonce);
+    storage_changes.record(io, &key);
+    io.remove_storage(&key);
+}
+
+fn get_queued_transaction<I: IO>(
+    io: &I,
+    sender: &Address,
+    nonce: u64,
+) -> Option<PendingTransaction> {
+    io.read_storage(&queued_transaction_key(sender, nonce))
+        .and_then(|bytes| PendingTransaction::try_from_slice(&bytes.to_vec()).ok())
+}
+
+/// Queued transactions of `sender`, in increasing nonce order.
+pub fn get_pending_transactions<I: IO>(io: &I, sender: &Address) -> Vec<PendingTransaction> {
+    get_queue_nonces(io, sender)
+        .into_iter()
+        .filter_map(|nonce| get_queued_transaction(io, sender, nonce))
+        .collect()
+}
+
+/// Adds a transaction to the queue of `sender`, replacing the queued transaction with the
+/// same nonce if the fees of the new one are high enough.
+#[allow(clippy::too_many_arguments)]
+pub fn enqueue<I: IO>(
+    io: &mut I,
+    sender: &Address,
+    block_height: u64,
+    nonce: &U256,
+    max_fee_per_gas: &U256,
+    max_priority_fee_per_gas: &U256,
+    max_cost: &U256,
+    transaction_bytes: &[u8],
+    storage_changes: &mut StorageChanges,
+) -> Result<(), TransactionQueueError> {
+    if nonce > &U256::from(u64::MAX) {
+        return Err(TransactionQueueError::NonceOverflow);
+    }
+    let nonce = nonce.low_u64();
+    let mut nonces = remove_stale(io, sender, block_height, |_| false, storage_changes);
+    match nonces.binary_search(&nonce) {
+        Ok(_) => {
+            let queued = get_queued_transaction(io, sender, nonce)
+                .ok_or(TransactionQueueError::ReplacementUnderpriced)?;
+            let is_bumped = |new: &U256, old: [u8; 32]| {
+                let threshold = U256::from(old)
+                    .saturating_mul(U256::from(100 + PRICE_BUMP_PERCENT))
+                    / U256::from(100);
+                new >= &threshold
+            };
+            if !is_bumped(max_fee_per_gas, queued.max_fee_per_gas)
+                || !is_bumped(max_priority_fee_per_gas, queued.max_priority_fee_per_gas)
+            {
+                return Err(TransactionQueueError::ReplacementUnderpriced);
+            }
+        }
+        Err(position) => {
+            if nonces.len() >= get_limit(io) as usize {
+                return Err(TransactionQueueError::QueueFull);
+            }
+            nonces.insert(position, nonce);
+            set_queue_nonces(io, sender, &nonces, storage_changes);
+        }
+    }
+    let pending = PendingTransaction {
+        nonce,
+        queued_at: block_height,
+        max_fee_per_gas: u256_to_arr(max_fee_per_gas),
+        max_priority_fee_per_gas: u256_to_arr(max_priority_fee_per_gas),
+        max_cost: u256_to_arr(max_cost),
+        transaction: transaction_bytes.to_vec(),
+    };
+    let key = queued_transaction_key(sender, nonce);
+    storage_changes.record(io, &key);
+    io.write_storage(&key, &pending.try_to_vec().expect("ERR_SER"));
+    Ok(())
+}
+
+/// Sum of the max costs of the queued transactions of `sender`, or `None` on overflow.
+pub fn get_queued_cost<I: IO>(io: &I, sender: &Address) -> Option<U256> {
+    get_pending_transactions(io, sender)
+        .iter()
+        .try_fold(U256::zero(), |total, pending| {
+            total.checked_add(U256::from(pending.max_cost))
+        })
+}
+
+fn is_expired(pending: &PendingTransaction, block_height: u64) -> bool {
+    block_height.saturating_sub(pending.queued_at) >= QUEUE_TTL_BLOCKS
+}
+
+/// Removes from the queue of `sender` the transactions expired at `block_height`, and those
+/// whose nonce is stale according to `is_stale`. Returns the nonces left in the queue.
+fn remove_stale<I: IO, F: Fn(u64) -> bool>(
+    io: &mut I,
+    sender: &Address,
+    block_height: u64,
+    is_stale: F,
+    storage_changes: &mut StorageChanges,
+) -> Vec<u64> {
+    let nonces = get_queue_nonces(io, sender);
+    let mut kept = Vec::with_capacity(nonces.len());
+    for nonce in nonces.iter().copied() {
+        let is_live = !is_stale(nonce)
+            && get_queued_transaction(io, sender, nonce)
+                .map_or(false, |pending| !is_expired(&pending, block_height));
+        if is_live {
+            kept.push(nonce);
+        } else {
+            remove_queued_transaction(io, sender, nonce, storage_changes);
+        }
+    }
+    if kept.len() != nonces.len() {
+        set_queue_nonces(io, sender, &kept, storage_changes);
+    }
+    kept
+}
+
+/// Removes from the queue of `sender` the transactions which can no longer be executed,
+/// because their nonce is below `account_nonce` or they are expired at `block_height`, then
+/// removes and returns the transaction with nonce `account_nonce`, if it is queued.
+pub fn take_next<I: IO>(
+    io: &mut I,
+    sender: &Address,
+    account_nonce: &U256,
+    block_height: u64,
+    storage_changes: &mut StorageChanges,
+) -> Option<PendingTransaction> {
+    let is_stale = |nonce| &U256::from(nonce) < account_nonce;
+    let mut nonces = remove_stale(io, sender, block_height, is_stale, storage_changes);
+    match nonces.first() {
+        Some(nonce) if &U256::from(*nonce) == account_nonce => {
+            let nonce = nonces.remove(0);
+            let next_transaction = get_queued_transaction(io, sender, nonce);
+            remove_queued_transaction(io, sender, nonce, storage_changes);
+            set_queue_nonces(io, sender, &nonces, storage_changes);
+            next_transaction
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,172 @@
+//! Validation of the code staged for an upgrade of the engine.
+//!
+//! Deploying code which is not a wasm module, or which misses one of the methods the upgrade
+//! process relies on, would leave the engine unusable. The staged code is therefore checked
+//! to be a wasm module exporting at least `REQUIRED_EXPORTS`.
+
+use crate::prelude::Vec;
+
+/// The wasm magic number (`\0asm`) followed by the version of the binary format.
+const WASM_HEADER: [u8; 8] = [0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00];
+
+/// Id of the export section of a wasm module.
+const EXPORT_SECTION_ID: u8 = 7;
+
+/// Kind of the exports which are functions.
+const FUNCTION_EXPORT_KIND: u8 = 0;
+
+/// Methods the engine must keep exporting after an upgrade.
+pub const REQUIRED_EXPORTS: [&str; 3] = ["new", "submit", "state_migration"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpgradeValidationError {
+    /// The code does not start with the wasm magic number and version 1.
+    InvalidMagic,
+    /// The sections of the module could not be parsed.
+    MalformedModule,
+    /// The module does not export one of `REQUIRED_EXPORTS`.
+    MissingExport(&'static str),
+}
+
+impl AsRef<[u8]> for UpgradeValidationError {
+    fn as_ref(&self) -> &[u8] {
+        match self {
+            Self::InvalidMagic => b"ERR_INVALID_WASM_MAGIC",
+            Self::MalformedModule => b"ERR_MALFORMED_WASM",
+            Self::MissingExport(_) => b"ERR_MISSING_REQUIRED_EXPORT",
+        }
+    }
+}
+
+/// Checks that `code` is a wasm module exporting all the `REQUIRED_EXPORTS` functions.
+pub fn validate_code(code: &[u8]) -> Result<(), UpgradeValidationError> {
+    if !code.starts_with(&WASM_HEADER) {
+        return Err(UpgradeValidationError::InvalidMagic);
+    }
+    let exports = function_exports(&code[WASM_HEADER.len()..])
+        .ok_or(UpgradeValidationError::MalformedModule)?;
+    for name in REQUIRED_EXPORTS.iter() {
+        if !exports.contains(&name.as_bytes()) {
+            return Err(UpgradeValidationError::MissingExport(*name));
+        }
+    }
+    Ok(())
+}
+
+/// Names of the functions exported by a module, given its sections.
+fn function_exports(mut sections: &[u8]) -> Option<Vec<&[u8]>> {
+    let mut exports = Vec::new();
+    while !sections.is_empty() {
+        let (&id, rest) = sections.split_first()?;
+        let (size, rest) = read_leb128_u32(rest)?;
+        let size = size as usize;
+        if rest.len() < size {
+            return None;
+        }
+        let (section, rest) = rest.split_at(size);
+        sections = rest;
+        if id != EXPORT_SECTION_ID {
+            continue;
+        }
+
+        let (count, mut entries) = read_leb128_u32(section)?;
+        for _ in 0..count {
+            let (name_len, rest) = read_leb128_u32(entries)?;
+            let name_len = name_len as usize;
+            if rest.len() < name_len {
+                return None;
+            }
+            let (name, rest) = rest.split_at(name_len);
+            let (&kind, rest) = rest.split_first()?;
+            let (_index, rest) = read_leb128_u32(rest)?;
+            entries = rest;
+            if kind == FUNCTION_EXPORT_KIND {
+                exports.push(name);
+            }
+        }
+    }
+    Some(exports)
+}
+
+/// Reads an unsigned LEB128 integer (at most 5 bytes), returning it with the remaining bytes.
+fn read_leb128_u32(bytes: &[u8]) -> Option<(u32, &[u8])> {
+    let mut result: u32 = 0;
+    for (i, byte) in bytes.iter().enumerate().take(5) {
+        result |= u32::from(byte & 0x7f).checked_shl(7 * i as u32)?;
+        if byte & 0x80 == 0 {
+            return Some((result, &bytes[i + 1..]));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::vec;
+
+    /// A module with an export section holding the given exports (without the matching
+    /// function section, which is not needed for the validation).
+    fn module(exports: &[(&str, u8)]) -> Vec<u8> {
+        let mut section = vec![exports.len() as u8];
+        for (name, kind) in exports {
+            section.push(name.len() as u8);
+            section.extend_from_slice(name.as_bytes());
+            section.push(*kind);
+            section.push(0);
+        }
+        let mut code = WASM_HEADER.to_vec();
+        // An empty custom section before the export section.
+        code.extend_from_slice(&[0, 1, 0]);
+        code.push(EXPORT_SECTION_ID);
+        code.push(section.len() as u8);
+        code.extend_from_slice(&section);
+        code
+    }
+
+    #[test]
+    fn test_validate_code() {
+        let exports = [
+            ("new", 0),
+            ("submit", 0),
+            ("state_migration", 0),
+            ("memory", 2),
+        ];
+        assert_eq!(validate_code(&module(&exports)), Ok(()));
+
+        assert_eq!(
+            validate_code(b"not wasm at all"),
+            Err(UpgradeValidationError::InvalidMagic)
+        );
+        assert_eq!(
+            validate_code(&module(&[("new", 0), ("submit", 0)])),
+            Err(UpgradeValidationError::MissingExport("state_migration"))
+        );
+        // Only function exports count.
+        assert_eq!(
+            validate_code(&module(&[
+                ("new", 0),
+                ("submit", 3),
+                ("state_migration", 0)
+            ])),
+            Err(UpgradeValidationError::MissingExport("submit"))
+        );
+
+        let mut truncated = module(&exports);
+        truncated.pop();
+        assert_eq!(
+            validate_code(&truncated),
+            Err(UpgradeValidationError::MalformedModule)
+        );
+    }
+
+    #[test]
+    fn test_read_leb128_u32() {
+        assert_eq!(read_leb128_u32(&[0x02, 0xff]), Some((2, &[0xff][..])));
+        assert_eq!(
+            read_leb128_u32(&[0xe5, 0x8e, 0x26]),
+            Some((624_485, &[][..]))
+        );
+        assert_eq!(read_leb128_u32(&[0x80, 0x80]), None);
+    }
+}
@@ -53,7 +53,7 @@ contract EvmErc20V2 is ERC20, AdminControlled, IExit {
         _burn(sender, amount);
 
         bytes32 amount_b = bytes32(amount);
-        bytes memory input = abi.encodePacked("\x01", sender, amount_b, recipient);
+        bytes memory input = abi.encodePacked("\x05", sender, amount_b, recipient);
         uint input_size = 1 + 20 + 32 + recipient.length;
 
         assembly {
@@ -42,7 +42,7 @@ contract TesterV2 {
 
     function withdrawEthToNear(bytes memory recipient) external payable {
         address sender = msg.sender;
-        bytes memory input = abi.encodePacked("\x00", sender, recipient);
+        bytes memory input = abi.encodePacked("\x04", sender, recipient);
         uint input_size = 1 + 20 + recipient.length;
         uint256 amount = msg.value;
 
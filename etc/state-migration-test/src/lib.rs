@@ -3,44 +3,119 @@
 extern crate alloc;
 
 use alloc::vec::Vec;
-use aurora_engine::engine::{self, EngineState};
+use aurora_engine::migration::{self, Migration, StepStatus};
+use aurora_engine_sdk::io::{StorageIntermediate, IO};
 use aurora_engine_sdk::near_runtime::Runtime;
-use aurora_engine_sdk::io::{IO, StorageIntermediate};
 use aurora_engine_types::storage;
 use borsh::{BorshDeserialize, BorshSerialize};
 
+/// Number of entries written by the `entries` migration step.
+const ENTRIES_COUNT: u32 = 10;
+/// Number of entries written by each call of the `entries` migration step.
+const ENTRIES_PER_CHUNK: u32 = 4;
+
 #[derive(BorshDeserialize, BorshSerialize)]
 struct NewFancyState {
-    old_state: EngineState,
     some_other_numbers: [u32; 7],
 }
 
+/// The migrations of the engine, followed by the ones of this contract.
+fn migrations() -> Vec<Migration<Runtime>> {
+    let mut migrations = migration::registry();
+    let engine_version = migration::latest_version(&migrations);
+    migrations.push(Migration {
+        version: engine_version + 1,
+        name: "fancy_state",
+        run: fancy_state,
+    });
+    migrations.push(Migration {
+        version: engine_version + 2,
+        name: "entries",
+        run: entries,
+    });
+    migrations
+}
+
+fn fancy_state(io: &mut Runtime, _cursor: Option<&[u8]>) -> StepStatus {
+    let new_state = NewFancyState {
+        some_other_numbers: [3, 1, 4, 1, 5, 9, 2],
+    };
+    io.write_storage(
+        &fancy_state_key(),
+        &new_state.try_to_vec().expect("ERR_SER"),
+    );
+    StepStatus::Done
+}
+
+/// Writes `ENTRIES_COUNT` entries, `ENTRIES_PER_CHUNK` at a time.
+fn entries(io: &mut Runtime, cursor: Option<&[u8]>) -> StepStatus {
+    let start = cursor
+        .map(|cursor| u32::try_from_slice(cursor).expect("ERR_CURSOR"))
+        .unwrap_or(0);
+    let end = (start + ENTRIES_PER_CHUNK).min(ENTRIES_COUNT);
+    for index in start..end {
+        io.write_storage(&entry_key(index), &index.to_le_bytes());
+    }
+    if end == ENTRIES_COUNT {
+        StepStatus::Done
+    } else {
+        StepStatus::Pending(end.try_to_vec().expect("ERR_SER"))
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn state_migration() {
     let mut io = Runtime;
-    let old_state = match engine::get_state(&io) {
-        Ok(state) => state,
+    match migration::state_migration(&mut io, &Runtime, &migrations()) {
+        Ok(outcome) => io.return_output(&outcome.try_to_vec().expect("ERR_SER")),
         Err(e) => aurora_engine_sdk::panic_utf8(e.as_ref()),
-    };
+    }
+}
 
-    let new_state = NewFancyState {
-        old_state,
-        some_other_numbers: [3, 1, 4, 1, 5, 9, 2],
-    };
+// The engine only accepts upgrades keeping the methods it relies on.
+#[no_mangle]
+pub extern "C" fn new() {
+    aurora_engine_sdk::panic_utf8(b"ERR_NOT_SUPPORTED")
+}
 
-    io.write_storage(&state_key(), &new_state.try_to_vec().expect("ERR_SER"));
+#[no_mangle]
+pub extern "C" fn submit() {
+    aurora_engine_sdk::panic_utf8(b"ERR_NOT_SUPPORTED")
+}
+
+#[no_mangle]
+pub extern "C" fn get_state_version() {
+    let mut io = Runtime;
+    let version = migration::get_version(&io);
+    io.return_output(&version.try_to_vec().expect("ERR_SER"));
 }
 
 #[no_mangle]
 pub extern "C" fn some_new_fancy_function() {
     let mut io = Runtime;
-    let state = io.read_storage(&state_key())
+    let state = io
+        .read_storage(&fancy_state_key())
         .and_then(|bytes| NewFancyState::try_from_slice(&bytes.to_vec()).ok())
         .unwrap();
 
     io.return_output(&state.some_other_numbers.try_to_vec().unwrap());
 }
 
-fn state_key() -> Vec<u8> {
-    storage::bytes_to_key(storage::KeyPrefix::Config, b"STATE")
+#[no_mangle]
+pub extern "C" fn count_entries() {
+    let mut io = Runtime;
+    let count = (0..ENTRIES_COUNT)
+        .filter(|index| io.storage_has_key(&entry_key(*index)))
+        .count() as u32;
+    io.return_output(&count.try_to_vec().unwrap());
+}
+
+fn fancy_state_key() -> Vec<u8> {
+    storage::bytes_to_key(storage::KeyPrefix::Config, b"FANCY_STATE")
+}
+
+fn entry_key(index: u32) -> Vec<u8> {
+    let mut key = b"ENTRY".to_vec();
+    key.extend_from_slice(&index.to_le_bytes());
+    storage::bytes_to_key(storage::KeyPrefix::Config, &key)
 }